@@ -1,4 +1,4 @@
-use crate::{execution::{Input, Program, StdStruct, Value}, ir::{Literal, VariableData}};
+use crate::{execution::{ErrorKind, Input, Program, StdStruct, Value}, ir::{Literal, VariableData}};
 use std::sync::Arc;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -15,12 +15,12 @@ impl StdStruct for Bool {
         "bool".to_string()
     }
 
-    fn from_data(&mut self, _program: &mut Program, args: Vec<VariableData>) -> Result<(), String> {
+    fn from_data(&mut self, program: &mut Program, args: Vec<VariableData>) -> Result<(), String> {
         if args.len() != 1 {
-            return Err("Missing boolean value: value".to_string());
+            Err("Missing boolean value: value".to_string())
         }
         else if args.len() > 1 {
-            return Err("Too many arguments: value".to_string());
+            Err("Too many arguments: value".to_string())
         }
         else {
             let arg = args[0].clone();
@@ -29,17 +29,17 @@ impl StdStruct for Bool {
                     self.value = value;
                     Ok(())
                 }
-                _ => Err("Invalid argument: value".to_string()),
+                _ => Err(program.fail(ErrorKind::Type, "Invalid argument: value")),
             }
         }
     }
     
-    fn from_value(&mut self, _program: &mut Program, args: Vec<Value>) -> Result<(), String> {
+    fn from_value(&mut self, program: &mut Program, args: Vec<Value>) -> Result<(), String> {
         if args.len() != 1 {
-            return Err("Missing boolean value: value".to_string());
+            Err("Missing boolean value: value".to_string())
         }
         else if args.len() > 1 {
-            return Err("Too many arguments: value".to_string());
+            Err("Too many arguments: value".to_string())
         }
         else {
             let arg = args[0].clone();
@@ -49,100 +49,104 @@ impl StdStruct for Bool {
                     self.value = bool_value;
                     Ok(())
                 } else {
-                    Err("Invalid boolean value".to_string())
+                    Err(program.fail(ErrorKind::Type, "Invalid boolean value"))
                 }
             } else {
-                Err("Invalid argument type: expected bool".to_string())
+                Err(program.fail(ErrorKind::Type, "Invalid argument type: expected bool"))
             }
         }
     }
 
     fn get_value(&self) -> serde_json::Value {
-        return serde_json::Value::Bool(self.value);
+        serde_json::Value::Bool(self.value)
     }
     fn add(&self, _program: &mut Program, _other: Value) -> Result<Value, String> {
-        return Ok(Value::StdStruct(Arc::new(Bool { value: self.value && _other.get_value().as_bool().unwrap() })));
+        Ok(Value::StdStruct(Arc::new(Bool { value: self.value && _other.get_value().as_bool().unwrap() })))
     }
     fn sub(&self, _program: &mut Program, _other: Value) -> Result<Value, String> {
-        return Ok(Value::StdStruct(Arc::new(Bool { value: self.value || _other.get_value().as_bool().unwrap() })));
+        Ok(Value::StdStruct(Arc::new(Bool { value: self.value || _other.get_value().as_bool().unwrap() })))
     }
 
-    fn mul(&self, _program: &mut Program, _other: Value) -> Result<Value, String> {
-        Err("Multiplication not supported for booleans".to_string())
+    fn mul(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, "Multiplication not supported for booleans"))
     }
 
-    fn div(&self, _program: &mut Program, _other: Value) -> Result<Value, String> {
-        Err("Division not supported for booleans".to_string())
+    fn div(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, "Division not supported for booleans"))
     }
 
-    fn modulo(&self, _program: &mut Program, _other: Value) -> Result<Value, String> {
-        Err("Modulo not supported for booleans".to_string())
+    fn modulo(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, "Modulo not supported for booleans"))
     }
 
-    fn eq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn eq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "bool" {
             let other_bool = other.get_value().as_bool().unwrap();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.value == other_bool })))
         }
         else{
-            Err("Invalid argument: bool".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: bool"))
         }
     }
 
-    fn neq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn neq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "bool" {
             let other_bool = other.get_value().as_bool().unwrap();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.value != other_bool })))
         }
         else{
-            Err("Invalid argument: bool".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: bool"))
         }
     }
 
-    fn less(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn less(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "bool" {
             let other_bool = other.get_value().as_bool().unwrap();
             // false < true
             Ok(Value::StdStruct(Arc::new(Bool { value: !self.value && other_bool })))
         }
         else{
-            Err("Invalid argument: bool".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: bool"))
         }
     }
 
-    fn less_eq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn less_eq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "bool" {
             let other_bool = other.get_value().as_bool().unwrap();
             // false <= true, false <= false, true <= true
             Ok(Value::StdStruct(Arc::new(Bool { value: !self.value || other_bool })))
         }
         else{
-            Err("Invalid argument: bool".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: bool"))
         }
     }
 
-    fn greater(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn greater(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "bool" {
             let other_bool = other.get_value().as_bool().unwrap();
             // true > false
             Ok(Value::StdStruct(Arc::new(Bool { value: self.value && !other_bool })))
         }
         else{
-            Err("Invalid argument: bool".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: bool"))
         }
     }
 
-    fn greater_eq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn greater_eq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "bool" {
             let other_bool = other.get_value().as_bool().unwrap();
             // true >= true, true >= false, false >= false
             Ok(Value::StdStruct(Arc::new(Bool { value: self.value || !other_bool })))
         }
         else{
-            Err("Invalid argument: bool".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: bool"))
         }
     }
 
+    fn default_value(&self) -> Option<Arc<dyn StdStruct>> {
+        Some(Arc::new(Bool::new_default()))
+    }
+
     fn new_default() -> Self where Self: Sized {
         Bool {
             value: false,
@@ -154,4 +158,10 @@ impl StdStruct for Bool {
         new_bool.from_data(program, vec![value])?;
         Ok(Arc::new(new_bool))
     }
+
+    fn construct(&self, program: &mut Program, args: Vec<Value>) -> Result<Arc<dyn StdStruct>, String> {
+        let mut new_bool = Bool::new_default();
+        new_bool.from_value(program, args)?;
+        Ok(Arc::new(new_bool))
+    }
 } 
\ No newline at end of file
@@ -1,4 +1,4 @@
-use crate::{execution::{Input, Program, StdStruct, Value}, ir::{Literal, VariableData}};
+use crate::{execution::{ErrorKind, Input, Program, StdStruct, Value}, ir::{Literal, VariableData}};
 use std::sync::Arc;
 use crate::builtin::{Bool, StringType};
 
@@ -16,12 +16,12 @@ impl StdStruct for Char {
         "char".to_string()
     }
 
-    fn from_data(&mut self, _program: &mut Program, args: Vec<VariableData>) -> Result<(), String> {
+    fn from_data(&mut self, program: &mut Program, args: Vec<VariableData>) -> Result<(), String> {
         if args.len() != 1 {
-            return Err("Missing char value: value".to_string());
+            Err("Missing char value: value".to_string())
         }
         else if args.len() > 1 {
-            return Err("Too many arguments: value".to_string());
+            Err("Too many arguments: value".to_string())
         }
         else {
             let arg = args[0].clone();
@@ -30,17 +30,17 @@ impl StdStruct for Char {
                     self.value = value;
                     Ok(())
                 }
-                _ => Err("Invalid argument: value".to_string()),
+                _ => Err(program.fail(ErrorKind::Type, "Invalid argument: value")),
             }
         }
     }
     
-    fn from_value(&mut self, _program: &mut Program, args: Vec<Value>) -> Result<(), String> {
+    fn from_value(&mut self, program: &mut Program, args: Vec<Value>) -> Result<(), String> {
         if args.len() != 1 {
-            return Err("Missing char value: value".to_string());
+            Err("Missing char value: value".to_string())
         }
         else if args.len() > 1 {
-            return Err("Too many arguments: value".to_string());
+            Err("Too many arguments: value".to_string())
         }
         else {
             let arg = args[0].clone();
@@ -51,21 +51,21 @@ impl StdStruct for Char {
                         self.value = string.chars().next().unwrap();
                         Ok(())
                     } else {
-                        Err("Invalid char value: expected a single character".to_string())
+                        Err(program.fail(ErrorKind::Type, "Invalid char value: expected a single character"))
                     }
                 } else {
-                    Err("Invalid char value".to_string())
+                    Err(program.fail(ErrorKind::Type, "Invalid char value"))
                 }
             } else {
-                Err("Invalid argument type: expected char".to_string())
+                Err(program.fail(ErrorKind::Type, "Invalid argument type: expected char"))
             }
         }
     }
 
     fn get_value(&self) -> serde_json::Value {
-        return serde_json::Value::String(self.value.to_string());
+        serde_json::Value::String(self.value.to_string())
     }
-    fn add(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn add(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "char" {
             let value = other.get_value();
             let other_str = value.as_str().unwrap();
@@ -73,94 +73,98 @@ impl StdStruct for Char {
             Ok(Value::StdStruct(Arc::new(StringType { value: format!("{}{}", self.value, other_char) })))
         }
         else{
-            Err("Invalid argument: char".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: char"))
         }
     }
-    fn sub(&self, _program: &mut Program, _other: Value) -> Result<Value, String> {
-        Err("Subtraction not supported for chars".to_string())
+    fn sub(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, "Subtraction not supported for chars"))
     }
 
-    fn mul(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn mul(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "int" {
             let count = other.get_value().as_i64().unwrap();
             if count < 0 {
-                return Err("Cannot multiply char by negative number".to_string());
+                return Err(program.fail(ErrorKind::Type, "Cannot multiply char by negative number"));
             }
             Ok(Value::StdStruct(Arc::new(StringType { value: self.value.to_string().repeat(count as usize) })))
         }
         else{
-            Err("Invalid argument: expected integer for char multiplication".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: expected integer for char multiplication"))
         }
     }
 
-    fn div(&self, _program: &mut Program, _other: Value) -> Result<Value, String> {
-        Err("Division not supported for chars".to_string())
+    fn div(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, "Division not supported for chars"))
     }
 
-    fn modulo(&self, _program: &mut Program, _other: Value) -> Result<Value, String> {
-        Err("Modulo not supported for chars".to_string())
+    fn modulo(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, "Modulo not supported for chars"))
     }
 
-    fn eq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn eq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "char" {
             let other_char = other.get_value().as_str().unwrap().chars().next().unwrap();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.value == other_char })))
         }
         else{
-            Err("Invalid argument: char".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: char"))
         }
     }
 
-    fn neq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn neq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "char" {
             let other_char = other.get_value().as_str().unwrap().chars().next().unwrap();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.value != other_char })))
         }
         else{
-            Err("Invalid argument: char".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: char"))
         }
     }
 
-    fn less(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn less(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "char" {
             let other_char = other.get_value().as_str().unwrap().chars().next().unwrap();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.value < other_char })))
         }
         else{
-            Err("Invalid argument: char".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: char"))
         }
     }
 
-    fn less_eq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn less_eq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "char" {
             let other_char = other.get_value().as_str().unwrap().chars().next().unwrap();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.value <= other_char })))
         }
         else{
-            Err("Invalid argument: char".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: char"))
         }
     }
 
-    fn greater(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn greater(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "char" {
             let other_char = other.get_value().as_str().unwrap().chars().next().unwrap();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.value > other_char })))
         }
         else{
-            Err("Invalid argument: char".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: char"))
         }
     }
 
-    fn greater_eq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn greater_eq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "char" {
             let other_char = other.get_value().as_str().unwrap().chars().next().unwrap();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.value >= other_char })))
         }
         else{
-            Err("Invalid argument: char".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: char"))
         }
     }
 
+    fn default_value(&self) -> Option<Arc<dyn StdStruct>> {
+        Some(Arc::new(Char::new_default()))
+    }
+
     fn new_default() -> Self where Self: Sized {
         Char {
             value: '\0',
@@ -172,4 +176,10 @@ impl StdStruct for Char {
         new_char.from_data(program, vec![value])?;
         Ok(Arc::new(new_char))
     }
+
+    fn construct(&self, program: &mut Program, args: Vec<Value>) -> Result<Arc<dyn StdStruct>, String> {
+        let mut new_char = Char::new_default();
+        new_char.from_value(program, args)?;
+        Ok(Arc::new(new_char))
+    }
 } 
\ No newline at end of file
@@ -1,4 +1,4 @@
-use crate::{execution::{Input, Program, StdStruct, Value}, ir::{Literal, VariableData}};
+use crate::{execution::{ErrorKind, Input, Program, StdStruct, Value}, ir::{Literal, VariableData}};
 use std::sync::Arc;
 use crate::builtin::Bool;
 
@@ -16,12 +16,12 @@ impl StdStruct for Float {
         "float".to_string()
     }
 
-    fn from_data(&mut self, _program: &mut Program, args: Vec<VariableData>) -> Result<(), String> {
+    fn from_data(&mut self, program: &mut Program, args: Vec<VariableData>) -> Result<(), String> {
         if args.len() != 1 {
-            return Err("Missing float value: number".to_string());
+            Err("Missing float value: number".to_string())
         }
         else if args.len() > 1 {
-            return Err("Too many arguments: number".to_string());
+            Err("Too many arguments: number".to_string())
         }
         else {
             let arg = args[0].clone();
@@ -30,17 +30,17 @@ impl StdStruct for Float {
                     self.number = number;
                     Ok(())
                 }
-                _ => Err("Invalid argument: number".to_string()),
+                _ => Err(program.fail(ErrorKind::Type, "Invalid argument: number")),
             }
         }
     }
     
-    fn from_value(&mut self, _program: &mut Program, args: Vec<Value>) -> Result<(), String> {
+    fn from_value(&mut self, program: &mut Program, args: Vec<Value>) -> Result<(), String> {
         if args.len() != 1 {
-            return Err("Missing float value: number".to_string());
+            Err("Missing float value: number".to_string())
         }
         else if args.len() > 1 {
-            return Err("Too many arguments: number".to_string());
+            Err("Too many arguments: number".to_string())
         }
         else {
             let arg = args[0].clone();
@@ -50,132 +50,136 @@ impl StdStruct for Float {
                     self.number = number;
                     Ok(())
                 } else {
-                    Err("Invalid float value".to_string())
+                    Err(program.fail(ErrorKind::Type, "Invalid float value"))
                 }
             } else {
-                Err("Invalid argument type: expected float".to_string())
+                Err(program.fail(ErrorKind::Type, "Invalid argument type: expected float"))
             }
         }
     }
 
     fn get_value(&self) -> serde_json::Value {
-        return serde_json::Value::Number(serde_json::Number::from_f64(self.number).unwrap());
+        serde_json::Value::Number(serde_json::Number::from_f64(self.number).unwrap())
     }
-    fn add(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn add(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "float" {
             let other_float = other.get_value().as_f64().unwrap();
             Ok(Value::StdStruct(Arc::new(Float { number: self.number + other_float })))
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
-    fn sub(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn sub(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "float" {
             let other_float = other.get_value().as_f64().unwrap();
             Ok(Value::StdStruct(Arc::new(Float { number: self.number - other_float })))
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn mul(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn mul(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "float" {
             let other_float = other.get_value().as_f64().unwrap();
             Ok(Value::StdStruct(Arc::new(Float { number: self.number * other_float })))
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn div(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn div(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "float" {
             let other_float = other.get_value().as_f64().unwrap();
             if other_float == 0.0 {
-                return Err("Division by zero".to_string());
+                return Err(program.fail(ErrorKind::Arithmetic, "Division by zero"));
             }
             Ok(Value::StdStruct(Arc::new(Float { number: self.number / other_float })))
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn modulo(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn modulo(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "float" {
             let other_float = other.get_value().as_f64().unwrap();
             if other_float == 0.0 {
-                return Err("Modulo by zero".to_string());
+                return Err(program.fail(ErrorKind::Arithmetic, "Modulo by zero"));
             }
             Ok(Value::StdStruct(Arc::new(Float { number: self.number % other_float })))
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn eq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn eq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "float" {
             let other_float = other.get_value().as_f64().unwrap();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.number == other_float })))
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn neq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn neq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "float" {
             let other_float = other.get_value().as_f64().unwrap();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.number != other_float })))
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn less(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn less(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "float" {
             let other_float = other.get_value().as_f64().unwrap();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.number < other_float })))
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn less_eq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn less_eq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "float" {
             let other_float = other.get_value().as_f64().unwrap();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.number <= other_float })))
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn greater(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn greater(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "float" {
             let other_float = other.get_value().as_f64().unwrap();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.number > other_float })))
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn greater_eq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn greater_eq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "float" {
             let other_float = other.get_value().as_f64().unwrap();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.number >= other_float })))
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
+    fn default_value(&self) -> Option<Arc<dyn StdStruct>> {
+        Some(Arc::new(Float::new_default()))
+    }
+
     fn new_default() -> Self where Self: Sized {
         Float {
             number: 0.0,
@@ -187,4 +191,10 @@ impl StdStruct for Float {
         new_float.from_data(program, vec![value])?;
         Ok(Arc::new(new_float))
     }
+
+    fn construct(&self, program: &mut Program, args: Vec<Value>) -> Result<Arc<dyn StdStruct>, String> {
+        let mut new_float = Float::new_default();
+        new_float.from_value(program, args)?;
+        Ok(Arc::new(new_float))
+    }
 } 
\ No newline at end of file
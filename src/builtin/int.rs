@@ -1,4 +1,4 @@
-use crate::{execution::{Input, Program, StdStruct, Value}, ir::{Literal, VariableData}};
+use crate::{execution::{ErrorKind, Input, Program, StdStruct, Value}, ir::{Literal, VariableData}};
 use std::sync::Arc;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -6,6 +6,28 @@ pub struct Int {
     pub number: i64,
 }
 
+impl Int {
+    /// Validates the right side of a shift, which has to fit the 64 bits of an int
+    fn shift_amount(program: &Program, other: &Value) -> Result<u32, String> {
+        if other.get_name() != "int" {
+            return Err(program.fail(ErrorKind::Type, "Invalid argument: number"));
+        }
+        let amount = other.get_value().as_i64().unwrap();
+        if !(0..64).contains(&amount) {
+            return Err(program.fail(ErrorKind::Arithmetic, format!("Shift amount {} is out of range, it must be between 0 and 63", amount)));
+        }
+        Ok(amount as u32)
+    }
+
+    /// Wraps the result of a checked operation, failing when it doesn't fit the 64 bits of an int
+    fn checked(program: &Program, result: Option<i64>, left: i64, operator: &str, right: i64) -> Result<Value, String> {
+        match result {
+            Some(number) => Ok(Value::StdStruct(Arc::new(Int { number }))),
+            None => Err(program.fail(ErrorKind::Arithmetic, format!("Integer overflow in {} {} {}", left, operator, right))),
+        }
+    }
+}
+
 impl StdStruct for Int {
     fn get_fields(&self) -> Vec<Input> {
         vec![Input { name: "val".to_string(), value: Value::StdStruct(Arc::new(Int { number: self.number })) }]
@@ -15,12 +37,12 @@ impl StdStruct for Int {
         "int".to_string()
     }
 
-    fn from_data(&mut self, _program: &mut Program, args: Vec<VariableData>) -> Result<(), String> {
+    fn from_data(&mut self, program: &mut Program, args: Vec<VariableData>) -> Result<(), String> {
         if args.len() != 1 {
-            return Err("Missing integer value: number".to_string());
+            Err("Missing integer value: number".to_string())
         }
         else if args.len() > 1 {
-            return Err("Too many arguments: number".to_string());
+            Err("Too many arguments: number".to_string())
         }
         else {
             let arg = args[0].clone();
@@ -29,17 +51,17 @@ impl StdStruct for Int {
                     self.number = number;
                     Ok(())
                 }
-                _ => Err("Invalid argument: number".to_string()),
+                _ => Err(program.fail(ErrorKind::Type, "Invalid argument: number")),
             }
         }
     }
     
-    fn from_value(&mut self, _program: &mut Program, args: Vec<Value>) -> Result<(), String> {
+    fn from_value(&mut self, program: &mut Program, args: Vec<Value>) -> Result<(), String> {
         if args.len() != 1 {
-            return Err("Missing integer value: number".to_string());
+            Err("Missing integer value: number".to_string())
         }
         else if args.len() > 1 {
-            return Err("Too many arguments: number".to_string());
+            Err("Too many arguments: number".to_string())
         }
         else {
             let arg = args[0].clone();
@@ -49,132 +71,180 @@ impl StdStruct for Int {
                     self.number = number;
                     Ok(())
                 } else {
-                    Err("Invalid integer value".to_string())
+                    Err(program.fail(ErrorKind::Type, "Invalid integer value"))
                 }
             } else {
-                Err("Invalid argument type: expected int".to_string())
+                Err(program.fail(ErrorKind::Type, "Invalid argument type: expected int"))
             }
         }
     }
 
     fn get_value(&self) -> serde_json::Value {
-        return serde_json::Value::Number(self.number.into());
+        serde_json::Value::Number(self.number.into())
     }
-    fn add(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn add(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "int" {
             let other_int = other.get_value().as_i64().unwrap();
-            Ok(Value::StdStruct(Arc::new(Int { number: self.number + other_int })))
+            Int::checked(program, self.number.checked_add(other_int), self.number, "+", other_int)
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
-    fn sub(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn sub(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "int" {
             let other_int = other.get_value().as_i64().unwrap();
-            Ok(Value::StdStruct(Arc::new(Int { number: self.number - other_int })))
+            Int::checked(program, self.number.checked_sub(other_int), self.number, "-", other_int)
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn mul(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn mul(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "int" {
             let other_int = other.get_value().as_i64().unwrap();
-            Ok(Value::StdStruct(Arc::new(Int { number: self.number * other_int })))
+            Int::checked(program, self.number.checked_mul(other_int), self.number, "*", other_int)
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn div(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn div(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "int" {
             let other_int = other.get_value().as_i64().unwrap();
             if other_int == 0 {
-                return Err("Division by zero".to_string());
+                return Err(program.fail(ErrorKind::Arithmetic, "Division by zero"));
             }
-            Ok(Value::StdStruct(Arc::new(Int { number: self.number / other_int })))
+            Int::checked(program, self.number.checked_div(other_int), self.number, "/", other_int)
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn modulo(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn modulo(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "int" {
             let other_int = other.get_value().as_i64().unwrap();
             if other_int == 0 {
-                return Err("Modulo by zero".to_string());
+                return Err(program.fail(ErrorKind::Arithmetic, "Modulo by zero"));
             }
-            Ok(Value::StdStruct(Arc::new(Int { number: self.number % other_int })))
+            Int::checked(program, self.number.checked_rem(other_int), self.number, "%", other_int)
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn eq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn bitwise_and(&self, program: &mut Program, other: Value) -> Result<Value, String> {
+        if other.get_name() == "int" {
+            let other_int = other.get_value().as_i64().unwrap();
+            Ok(Value::StdStruct(Arc::new(Int { number: self.number & other_int })))
+        }
+        else{
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
+        }
+    }
+
+    fn bitwise_or(&self, program: &mut Program, other: Value) -> Result<Value, String> {
+        if other.get_name() == "int" {
+            let other_int = other.get_value().as_i64().unwrap();
+            Ok(Value::StdStruct(Arc::new(Int { number: self.number | other_int })))
+        }
+        else{
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
+        }
+    }
+
+    fn bitwise_xor(&self, program: &mut Program, other: Value) -> Result<Value, String> {
+        if other.get_name() == "int" {
+            let other_int = other.get_value().as_i64().unwrap();
+            Ok(Value::StdStruct(Arc::new(Int { number: self.number ^ other_int })))
+        }
+        else{
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
+        }
+    }
+
+    fn shift_left(&self, program: &mut Program, other: Value) -> Result<Value, String> {
+        let amount = Int::shift_amount(program, &other)?;
+        Ok(Value::StdStruct(Arc::new(Int { number: self.number << amount })))
+    }
+
+    fn shift_right(&self, program: &mut Program, other: Value) -> Result<Value, String> {
+        let amount = Int::shift_amount(program, &other)?;
+        Ok(Value::StdStruct(Arc::new(Int { number: self.number >> amount })))
+    }
+
+    fn bitwise_not(&self, _program: &mut Program) -> Result<Value, String> {
+        Ok(Value::StdStruct(Arc::new(Int { number: !self.number })))
+    }
+
+    fn eq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "int" {
             let other_int = other.get_value().as_i64().unwrap();
             Ok(Value::StdStruct(Arc::new(Int { number: if self.number == other_int { 1 } else { 0 } })))
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn neq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn neq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "int" {
             let other_int = other.get_value().as_i64().unwrap();
             Ok(Value::StdStruct(Arc::new(Int { number: if self.number != other_int { 1 } else { 0 } })))
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn less(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn less(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "int" {
             let other_int = other.get_value().as_i64().unwrap();
             Ok(Value::StdStruct(Arc::new(Int { number: if self.number < other_int { 1 } else { 0 } })))
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn less_eq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn less_eq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "int" {
             let other_int = other.get_value().as_i64().unwrap();
             Ok(Value::StdStruct(Arc::new(Int { number: if self.number <= other_int { 1 } else { 0 } })))
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn greater(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn greater(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "int" {
             let other_int = other.get_value().as_i64().unwrap();
             Ok(Value::StdStruct(Arc::new(Int { number: if self.number > other_int { 1 } else { 0 } })))
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
-    fn greater_eq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn greater_eq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "int" {
             let other_int = other.get_value().as_i64().unwrap();
             Ok(Value::StdStruct(Arc::new(Int { number: if self.number >= other_int { 1 } else { 0 } })))
         }
         else{
-            Err("Invalid argument: number".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: number"))
         }
     }
 
+    fn default_value(&self) -> Option<Arc<dyn StdStruct>> {
+        Some(Arc::new(Int::new_default()))
+    }
+
     fn new_default() -> Self where Self: Sized {
         Int {
             number: 0,
@@ -186,4 +256,10 @@ impl StdStruct for Int {
         new_int.from_data(program, vec![value])?;
         Ok(Arc::new(new_int))
     }
+
+    fn construct(&self, program: &mut Program, args: Vec<Value>) -> Result<Arc<dyn StdStruct>, String> {
+        let mut new_int = Int::new_default();
+        new_int.from_value(program, args)?;
+        Ok(Arc::new(new_int))
+    }
 } 
\ No newline at end of file
@@ -3,7 +3,10 @@ mod float;
 mod string;
 mod char;
 mod bool;
+mod range;
 mod to_string;
+mod sort;
+mod parse_int;
 
 use std::sync::Arc;
 
@@ -17,7 +20,11 @@ pub use string::StringType;
 pub use char::Char;
 #[allow(unused_imports)]
 pub use bool::Bool;
+#[allow(unused_imports)]
+pub use range::Range;
 use to_string::ToString;
+use sort::Sort;
+use parse_int::ParseInt;
 
 use crate::execution::{StdFunction, StdStruct};
 
@@ -28,12 +35,21 @@ pub fn get_std_lib() -> Vec<Arc<dyn StdStruct>> {
         Arc::new(StringType { value: "".to_string() }),
         Arc::new(Char { value: ' ' }),
         Arc::new(Bool { value: false }),
+        Arc::new(Range { start: 0, end: 0, step: 1, inclusive: false }),
     ]
 }
 
 pub fn get_std_functions() -> Vec<Arc<dyn StdFunction>> {
     vec![
         Arc::new(ToString::new()),
+        Arc::new(Sort::new()),
+        Arc::new(ParseInt::new()),
     ]
 }
 
+/// The names of the builtin types and functions, which scripts can use without declaring them
+pub fn get_std_names() -> Vec<String> {
+    get_std_lib().iter().map(|std_struct| std_struct.get_name())
+        .chain(get_std_functions().iter().map(|std_function| std_function.get_name()))
+        .collect()
+}
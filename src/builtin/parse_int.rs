@@ -0,0 +1,40 @@
+use crate::execution::{ErrorKind, Input, Program, StdFunction, Value};
+use crate::ir::{Literal, VariableData};
+
+/// `parseInt(text)` returns `Result::Ok` with the integer, or `Result::Err` with a message
+/// when the text isn't one, so scripts can handle bad input with `match` or `?`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseInt {
+
+}
+
+impl StdFunction for ParseInt {
+    fn run(&self, program: &mut Program, args: Vec<Value>) -> Result<Value, String> {
+        if args.len() != 1 {
+            return Err(program.fail(ErrorKind::Type, "Invalid number of arguments"));
+        }
+        let text = match args[0].get_value() {
+            serde_json::Value::String(text) => text,
+            _ => return Err(format!("parseInt needs a string, found {}", args[0].get_name())),
+        };
+        let int = match program.get_value(String::from("int")) {
+            Value::StdStruct(int) => int,
+            _ => return Err("Int not found".to_string()),
+        };
+        let parsed = text.trim().parse::<i64>()
+            .map_err(|_| format!("'{}' is not an integer", text))
+            .and_then(|number| int.clone_with_value(program, VariableData::Literal(Literal::Integer(number))))
+            .map(Value::StdStruct);
+        program.recoverable(parsed)
+    }
+    fn get_name(&self) -> String {
+        "parseInt".to_string()
+    }
+    fn get_parameters(&self, program: &mut Program) -> Vec<Input> {
+        let string = program.get_value(String::from("string"));
+        vec![Input { name: "text".to_string(), value: string }]
+    }
+    fn new() -> Self {
+        Self {}
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{execution::{ErrorKind, Input, Program, StdStruct, Value}, ir::{Literal, VariableData}};
+use std::sync::Arc;
+use crate::builtin::{Bool, Int};
+
+/// The integers from `start` up to `end` (or down to it with a negative step), counting by `step`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range {
+    pub start: i64,
+    pub end: i64,
+    pub step: i64,
+    pub inclusive: bool,
+}
+
+impl Range {
+    fn set(&mut self, program: &Program, start: i64, end: i64, step: i64, inclusive: bool) -> Result<(), String> {
+        if step == 0 {
+            return Err(program.fail(ErrorKind::Arithmetic, "Range step cannot be zero"));
+        }
+        self.start = start;
+        self.end = end;
+        self.step = step;
+        self.inclusive = inclusive;
+        Ok(())
+    }
+
+    fn int(value: i64) -> Value {
+        Value::StdStruct(Arc::new(Int { number: value }))
+    }
+}
+
+impl StdStruct for Range {
+    fn get_fields(&self) -> Vec<Input> {
+        vec![
+            Input { name: "start".to_string(), value: Range::int(self.start) },
+            Input { name: "end".to_string(), value: Range::int(self.end) },
+            Input { name: "step".to_string(), value: Range::int(self.step) },
+        ]
+    }
+
+    fn get_name(&self) -> String {
+        "range".to_string()
+    }
+
+    fn from_data(&mut self, program: &mut Program, args: Vec<VariableData>) -> Result<(), String> {
+        let mut numbers = Vec::new();
+        let mut inclusive = false;
+        for arg in args.iter() {
+            match arg {
+                VariableData::Literal(Literal::Integer(number)) => numbers.push(*number),
+                VariableData::Literal(Literal::Boolean(value)) => inclusive = *value,
+                _ => return Err(program.fail(ErrorKind::Type, "Invalid argument: range bounds must be int")),
+            }
+        }
+        match numbers.as_slice() {
+            [start, end] => self.set(program, *start, *end, 1, inclusive),
+            [start, end, step] => self.set(program, *start, *end, *step, inclusive),
+            _ => Err(program.fail(ErrorKind::Type, "Invalid arguments: range(start, end, step?, inclusive?)")),
+        }
+    }
+
+    fn from_value(&mut self, program: &mut Program, args: Vec<Value>) -> Result<(), String> {
+        let mut numbers = Vec::new();
+        let mut inclusive = false;
+        for arg in args.iter() {
+            match arg.get_name().as_str() {
+                "int" => numbers.push(arg.get_value().as_i64().unwrap()),
+                "bool" => inclusive = arg.get_value().as_bool().unwrap(),
+                other => return Err(program.fail(ErrorKind::Type, format!("Invalid argument type: range bounds must be int, found {}", other))),
+            }
+        }
+        match numbers.as_slice() {
+            [start, end] => self.set(program, *start, *end, 1, inclusive),
+            [start, end, step] => self.set(program, *start, *end, *step, inclusive),
+            _ => Err(program.fail(ErrorKind::Type, "Invalid arguments: range(start, end, step?, inclusive?)")),
+        }
+    }
+
+    fn get_value(&self) -> serde_json::Value {
+        serde_json::json!({
+            "start": self.start,
+            "end": self.end,
+            "step": self.step,
+            "inclusive": self.inclusive,
+        })
+    }
+
+    fn nth(&self, _program: &mut Program, index: usize) -> Result<Option<Value>, String> {
+        let value = match (index as i64).checked_mul(self.step).and_then(|offset| self.start.checked_add(offset)) {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        let within = match (self.step > 0, self.inclusive) {
+            (true, false) => value < self.end,
+            (true, true) => value <= self.end,
+            (false, false) => value > self.end,
+            (false, true) => value >= self.end,
+        };
+        Ok(if within { Some(Range::int(value)) } else { None })
+    }
+
+    fn add(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, "Addition not supported for ranges"))
+    }
+
+    fn sub(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, "Subtraction not supported for ranges"))
+    }
+
+    fn mul(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, "Multiplication not supported for ranges"))
+    }
+
+    fn div(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, "Division not supported for ranges"))
+    }
+
+    fn modulo(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, "Modulo not supported for ranges"))
+    }
+
+    fn eq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
+        if other.get_name() == "range" {
+            Ok(Value::StdStruct(Arc::new(Bool { value: self.get_value() == other.get_value() })))
+        }
+        else{
+            Err(program.fail(ErrorKind::Type, "Invalid argument: range"))
+        }
+    }
+
+    fn neq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
+        if other.get_name() == "range" {
+            Ok(Value::StdStruct(Arc::new(Bool { value: self.get_value() != other.get_value() })))
+        }
+        else{
+            Err(program.fail(ErrorKind::Type, "Invalid argument: range"))
+        }
+    }
+
+    fn less(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, "Comparison not supported for ranges"))
+    }
+
+    fn less_eq(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, "Comparison not supported for ranges"))
+    }
+
+    fn greater(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, "Comparison not supported for ranges"))
+    }
+
+    fn greater_eq(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, "Comparison not supported for ranges"))
+    }
+
+    fn new_default() -> Self where Self: Sized {
+        Range {
+            start: 0,
+            end: 0,
+            step: 1,
+            inclusive: false,
+        }
+    }
+
+    fn clone_with_value(&self, program: &mut Program, value: VariableData) -> Result<Arc<dyn StdStruct>, String> {
+        let mut new_range = Range::new_default();
+        new_range.from_data(program, vec![value])?;
+        Ok(Arc::new(new_range))
+    }
+
+    fn construct(&self, program: &mut Program, args: Vec<Value>) -> Result<Arc<dyn StdStruct>, String> {
+        let mut new_range = Range::new_default();
+        new_range.from_value(program, args)?;
+        Ok(Arc::new(new_range))
+    }
+}
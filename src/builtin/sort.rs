@@ -0,0 +1,59 @@
+use crate::execution::{ErrorKind, Input, Program, StdFunction, Value};
+use std::cmp::Ordering;
+
+/// `sort(array, compare)` returns the array sorted by a comparison callback,
+/// which returns a negative number, zero or a positive number like C's `qsort`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sort {
+
+}
+
+impl StdFunction for Sort {
+    fn run(&self, program: &mut Program, args: Vec<Value>) -> Result<Value, String> {
+        if args.len() != 2 {
+            return Err(program.fail(ErrorKind::Type, "Invalid number of arguments"));
+        }
+        let mut array = match &args[0] {
+            Value::Array(array) => array.clone(),
+            other => return Err(program.fail(ErrorKind::Type, format!("Cannot sort {}", other.get_name()))),
+        };
+        let compare = args[1].clone();
+
+        // sort_by can't fail, so the first error from the callback is kept and returned afterwards
+        let mut error = None;
+        array.sort_by(|a, b| {
+            if error.is_some() {
+                return Ordering::Equal;
+            }
+            match program.call_value(&compare, vec![a.clone(), b.clone()]) {
+                Ok(result) => match result.get_value().as_f64() {
+                    Some(order) => order.partial_cmp(&0.0).unwrap_or(Ordering::Equal),
+                    None => {
+                        error = Some(format!("Comparison must return a number, found {}", result.get_name()));
+                        Ordering::Equal
+                    }
+                },
+                Err(err) => {
+                    error = Some(err);
+                    Ordering::Equal
+                }
+            }
+        });
+        match error {
+            Some(err) => Err(err),
+            None => Ok(Value::Array(array)),
+        }
+    }
+    fn get_name(&self) -> String {
+        "sort".to_string()
+    }
+    fn get_parameters(&self, _program: &mut Program) -> Vec<Input> {
+        vec![
+            Input { name: "array".to_string(), value: Value::Array(Vec::new()) },
+            Input { name: "compare".to_string(), value: Value::Null },
+        ]
+    }
+    fn new() -> Self {
+        Self {}
+    }
+}
@@ -1,6 +1,6 @@
-use crate::{execution::{Input, Program, StdStruct, Value}, ir::{Literal, VariableData}};
+use crate::{execution::{ErrorKind, Input, Program, StdStruct, Value}, ir::{Literal, VariableData}};
 use std::sync::Arc;
-use crate::builtin::Bool;
+use crate::builtin::{Bool, Char};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct StringType {
@@ -16,12 +16,12 @@ impl StdStruct for StringType {
         "string".to_string()
     }
 
-    fn from_data(&mut self, _program: &mut Program, args: Vec<VariableData>) -> Result<(), String> {
+    fn from_data(&mut self, program: &mut Program, args: Vec<VariableData>) -> Result<(), String> {
         if args.len() != 1 {
-            return Err("Missing string value: value".to_string());
+            Err("Missing string value: value".to_string())
         }
         else if args.len() > 1 {
-            return Err("Too many arguments: value".to_string());
+            Err("Too many arguments: value".to_string())
         }
         else {
             let arg = args[0].clone();
@@ -30,17 +30,17 @@ impl StdStruct for StringType {
                     self.value = value;
                     Ok(())
                 }
-                _ => Err("Invalid argument: value".to_string()),
+                _ => Err(program.fail(ErrorKind::Type, "Invalid argument: value")),
             }
         }
     }
     
-    fn from_value(&mut self, _program: &mut Program, args: Vec<Value>) -> Result<(), String> {
+    fn from_value(&mut self, program: &mut Program, args: Vec<Value>) -> Result<(), String> {
         if args.len() != 1 {
-            return Err("Missing string value: value".to_string());
+            Err("Missing string value: value".to_string())
         }
         else if args.len() > 1 {
-            return Err("Too many arguments: value".to_string());
+            Err("Too many arguments: value".to_string())
         }
         else {
             let arg = args[0].clone();
@@ -50,116 +50,123 @@ impl StdStruct for StringType {
                     self.value = string.to_string();
                     Ok(())
                 } else {
-                    Err("Invalid string value".to_string())
+                    Err(program.fail(ErrorKind::Type, "Invalid string value"))
                 }
             } else {
-                Err("Invalid argument type: expected string".to_string())
+                Err(program.fail(ErrorKind::Type, "Invalid argument type: expected string"))
             }
         }
     }
 
     fn get_value(&self) -> serde_json::Value {
-        return serde_json::Value::String(self.value.clone());
+        serde_json::Value::String(self.value.clone())
     }
-    fn add(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn nth(&self, _program: &mut Program, index: usize) -> Result<Option<Value>, String> {
+        Ok(self.value.chars().nth(index).map(|value| Value::StdStruct(Arc::new(Char { value }))))
+    }
+    fn add(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "string" {
             let other_string = other.get_value().as_str().unwrap().to_string();
             Ok(Value::StdStruct(Arc::new(StringType { value: format!("{}{}", self.value, other_string) })))
         }
         else{
-            Err("Invalid argument: string".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: string"))
         }
     }
-    fn sub(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn sub(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_value().as_i64().unwrap_or(-1) == 0 {
             return Ok(Value::StdStruct(Arc::new(StringType { value: self.value.clone() })));
         }
-        Err("Subtraction not supported for strings".to_string())
+        Err(program.fail(ErrorKind::Type, "Subtraction not supported for strings"))
     }
 
-    fn mul(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn mul(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "int" {
             let count = other.get_value().as_i64().unwrap();
             if count < 0 {
-                return Err("Cannot multiply string by negative number".to_string());
+                return Err(program.fail(ErrorKind::Type, "Cannot multiply string by negative number"));
             }
             Ok(Value::StdStruct(Arc::new(StringType { value: self.value.repeat(count as usize) })))
         }
         else{
-            Err("Invalid argument: expected integer for string multiplication".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: expected integer for string multiplication"))
         }
     }
 
-    fn div(&self, _program: &mut Program, _other: Value) -> Result<Value, String> {
-        Err("Division not supported for strings".to_string())
+    fn div(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, "Division not supported for strings"))
     }
 
-    fn modulo(&self, _program: &mut Program, _other: Value) -> Result<Value, String> {
-        Err("Modulo not supported for strings".to_string())
+    fn modulo(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, "Modulo not supported for strings"))
     }
 
-    fn eq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn eq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "string" {
             let value = other.get_value();
             let other_string = value.as_str().unwrap().to_string();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.value == other_string })))
         }
         else{
-            Err("Invalid argument: string".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: string"))
         }
     }
 
-    fn neq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn neq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "string" {
             let value = other.get_value();
             let other_string = value.as_str().unwrap().to_string();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.value != other_string })))
         }
         else{
-            Err("Invalid argument: string".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: string"))
         }
     }
 
-    fn less(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn less(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "string" {
             let other_string = other.get_value().as_str().unwrap().to_string();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.value < other_string })))
         }
         else{
-            Err("Invalid argument: string".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: string"))
         }
     }
 
-    fn less_eq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn less_eq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "string" {
             let other_string = other.get_value().as_str().unwrap().to_string();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.value <= other_string })))
         }
         else{
-            Err("Invalid argument: string".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: string"))
         }
     }
 
-    fn greater(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn greater(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "string" {
             let other_string = other.get_value().as_str().unwrap().to_string();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.value > other_string })))
         }
         else{
-            Err("Invalid argument: string".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: string"))
         }
     }
 
-    fn greater_eq(&self, _program: &mut Program, other: Value) -> Result<Value, String> {
+    fn greater_eq(&self, program: &mut Program, other: Value) -> Result<Value, String> {
         if other.get_name() == "string" {
             let other_string = other.get_value().as_str().unwrap().to_string();
             Ok(Value::StdStruct(Arc::new(Bool { value: self.value >= other_string })))
         }
         else{
-            Err("Invalid argument: string".to_string())
+            Err(program.fail(ErrorKind::Type, "Invalid argument: string"))
         }
     }
 
+    fn default_value(&self) -> Option<Arc<dyn StdStruct>> {
+        Some(Arc::new(StringType::new_default()))
+    }
+
     fn new_default() -> Self where Self: Sized {
         StringType {
             value: String::new(),
@@ -171,4 +178,10 @@ impl StdStruct for StringType {
         new_string.from_data(program, vec![value])?;
         Ok(Arc::new(new_string))
     }
+
+    fn construct(&self, program: &mut Program, args: Vec<Value>) -> Result<Arc<dyn StdStruct>, String> {
+        let mut new_string = StringType::new_default();
+        new_string.from_value(program, args)?;
+        Ok(Arc::new(new_string))
+    }
 } 
\ No newline at end of file
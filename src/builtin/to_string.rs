@@ -1,4 +1,4 @@
-use crate::execution::{Input, Program, StdFunction, Value};
+use crate::execution::{ErrorKind, Input, Program, StdFunction, Value};
 use crate::ir::{Literal, VariableData};
 use std::sync::Arc;
 
@@ -10,7 +10,7 @@ pub struct ToString {
 impl StdFunction for ToString {
     fn run(&self, program: &mut Program, args: Vec<Value>) -> Result<Value, String> {
         if args.len() != 1 {
-            return Err("Invalid number of arguments".to_string());
+            return Err(program.fail(ErrorKind::Type, "Invalid number of arguments"));
         }
         let string = program.get_value(String::from("string"));
         match string {
@@ -19,12 +19,12 @@ impl StdFunction for ToString {
                 let string_clone = string.clone();
                 let mut new_struct = (*string_clone).clone();
                 new_struct.value = serde_json::Value::String(string_value);
-                return Ok(Value::Struct(Arc::new(new_struct)));
+                Ok(Value::Struct(Arc::new(new_struct)))
             }
             Value::StdStruct(string) => {
                 let string_value = args[0].get_value().to_string();
                 let result = string.clone_with_value(program, VariableData::Literal(Literal::String(string_value)))?;
-                return Ok(Value::StdStruct(result));
+                Ok(Value::StdStruct(result))
             }
             _ => Err("String not found".to_string()),
         }
@@ -1,21 +1,190 @@
-use crate::ir::IR;
-use super::types::{Value, Function};
-use super::program::Program;
+use std::sync::Arc;
+use crate::ir::{IR, Attribute};
+use super::types::{Value, Function, Closure, Variable, NamedArguments};
+use super::program::{Program, ErrorKind};
+use super::generator::Generator;
 
 impl Function {
     pub fn run(&mut self, program: &mut Program, args: Vec<Value>) -> Result<Value, String> {
+        self.run_with(program, args, Vec::new())
+    }
+
+    /// Runs the function with positional arguments followed by named ones, e.g. `f(1, y: 2)`
+    pub fn run_with(&mut self, program: &mut Program, args: Vec<Value>, named: NamedArguments) -> Result<Value, String> {
+        let arguments = self.bind(program, args, named)?;
+        if self.generator {
+            return Ok(Value::Generator(Generator::new(self.clone(), program.clone(), arguments)));
+        }
+        if !Attribute::has(&self.attributes, "memoize") {
+            return self.call(program, arguments).map(|(value, _)| value);
+        }
+        let key = format!("{}({})", self.name, arguments.iter()
+            .map(|arg| match arg {
+                Some(arg) => format!("{}:{}", arg.get_name(), arg.get_value()),
+                None => "default".to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join(","));
+        if let Some(value) = program.memoized(&key) {
+            return Ok(value);
+        }
+        let (value, _) = self.call(program, arguments)?;
+        program.memoize(key, value.clone());
+        Ok(value)
+    }
+
+    /// Runs the function as a method, also returning `self` as the body left it if it was changed
+    pub fn run_method(&mut self, program: &mut Program, args: Vec<Value>, named: NamedArguments) -> Result<(Value, Option<Value>), String> {
+        let receiver = match self.parameters.first() {
+            Some(parameter) if parameter.name == "self" => args.first().cloned(),
+            _ => None,
+        };
+        let arguments = self.bind(program, args, named)?;
+        let (value, scope) = self.call(program, arguments)?;
+        let updated = scope.variables.get("self").map(|variable| variable.value.clone());
+        match (receiver, updated) {
+            (Some(receiver), Some(updated)) if receiver != updated => Ok((value, Some(updated))),
+            _ => Ok((value, None)),
+        }
+    }
+
+    /// Matches the arguments to the parameters. Parameters left without one take their default value
+    /// when the function is called, and a variadic parameter takes an array of the remaining arguments
+    pub(super) fn bind(&self, program: &Program, args: Vec<Value>, named: NamedArguments) -> Result<Vec<Option<Value>>, String> {
+        let fixed = self.parameters.len() - usize::from(self.variadic);
+        let required = (0..fixed).filter(|&i| self.defaults[i].is_none()).count();
+        if args.len() > fixed && !self.variadic || named.is_empty() && args.len() < required {
+            let expected = if self.variadic {
+                format!("at least {}", required)
+            } else if required == fixed {
+                fixed.to_string()
+            } else {
+                format!("between {} and {}", required, fixed)
+            };
+            return Err(program.fail(ErrorKind::Type, format!("{} expects {} arguments, but {} were provided", self.describe(), expected, args.len())));
+        }
+        let mut arguments: Vec<Option<Value>> = vec![None; fixed];
+        let mut rest = Vec::new();
+        for (i, arg) in args.into_iter().enumerate() {
+            match arguments.get_mut(i) {
+                Some(slot) => *slot = Some(arg),
+                None => rest.push(arg),
+            }
+        }
+        for (name, value) in named {
+            let index = self.parameters.iter().position(|parameter| parameter.name == name)
+                .ok_or_else(|| program.fail(ErrorKind::Name, format!("{} has no parameter named '{}'", self.describe(), name)))?;
+            let slot = arguments.get_mut(index)
+                .ok_or(format!("Variadic parameter '{}' of {} cannot be passed by name", name, self.describe()))?;
+            if slot.is_some() {
+                return Err(format!("Parameter '{}' of {} is given more than once", name, self.describe()));
+            }
+            *slot = Some(value);
+        }
+        for (i, argument) in arguments.iter().enumerate() {
+            if argument.is_none() && self.defaults[i].is_none() {
+                return Err(format!("Missing argument for parameter '{}' of {}", self.parameters[i].name, self.describe()));
+            }
+        }
+        if self.variadic {
+            arguments.push(Some(Value::Array(rest)));
+        }
+        Ok(arguments)
+    }
+
+    /// The function as named in error messages, like `'add'`
+    fn describe(&self) -> String {
+        match self.name.is_empty() {
+            true => "anonymous function".to_string(),
+            false => format!("'{}'", self.name),
+        }
+    }
+
+    /// The name and parameters of the function, as in `area(int width, int height = ...)`
+    pub fn signature(&self) -> String {
+        let parameters: Vec<String> = self.parameters.iter().zip(&self.parameter_types).zip(&self.defaults).enumerate()
+            .map(|(i, ((parameter, typing), default))| match default {
+                _ if self.variadic && i == self.parameters.len() - 1 => {
+                    let mut element = typing.clone();
+                    element.array_dimensions -= 1;
+                    format!("{} ...{}", element, parameter.name)
+                }
+                Some(_) => format!("{} {} = ...", typing, parameter.name),
+                None => format!("{} {}", typing, parameter.name),
+            })
+            .collect();
+        format!("{}({})", self.name, parameters.join(", "))
+    }
+
+    pub(super) fn call(&mut self, program: &mut Program, arguments: Vec<Option<Value>>) -> Result<(Value, Program), String> {
+        if let Some(warning) = Attribute::deprecation(&self.name, &self.attributes) {
+            program.warn(warning);
+        }
         let ir = IR::from_actions(self.body.clone());
         let mut program = program.clone();
-        for arg in args {
-            program.variables.insert(arg.get_name(), super::types::Variable {
-                name: arg.get_name(),
-                value: arg,
+        program.enter_frame(self);
+        // Defaults are evaluated in the function's scope, so they can use the parameters before them
+        for ((parameter, default), arg) in self.parameters.iter().zip(&self.defaults).zip(arguments) {
+            let value = match (arg, default) {
+                (Some(arg), _) => arg,
+                (None, Some(default)) => program.evaluate(default)?,
+                (None, None) => Value::Null,
+            };
+            program.immutable.remove(&parameter.name);
+            program.variables.insert(parameter.name.clone(), Variable {
+                name: parameter.name.clone(),
+                value,
             });
         }
-        let res = program.run(&ir);
-        if res.is_err() {
-            return Err(res.unwrap_err());
+        let value = match program.run(&ir) {
+            Ok(value) => value,
+            // `?` returns the `Err` from this function, rather than failing the whole program
+            Err(err) => match program.take_propagated() {
+                Some(value) => value,
+                None => {
+                    program.unwind();
+                    return Err(err);
+                }
+            },
+        };
+        Ok((value, program))
+    }
+}
+
+impl Closure {
+    /// Runs the function with the captured variables in scope, taking precedence over the caller's
+    pub fn run(&self, program: &mut Program, args: Vec<Value>) -> Result<Value, String> {
+        self.run_with(program, args, Vec::new())
+    }
+
+    pub fn run_with(&self, program: &mut Program, args: Vec<Value>, named: NamedArguments) -> Result<Value, String> {
+        let mut scope = program.clone();
+        let captured = self.captured.lock().map_err(|_| "Captured variables are poisoned".to_string())?.clone();
+        scope.variables.extend(captured.clone());
+        // A named closure can call itself, even though it was created before being assigned
+        let name = self.function.name.clone();
+        if !name.is_empty() {
+            scope.variables.insert(name.clone(), Variable { name, value: Value::Closure(Arc::new(self.clone())) });
+        }
+        let mut function = self.function.clone();
+        if function.generator || Attribute::has(&function.attributes, "memoize") {
+            return function.run_with(&mut scope, args, named);
+        }
+        let arguments = function.bind(&scope, args, named)?;
+        let (value, scope) = function.call(&mut scope, arguments)?;
+        // Captured variables the body assigned keep their new value, unless a parameter shadowed them
+        let mut shared = self.captured.lock().map_err(|_| "Captured variables are poisoned".to_string())?;
+        for (name, before) in captured {
+            if function.parameters.iter().any(|parameter| parameter.name == name) {
+                continue;
+            }
+            match scope.variables.get(&name) {
+                Some(after) if after.value != before.value => {
+                    shared.insert(name, after.clone());
+                }
+                _ => {}
+            }
         }
-        Ok(res.unwrap())
+        Ok(value)
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,117 @@
+use std::sync::{atomic::{AtomicBool, Ordering}, mpsc::{self, Receiver, SyncSender}, Arc, Mutex};
+use std::thread;
+use super::program::Program;
+use super::types::{Function, Value};
+
+// The body of a generator runs on its own thread, which needs as much stack as the interpreter does
+const STACK_SIZE: usize = 8 * 1024 * 1024;
+const DROPPED: &str = "The generator was dropped before it finished";
+
+/// What the body of a generator sends its caller: the next value, None once it returned, or the error it failed with
+type Yielded = Result<Option<Value>, String>;
+
+/// The body's side of a generator, where `yield` waits until the caller asks for the next value
+#[derive(Debug)]
+pub struct Yielder {
+    yielded: SyncSender<Yielded>,
+    resume: Mutex<Receiver<()>>,
+    // set once the caller dropped the generator, after which the body must stop without running any more code
+    dropped: AtomicBool,
+}
+
+impl Yielder {
+    pub fn yield_value(&self, value: Value) -> Result<(), String> {
+        let resumed = self.yielded.send(Ok(Some(value))).is_ok()
+            && self.resume.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).recv().is_ok();
+        if !resumed {
+            self.dropped.store(true, Ordering::SeqCst);
+            return Err(DROPPED.to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether the caller dropped the generator, so the error `yield` returned must not be caught
+    pub fn dropped(&self) -> bool {
+        self.dropped.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Debug)]
+enum State {
+    Created { function: Function, program: Box<Program>, args: Vec<Option<Value>> },
+    Suspended { resume: SyncSender<()>, yielded: Receiver<Yielded> },
+    Finished,
+}
+
+/// A call to a `fn*` function, which runs its body lazily up to the next `yield` every time a value is asked for.
+/// Hosts can use it as an `Iterator` over the yielded values.
+#[derive(Debug, Clone)]
+pub struct Generator {
+    pub name: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl Generator {
+    pub fn new(function: Function, program: Program, args: Vec<Option<Value>>) -> Self {
+        Generator {
+            name: function.name.clone(),
+            state: Arc::new(Mutex::new(State::Created { function, program: Box::new(program), args })),
+        }
+    }
+
+    /// Runs the body until it yields the next value, None once it has finished
+    pub fn resume(&self) -> Option<Result<Value, String>> {
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let (resume, yielded) = match std::mem::replace(&mut *state, State::Finished) {
+            State::Created { function, program, args } => match Self::start(function, *program, args) {
+                Ok(channels) => channels,
+                Err(err) => return Some(Err(err)),
+            },
+            State::Suspended { resume, yielded } => {
+                resume.send(()).ok()?;
+                (resume, yielded)
+            }
+            State::Finished => return None,
+        };
+        match yielded.recv() {
+            Ok(Ok(Some(value))) => {
+                *state = State::Suspended { resume, yielded };
+                Some(Ok(value))
+            }
+            Ok(Err(err)) => Some(Err(err)),
+            Ok(Ok(None)) | Err(_) => None,
+        }
+    }
+
+    fn start(mut function: Function, mut program: Program, args: Vec<Option<Value>>) -> Result<(SyncSender<()>, Receiver<Yielded>), String> {
+        let (resume, resumed) = mpsc::sync_channel(0);
+        let (yields, yielded) = mpsc::sync_channel(0);
+        let yielder = Arc::new(Yielder { yielded: yields.clone(), resume: Mutex::new(resumed), dropped: AtomicBool::new(false) });
+        let name = function.name.clone();
+        thread::Builder::new()
+            .name(format!("generator {}", name))
+            .stack_size(STACK_SIZE)
+            .spawn(move || {
+                program.set_yielder(yielder);
+                let result = function.call(&mut program, args).map(|_| None);
+                // The caller may be gone already, then there's nobody left to tell
+                let _ = yields.send(result);
+            })
+            .map_err(|err| format!("Cannot start generator '{}': {}", name, err))?;
+        Ok((resume, yielded))
+    }
+}
+
+impl PartialEq for Generator {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+impl Iterator for Generator {
+    type Item = Result<Value, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.resume()
+    }
+}
@@ -2,7 +2,9 @@ mod types;
 mod traits;
 mod program;
 mod function;
+mod generator;
 #[allow(unused_imports)]
-pub use types::{Value, Input, Function, Struct, Variable};
+pub use types::{Value, Input, Function, Closure, Struct, Enum, Variant, Interface, Variable};
 pub use traits::{StdFunction, StdStruct};
-pub use program::Program;
+pub use program::{Program, ErrorKind};
+pub use generator::Generator;
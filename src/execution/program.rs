@@ -1,15 +1,81 @@
-use std::{collections::HashMap, fmt::{Debug, Display}, sync::Arc};
-use crate::ir::{IR, Variable as IRVariable, VariableData, Function as IRFunction, Struct as IRStruct, Action, Literal, Expression, Operator};
-use super::types::{Value, Input, Function, Struct, Variable};
+use std::{collections::{HashMap, HashSet}, fmt::{Debug, Display}, sync::{Arc, Mutex, MutexGuard}};
+use crate::ir::{IR, PassManager, OptimizationLevel, Attribute, Variable as IRVariable, Binding as IRBinding, BindingKind, VariableData, Function as IRFunction, Struct as IRStruct, Interface as IRInterface, Impl as IRImpl, Enum as IREnum, Import as IRImport, ForIn, Loop, Switch, Destructure, Try, Action, Literal, Expression, FunctionCall, Operator, Pattern, SizeOf, Typing};
+use super::types::{Value, Input, Function, Closure, Struct, Enum, Variant, Interface, Variable, NamedArguments};
 use super::traits::{StdFunction, StdStruct};
+use super::generator::Yielder;
+use crate::modules::{self, ModuleLoader, ModuleResolver};
+use crate::parsing::parse;
 
 #[derive(Debug)]
 pub struct Program{
     pub std_functions: HashMap<String, Arc<dyn StdFunction>>,
     pub std_structs: HashMap<String, Arc<dyn StdStruct>>,
-    pub functions: HashMap<String, Function>,
+    // overloads of each function, in the order they were declared
+    pub functions: HashMap<String, Vec<Function>>,
     pub structs: HashMap<String, Struct>,
+    pub enums: HashMap<String, Enum>,
+    pub interfaces: HashMap<String, Interface>,
+    // methods from impl blocks, by the name of the type they're implemented for
+    pub methods: HashMap<String, HashMap<String, Function>>,
     pub variables: HashMap<String, Variable>,
+    // `const` and `let` bindings, which can't be assigned to
+    pub immutable: HashMap<String, BindingKind>,
+    // the constants declared by the blocks being run in this function call, which can't be declared again until
+    // the block declaring them is left, as when a loop starts its next iteration
+    constants: HashSet<String>,
+    // imported modules by namespace, and the functions imported by name from them
+    pub modules: HashMap<String, Arc<Program>>,
+    pub imports: HashMap<String, String>,
+    loader: Option<Arc<Mutex<ModuleLoader>>>,
+    // the key of the module this program is running, None for the main program
+    module: Option<String>,
+    // how imported modules are optimized, the same as the main program
+    optimization: OptimizationLevel,
+    // shared by every scope of a run: warnings raised while running, and results of `@memoize` functions
+    warnings: Arc<Mutex<Vec<String>>>,
+    memoized: Arc<Mutex<HashMap<String, Value>>>,
+    // the name of the function this scope runs, None at the top level
+    frame: Option<String>,
+    // the line of the call this scope is running, set by the caller while the call runs
+    call_line: Option<usize>,
+    unwinding: Arc<Mutex<Unwinding>>,
+    // where `yield` sends values, only set in the scope of a generator's body
+    yielder: Option<Arc<Yielder>>,
+}
+
+/// What is known about an error on its way up the call frames, besides its message
+#[derive(Debug, Default)]
+struct Unwinding {
+    // the `Err` a `?` is returning with, until the function it returns from takes it
+    propagated: Option<Value>,
+    // the value a `throw` threw, until a `catch` takes it
+    thrown: Option<Value>,
+    // where the error was raised
+    location: Option<String>,
+    // the kind of the last runtime error raised, with its message
+    kind: Option<(ErrorKind, String)>,
+}
+
+/// What a runtime error is about, set where it is raised, and the `kind` a `catch` sees in the `Error` it gets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorKind {
+    Arithmetic,
+    Null,
+    Index,
+    Name,
+    Type,
+}
+
+impl ErrorKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorKind::Arithmetic => "ArithmeticError",
+            ErrorKind::Null => "NullError",
+            ErrorKind::Index => "IndexError",
+            ErrorKind::Name => "NameError",
+            ErrorKind::Type => "TypeError",
+        }
+    }
 }
 
 impl Clone for Program {
@@ -19,7 +85,23 @@ impl Clone for Program {
             std_structs: self.std_structs.clone(),
             functions: self.functions.clone(),
             structs: self.structs.clone(),
+            enums: self.enums.clone(),
+            interfaces: self.interfaces.clone(),
+            methods: self.methods.clone(),
             variables: self.variables.clone(),
+            immutable: self.immutable.clone(),
+            constants: self.constants.clone(),
+            modules: self.modules.clone(),
+            imports: self.imports.clone(),
+            loader: self.loader.clone(),
+            module: self.module.clone(),
+            optimization: self.optimization,
+            warnings: self.warnings.clone(),
+            memoized: self.memoized.clone(),
+            frame: self.frame.clone(),
+            call_line: self.call_line,
+            unwinding: self.unwinding.clone(),
+            yielder: self.yielder.clone(),
         }
     }
 }
@@ -29,18 +111,133 @@ impl PartialEq for Program {
         // Can't compare dyn traits, so we skip those fields
         self.functions == other.functions &&
         self.structs == other.structs &&
-        self.variables == other.variables
+        self.enums == other.enums &&
+        self.interfaces == other.interfaces &&
+        self.methods == other.methods &&
+        self.variables == other.variables &&
+        self.immutable == other.immutable &&
+        self.modules == other.modules &&
+        self.imports == other.imports
+    }
+}
+
+/// How running a block of actions ended
+enum Flow {
+    Next,
+    Return(Value),
+    // the label of the loop to leave or continue, the innermost loop if None
+    Break(Option<String>),
+    Continue(Option<String>),
+}
+
+impl Flow {
+    /// Whether a `break` or `continue` with the given label is meant for the loop with `loop_label`
+    fn targets(label: &Option<String>, loop_label: &Option<String>) -> bool {
+        label.is_none() || label == loop_label
     }
 }
 
 impl Program{
     pub fn new() -> Self{
-        Program{
+        let mut program = Program{
             std_functions: HashMap::new(),
             std_structs: HashMap::new(),
             functions: HashMap::new(),
             structs: HashMap::new(),
+            enums: HashMap::new(),
+            interfaces: HashMap::new(),
+            methods: HashMap::new(),
             variables: HashMap::new(),
+            immutable: HashMap::new(),
+            constants: HashSet::new(),
+            modules: HashMap::new(),
+            imports: HashMap::new(),
+            loader: None,
+            module: None,
+            optimization: OptimizationLevel::default(),
+            warnings: Arc::new(Mutex::new(Vec::new())),
+            memoized: Arc::new(Mutex::new(HashMap::new())),
+            frame: None,
+            call_line: None,
+            unwinding: Arc::new(Mutex::new(Unwinding::default())),
+            yielder: None,
+        };
+        program.run_enum(&IREnum::result()).expect("Result is the first enum defined");
+        program.run_struct(&IRStruct::error()).expect("Error is the first struct defined");
+        program
+    }
+    /// Records a warning for the host, once per distinct message
+    pub fn warn(&self, warning: String) {
+        let mut warnings = self.warnings.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+    }
+    /// The warnings raised so far while running, e.g. uses of `@deprecated` functions
+    pub fn warnings(&self) -> Vec<String> {
+        self.warnings.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+    /// A result cached for a `@memoize` function, keyed by its name and arguments
+    pub fn memoized(&self, key: &str) -> Option<Value> {
+        self.memoized.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).get(key).cloned()
+    }
+    pub fn memoize(&self, key: String, value: Value) {
+        self.memoized.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).insert(key, value);
+    }
+    fn unwinding(&self) -> MutexGuard<'_, Unwinding> {
+        self.unwinding.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+    /// Takes the `Err` a `?` returned with, if the error being handled came from one
+    pub fn take_propagated(&self) -> Option<Value> {
+        self.unwinding().propagated.take()
+    }
+    /// Enters the scope of a function call, for the location of errors raised in it.
+    /// `yield` in a function called by a generator doesn't suspend the generator
+    pub fn enter_frame(&mut self, function: &Function) {
+        self.frame = Some(function.name.clone());
+        self.constants.clear();
+        if !function.generator {
+            self.yielder = None;
+        }
+    }
+    pub(super) fn set_yielder(&mut self, yielder: Arc<Yielder>) {
+        self.yielder = Some(yielder);
+    }
+    /// Whether this scope runs the body of a generator its caller dropped, which neither `catch` nor `finally` can keep running
+    fn dropped(&self) -> bool {
+        self.yielder.as_ref().is_some_and(|yielder| yielder.dropped())
+    }
+    /// Raises a runtime error of the given kind, returning the message to return as the `Err`
+    pub fn fail(&self, kind: ErrorKind, message: impl Into<String>) -> String {
+        let message = message.into();
+        self.unwinding().kind = Some((kind, message.clone()));
+        message
+    }
+    /// Records that an error left this scope, unless an inner scope already said where it was raised
+    pub fn unwind(&self) {
+        let location = self.location();
+        self.unwinding().location.get_or_insert(location);
+    }
+    fn location(&self) -> String {
+        let location = match self.frame.as_deref() {
+            Some("") => "anonymous function".to_string(),
+            Some(function) => format!("function '{}'", function),
+            None => "top level".to_string(),
+        };
+        let location = match (&self.frame, self.call_line) {
+            (Some(_), Some(line)) => format!("{} called at line {}", location, line),
+            _ => location,
+        };
+        match &self.module {
+            Some(module) => format!("{} of module '{}'", location, module),
+            None => location,
+        }
+    }
+    /// Turns the result of a `StdFunction` into a `Result` value, so scripts can recover from its errors
+    pub fn recoverable(&mut self, result: Result<Value, String>) -> Result<Value, String> {
+        match result {
+            Ok(value) => Ok(Value::ok(value)),
+            Err(message) => Ok(Value::err(self.extract_value(&VariableData::Literal(Literal::String(message)))?)),
         }
     }
     pub fn include_std_struct(&mut self, struct_: Arc<dyn StdStruct>){
@@ -57,56 +254,314 @@ impl Program{
             self.include_std_function(function);
         }
     }
+    /// Sets where imported modules are loaded from
+    pub fn set_resolver(&mut self, resolver: Arc<dyn ModuleResolver>){
+        self.loader = Some(Arc::new(Mutex::new(ModuleLoader::new(resolver))));
+    }
+    /// Sets how imported modules are optimized before they run
+    pub fn set_optimization(&mut self, optimization: OptimizationLevel){
+        self.optimization = optimization;
+    }
     pub fn run(&mut self, ir: &IR) -> Result<Value, String> {
-        // Process each action in the IR
-        for action in &ir.actions {
+        match self.run_actions(&ir.actions)? {
+            Flow::Return(value) => Ok(value),
+            _ => Ok(Value::Null),
+        }
+    }
+    /// Runs the actions in order, stopping early once a `return`, `break` or `continue` is reached
+    fn run_actions(&mut self, actions: &[Action]) -> Result<Flow, String> {
+        for action in actions {
             match action {
                 Action::Function(function) => {
                     // Process function declarations
-                    if let Err(err) = self.run_function(function) {
-                        return Err(err);
-                    }
+                    self.run_function(function)?
                 },
+                Action::Binding(binding) => {
+                    self.run_binding(binding)?;
+                }
                 Action::Variable(variable) => {
                     // Process variable declarations
-                    if let Err(err) = self.run_variable(variable) {
-                        return Err(err);
-                    }
+                    self.run_variable(variable)?
                 },
                 Action::Struct(ir_struct) => {
                     // Process struct declarations
-                    if let Err(err) = self.run_struct(ir_struct) {
-                        return Err(err);
-                    }
+                    self.run_struct(ir_struct)?
+                },
+                Action::Enum(ir_enum) => {
+                    self.run_enum(ir_enum)?;
+                }
+                Action::Import(import) => {
+                    self.run_import(import)?;
+                }
+                Action::Interface(interface) => {
+                    self.run_interface(interface)?
+                },
+                Action::Impl(implementation) => {
+                    self.run_impl(implementation)?
                 },
                 Action::Expression(expr) => {
-                    let data = VariableData::Expression(Box::new(expr.clone()));
-                    let value = self.extract_value(&data)?;
-                    return Ok(value);
+                    self.evaluate(expr)?;
+                }
+                Action::Operation(operation) if matches!(operation.operator, Operator::Break | Operator::Continue) => {
+                    let label = match &*operation.left {
+                        Expression::Variable(label, _) => Some(label.clone()),
+                        _ => None,
+                    };
+                    return Ok(if operation.operator == Operator::Break { Flow::Break(label) } else { Flow::Continue(label) });
                 }
                 Action::Operation(operation) => {
-                    let data = VariableData::Expression(Box::new(Expression::Operation(operation.clone())));
-                    let value = self.extract_value(&data)?;
-                    return Ok(value);
+                    let value = self.evaluate(&Expression::Operation(operation.clone()))?;
+                    if operation.operator == Operator::Return {
+                        return Ok(Flow::Return(value));
+                    }
                 },
-                Action::Conditional(_conditional) => {
-                    // Process conditional statements (if, while, etc.)
-                    // This would be implemented in a more complete version
-                    // For now, we'll just skip conditionals
-                    unimplemented!()
+                Action::Conditional(conditional) => {
+                    let condition = self.evaluate(&conditional.condition)?;
+                    let branch = if condition.is_truthy() {
+                        &conditional.then_actions
+                    } else {
+                        &conditional.else_actions
+                    };
+                    match self.run_block(branch)? {
+                        Flow::Next => {}
+                        flow => return Ok(flow),
+                    }
                 },
+                Action::Loop(looped) => {
+                    match self.run_loop(looped)? {
+                        Flow::Next => {}
+                        flow => return Ok(flow),
+                    }
+                }
+                Action::ForIn(for_in) => {
+                    match self.run_for_in(for_in)? {
+                        Flow::Next => {}
+                        flow => return Ok(flow),
+                    }
+                }
+                Action::Destructure(destructure) => {
+                    self.run_destructure(destructure)?;
+                }
+                Action::Try(try_) => {
+                    match self.run_try(try_)? {
+                        Flow::Next => {}
+                        flow => return Ok(flow),
+                    }
+                }
+                Action::Switch(switch) => {
+                    match self.run_switch(switch)? {
+                        Flow::Next => {}
+                        flow => return Ok(flow),
+                    }
+                }
+            }
+        }
+        Ok(Flow::Next)
+    }
+    /// Runs a nested block, after which the constants and `let` bindings it declared are out of scope
+    fn run_block(&mut self, actions: &[Action]) -> Result<Flow, String> {
+        let constants = self.constants.clone();
+        let immutable = self.immutable.clone();
+        let flow = self.run_actions(actions);
+        self.constants = constants;
+        self.immutable = immutable;
+        flow
+    }
+    fn run_loop(&mut self, looped: &Loop) -> Result<Flow, String> {
+        self.run_actions(&looped.init)?;
+        let mut first = true;
+        loop {
+            if (looped.check_first || !first) && !self.evaluate(&looped.condition)?.is_truthy() {
+                return Ok(Flow::Next);
+            }
+            first = false;
+            match self.run_block(&looped.body)? {
+                Flow::Break(label) if Flow::targets(&label, &looped.label) => return Ok(Flow::Next),
+                Flow::Continue(label) if Flow::targets(&label, &looped.label) => {}
+                Flow::Next => {}
+                flow => return Ok(flow),
             }
+            self.run_actions(&looped.update)?;
+        }
+    }
+    /// Jumps to the first case equal to the value, or to `default`, and falls through the cases after it until a `break`
+    fn run_switch(&mut self, switch: &Switch) -> Result<Flow, String> {
+        let value = self.evaluate(&switch.value)?;
+        let mut start = None;
+        for (index, case) in switch.cases.iter().enumerate() {
+            if let Some(label) = &case.label {
+                let label = self.evaluate(label)?;
+                if self.run_operation(&Operator::Equal, value.clone(), label)?.is_truthy() {
+                    start = Some(index);
+                    break;
+                }
+            }
+        }
+        let start = match start.or_else(|| switch.cases.iter().position(|case| case.label.is_none())) {
+            Some(start) => start,
+            None => return Ok(Flow::Next),
+        };
+        for case in &switch.cases[start..] {
+            match self.run_block(&case.body)? {
+                Flow::Next => {}
+                Flow::Break(None) => return Ok(Flow::Next),
+                flow => return Ok(flow),
+            }
+        }
+        Ok(Flow::Next)
+    }
+    /// Runs the body once per item, with the loop variable shadowing any variable of the same name until the loop is done
+    fn run_for_in(&mut self, for_in: &ForIn) -> Result<Flow, String> {
+        let iterable = self.evaluate(&for_in.iterable)?;
+        let shadowed = self.variables.get(&for_in.variable).cloned();
+        let shadowed_kind = self.immutable.remove(&for_in.variable);
+        let mut result = Ok(Flow::Next);
+        let mut index = 0;
+        loop {
+            let item = match self.iterate(&iterable, index) {
+                Ok(Some(item)) => item,
+                Ok(None) => break,
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            };
+            self.variables.insert(for_in.variable.clone(), Variable { name: for_in.variable.clone(), value: item });
+            match self.run_block(&for_in.body) {
+                Ok(Flow::Break(label)) if Flow::targets(&label, &for_in.label) => break,
+                Ok(Flow::Continue(label)) if Flow::targets(&label, &for_in.label) => index += 1,
+                Ok(Flow::Next) => index += 1,
+                flow => {
+                    result = flow;
+                    break;
+                }
+            }
+        }
+        match shadowed {
+            Some(variable) => { self.variables.insert(for_in.variable.clone(), variable); }
+            None => { self.variables.remove(&for_in.variable); }
+        }
+        if let Some(kind) = shadowed_kind {
+            self.immutable.insert(for_in.variable.clone(), kind);
+        }
+        result
+    }
+    fn run_try(&mut self, try_: &Try) -> Result<Flow, String> {
+        let mut result = self.run_block(&try_.body);
+        if self.dropped() {
+            return result;
+        }
+        let caught = match (&result, &try_.catch) {
+            // `?` returning from the function isn't an error to catch
+            (Err(message), Some(catch)) if self.unwinding().propagated.is_none() => Some((message.clone(), catch)),
+            _ => None,
+        };
+        if let Some((message, catch)) = caught {
+            let error = self.caught(message)?;
+            let shadowed = self.variables.get(&catch.variable).cloned();
+            let shadowed_kind = self.immutable.remove(&catch.variable);
+            self.variables.insert(catch.variable.clone(), Variable { name: catch.variable.clone(), value: error });
+            result = self.run_block(&catch.body);
+            match shadowed {
+                Some(variable) => { self.variables.insert(catch.variable.clone(), variable); }
+                None => { self.variables.remove(&catch.variable); }
+            }
+            if let Some(kind) = shadowed_kind {
+                self.immutable.insert(catch.variable.clone(), kind);
+            }
+        }
+        if let Some(finally) = &try_.finally {
+            if self.dropped() {
+                return result;
+            }
+            // The error being unwound is set aside, so one raised by the finally block replaces it
+            let pending = std::mem::take(&mut *self.unwinding());
+            match self.run_block(finally)? {
+                Flow::Next => *self.unwinding() = pending,
+                flow => return Ok(flow),
+            }
+        }
+        result
+    }
+    /// The value a `catch` binds: what was thrown, or an `Error` describing a runtime error
+    fn caught(&mut self, message: String) -> Result<Value, String> {
+        let (thrown, location, kind) = {
+            let mut unwinding = self.unwinding();
+            (unwinding.thrown.take(), unwinding.location.take(), unwinding.kind.take())
+        };
+        if let Some(thrown) = thrown {
+            return Ok(thrown);
+        }
+        // errors raised without a kind, or whose kind was recorded for another error, are plain runtime errors
+        let kind = match kind {
+            Some((kind, raised)) if message.ends_with(&raised) => kind.name(),
+            _ => "RuntimeError",
+        }.to_string();
+        let location = location.unwrap_or_else(|| self.location());
+        let mut fields = Vec::new();
+        for (name, value) in [("message", message), ("kind", kind), ("location", location)] {
+            let value = self.extract_value(&VariableData::Literal(Literal::String(value)))?;
+            fields.push(Input { name: name.to_string(), value });
+        }
+        Ok(Value::Struct(Arc::new(Struct {
+            attributes: Vec::new(),
+            name: "Error".to_string(),
+            fields,
+            value: serde_json::Value::Null,
+        })))
+    }
+    fn throw(&mut self, value: Value) -> String {
+        let message = match &value {
+            Value::Struct(error) if error.name == "Error" => {
+                let field = |name: &str| error.fields.iter().find(|f| f.name == name).map(|f| f.value.get_value());
+                match (field("kind"), field("message")) {
+                    (Some(serde_json::Value::String(kind)), Some(serde_json::Value::String(message))) => format!("{}: {}", kind, message),
+                    _ => value.get_value().to_string(),
+                }
+            }
+            value => match value.get_value() {
+                serde_json::Value::String(message) => message,
+                other => other.to_string(),
+            },
+        };
+        let location = self.location();
+        let mut unwinding = self.unwinding();
+        unwinding.thrown = Some(value);
+        unwinding.location = Some(location);
+        format!("Uncaught exception: {}", message)
+    }
+    /// The item at `index` of an iterable value, or None once there are no more.
+    /// Arrays and builtins iterate natively, user types through their `len` and `get` methods
+    pub fn iterate(&mut self, iterable: &Value, index: usize) -> Result<Option<Value>, String> {
+        match iterable {
+            Value::Array(items) => Ok(items.get(index).cloned()),
+            Value::StdStruct(std_struct) => std_struct.clone().nth(self, index),
+            // a generator resumes where it left off, so the index isn't needed
+            Value::Generator(generator) => generator.resume().transpose(),
+            Value::Struct(_) | Value::Enum { .. } => {
+                let type_name = iterable.get_name();
+                let (len, get) = match (self.get_method(&type_name, "len"), self.get_method(&type_name, "get")) {
+                    (Some(len), Some(get)) => (len.clone(), get.clone()),
+                    _ => return Err(self.fail(ErrorKind::Type, format!("Cannot iterate over a value of type {}, it needs `len` and `get` methods", type_name))),
+                };
+                let length = len.clone().run(self, vec![iterable.clone()])?;
+                let length = length.get_value().as_i64()
+                    .ok_or(format!("'{}::len' must return an int", type_name))?;
+                if index as i64 >= length {
+                    return Ok(None);
+                }
+                let index = self.extract_value(&VariableData::Literal(Literal::Integer(index as i64)))?;
+                get.clone().run(self, vec![iterable.clone(), index]).map(Some)
+            }
+            _ => Err(self.fail(ErrorKind::Type, format!("Cannot iterate over a value of type {}", iterable.get_name()))),
         }
-        // Return a success message
-        Ok(Value::Null)
     }
     fn run_variable(&mut self, variable: &IRVariable) -> Result<(), String>{
-        let type_valid = self.extract_value(&variable.data);
-        if type_valid.is_err(){
-            let type_valid = type_valid.unwrap_err();
-            return Err(format!("On variable {}: {}", variable.name, type_valid));
-        } 
-        let type_valid = type_valid.unwrap();
+        if let Some(kind) = self.immutable.get(&variable.name) {
+            return Err(self.fail(ErrorKind::Type, format!("Cannot assign to {} '{}'", kind.describe(), variable.name)));
+        }
+        let type_valid = self.extract_value(&variable.data)
+            .map_err(|err| format!("On variable {}: {}", variable.name, err))?;
         let variable = Variable{
             name: variable.name.clone(),
             value: type_valid,
@@ -114,12 +569,36 @@ impl Program{
         self.variables.insert(variable.name.clone(), variable);
         Ok(())
     }
+    fn run_binding(&mut self, binding: &IRBinding) -> Result<(), String> {
+        if self.constants.contains(&binding.name) {
+            return Err(format!("Constant '{}' is already defined", binding.name));
+        }
+        let value = match &binding.value {
+            Some(value) => self.evaluate(value)
+                .map_err(|err| format!("On {} {}: {}", binding.kind.describe(), binding.name, err))?,
+            None => Value::Null,
+        };
+        self.variables.insert(binding.name.clone(), Variable { name: binding.name.clone(), value });
+        match binding.kind {
+            BindingKind::Var => { self.immutable.remove(&binding.name); }
+            _ => { self.immutable.insert(binding.name.clone(), binding.kind.clone()); }
+        }
+        if binding.kind == BindingKind::Const {
+            self.constants.insert(binding.name.clone());
+        }
+        Ok(())
+    }
     fn run_function(&mut self, function: &IRFunction) -> Result<(), String> {
-        // Check if the function already exists
-        if self.functions.contains_key(&function.name) {
-            return Err(format!("Function '{}' is already defined", function.name));
+        // Functions of the same name are overloads, as long as their parameter types differ
+        let execution_function = Self::to_execution_function(function);
+        let overloads = self.functions.entry(function.name.clone()).or_default();
+        if overloads.iter().any(|overload| overload.parameter_types == execution_function.parameter_types) {
+            return Err(format!("Function '{}' is already defined", execution_function.signature()));
         }
-        
+        overloads.push(execution_function);
+        Ok(())
+    }
+    fn to_execution_function(function: &IRFunction) -> Function {
         // Convert IR function parameters to execution function parameters
         let mut parameters = Vec::new();
         for param in &function.params {
@@ -128,21 +607,134 @@ impl Program{
                 value: Value::Null,
             });
         }
-
-        let _ = IR::from_actions(function.body.clone());
         
-        // Create the execution function
-        let execution_function = Function {
+        Function {
+            attributes: function.attributes.clone(),
             name: function.name.clone(),
             parameters,
+            parameter_types: function.params.iter().map(|param| param.typing.clone()).collect(),
             body: function.body.clone(),
+            generator: function.generator,
+            defaults: function.defaults.clone(),
+            variadic: function.variadic,
+        }
+    }
+    fn run_interface(&mut self, interface: &IRInterface) -> Result<(), String> {
+        if self.interfaces.contains_key(&interface.name) {
+            return Err(format!("Interface '{}' is already defined", interface.name));
+        }
+        self.interfaces.insert(interface.name.clone(), Interface {
+            name: interface.name.clone(),
+            methods: interface.methods.iter().map(|m| m.name.clone()).collect(),
+        });
+        Ok(())
+    }
+    fn run_impl(&mut self, implementation: &IRImpl) -> Result<(), String> {
+        let target = &implementation.target;
+        if !self.structs.contains_key(target) && !self.std_structs.contains_key(target) && !self.enums.contains_key(target) {
+            return Err(self.fail(ErrorKind::Name, format!("Struct type {} not found", target)));
+        }
+        if let Some(interface) = &implementation.interface {
+            let interface = self.interfaces.get(interface)
+                .ok_or_else(|| self.fail(ErrorKind::Name, format!("Interface '{}' not found", interface)))?;
+            for method in &implementation.methods {
+                if !interface.methods.contains(&method.name) {
+                    return Err(format!("Method '{}' is not a member of interface '{}'", method.name, interface.name));
+                }
+            }
+        }
+
+        let methods = self.methods.entry(implementation.target.clone()).or_default();
+        for method in &implementation.methods {
+            if methods.contains_key(&method.name) {
+                return Err(format!("Method '{}' is already defined for '{}'", method.name, implementation.target));
+            }
+            methods.insert(method.name.clone(), Self::to_execution_function(method));
+        }
+        Ok(())
+    }
+    /// Finds the method an impl block defined for the given type, here or in an imported module
+    pub fn get_method(&self, type_name: &str, name: &str) -> Option<&Function> {
+        self.methods.get(type_name).and_then(|methods| methods.get(name))
+            .or_else(|| self.modules.values().find_map(|module| module.get_method(type_name, name)))
+    }
+    fn run_import(&mut self, import: &IRImport) -> Result<(), String> {
+        let loader = self.loader.clone()
+            .ok_or(format!("Cannot import '{}' without a module resolver", import.path))?;
+        let module = self.load_module(&loader, &import.path)?;
+        let namespace = modules::namespace(&import.path);
+        for name in &import.names {
+            self.import_symbol(&module, &namespace, name)?;
+        }
+        self.modules.insert(namespace, module);
+        Ok(())
+    }
+    /// Returns the symbol table of a module, running it first if no other import did
+    fn load_module(&self, loader: &Arc<Mutex<ModuleLoader>>, path: &str) -> Result<Arc<Program>, String> {
+        let (key, source) = {
+            let mut loader = loader.lock().map_err(|_| "The module loader is poisoned".to_string())?;
+            // a module the resolver can't find is a missing name, unlike one it fails to read
+            let key = loader.resolver.resolve(path, self.module.as_deref()).map_err(|err| self.fail(ErrorKind::Name, err))?;
+            if let Some(module) = loader.modules.get(&key) {
+                return Ok(module.clone());
+            }
+            loader.start(&key)?;
+            match loader.resolver.load(&key) {
+                Ok(source) => (key, source),
+                Err(err) => {
+                    loader.finish(&key, None);
+                    return Err(err);
+                }
+            }
         };
-        
-        // Add the function to the program
-        self.functions.insert(execution_function.name.clone(), execution_function);
-        
+        let module = self.run_module(loader, &key, &source).map(Arc::new);
+        loader.lock().map_err(|_| "The module loader is poisoned".to_string())?
+            .finish(&key, module.as_ref().ok().cloned());
+        module.map_err(|err| format!("In module '{}': {}", path, err))
+    }
+    fn run_module(&self, loader: &Arc<Mutex<ModuleLoader>>, key: &str, source: &str) -> Result<Program, String> {
+        let tokens = parse(source).map_err(|err| err.to_string())?;
+        let mut ir = IR::from_tokens(tokens);
+        let builtins: Vec<String> = self.std_structs.keys().chain(self.std_functions.keys()).cloned().collect();
+        ir.resolve(&builtins)?;
+        for warning in ir.type_check()? {
+            self.warn(format!("In module '{}': {}", key, warning));
+        }
+        PassManager::for_level(self.optimization).run(&mut ir);
+        let mut module = Program::new();
+        module.std_functions = self.std_functions.clone();
+        module.std_structs = self.std_structs.clone();
+        module.loader = Some(loader.clone());
+        module.module = Some(key.to_string());
+        module.optimization = self.optimization;
+        module.warnings = self.warnings.clone();
+        module.run(&ir)?;
+        Ok(module)
+    }
+    /// Brings a symbol of a module into scope, for `import { name } from "module";`
+    fn import_symbol(&mut self, module: &Program, namespace: &str, name: &str) -> Result<(), String> {
+        if module.functions.contains_key(name) {
+            self.imports.insert(name.to_string(), namespace.to_string());
+        } else if let Some(struct_) = module.structs.get(name) {
+            self.structs.insert(name.to_string(), struct_.clone());
+        } else if let Some(enum_) = module.enums.get(name) {
+            self.enums.insert(name.to_string(), enum_.clone());
+        } else if let Some(interface) = module.interfaces.get(name) {
+            self.interfaces.insert(name.to_string(), interface.clone());
+        } else if let Some(variable) = module.variables.get(name) {
+            self.variables.insert(name.to_string(), variable.clone());
+        } else {
+            return Err(self.fail(ErrorKind::Name, format!("Module '{}' has no symbol '{}'", namespace, name)));
+        }
         Ok(())
     }
+    /// Calls a function of an imported module, with the module's own symbols in scope
+    fn call_in_module(&mut self, namespace: &str, name: &str, args: Vec<Value>) -> Result<Value, String> {
+        let module = self.modules.get(namespace).cloned()
+            .ok_or_else(|| self.fail(ErrorKind::Name, format!("Module '{}' not found", namespace)))?;
+        let mut scope = (*module).clone();
+        scope.call_function(name, args)
+    }
     fn run_struct(&mut self, ir_struct: &IRStruct) -> Result<(), String> {
         // Check if the struct already exists
         for existing_struct in self.structs.values() {
@@ -167,6 +759,7 @@ impl Program{
         
         // Create the execution struct
         let execution_struct = Struct {
+            attributes: ir_struct.attributes.clone(),
             name: ir_struct.name.clone(),
             fields,
             value: serde_json::Value::Null,
@@ -177,10 +770,28 @@ impl Program{
         
         Ok(())
     }
+    fn run_enum(&mut self, ir_enum: &IREnum) -> Result<(), String> {
+        if self.enums.contains_key(&ir_enum.name) || self.structs.contains_key(&ir_enum.name) || self.std_structs.contains_key(&ir_enum.name) {
+            return Err(format!("Enum '{}' is already defined", ir_enum.name));
+        }
+        let mut variants: Vec<Variant> = Vec::new();
+        for variant in &ir_enum.variants {
+            if variants.iter().any(|v| v.name == variant.name) {
+                return Err(format!("Variant '{}' is already defined in enum '{}'", variant.name, ir_enum.name));
+            }
+            variants.push(Variant {
+                name: variant.name.clone(),
+                fields: variant.fields.iter().map(|f| Input { name: f.name.clone(), value: Value::Null }).collect(),
+            });
+        }
+        self.enums.insert(ir_enum.name.clone(), Enum { name: ir_enum.name.clone(), variants });
+        Ok(())
+    }
     fn extract_value(&mut self, values: &VariableData) -> Result<Value, String> {
         match values {
             VariableData::Literal(literal) => {
                 match literal {
+                    Literal::Null => Ok(Value::Null),
                     Literal::Integer(i) => {
                         let int_value = self.get_value(String::from("int"));
                         match int_value {
@@ -256,97 +867,661 @@ impl Program{
                     }
                     
                     Ok(Value::Struct(Arc::new(Struct {
+                        attributes: Vec::new(),
                         name: name.clone(),
                         fields: field_values,
                         value: serde_json::Value::Null,
                     })))
                 } else {
-                    Err(format!("Struct type {} not found", name))
+                    Err(self.fail(ErrorKind::Name, format!("Struct type {} not found", name)))
                 }
             },
             VariableData::Array(elements) => {
                 let mut array = Vec::new();
                 for element in elements{
-                    let value = self.extract_value(&element)?;
+                    let value = self.extract_value(element)?;
                     array.push(value);
                 }
                 Ok(Value::Array(array))
             },
-            VariableData::Expression(expr) => {
-                match &**expr{
-                    Expression::Literal(lit) => {
-                        self.extract_value(&VariableData::Literal(lit.clone()))
-                    },
-                    Expression::Operation(op) => {
-                        let left = self.extract_value(&VariableData::Expression(op.left.clone()))?;
-                        let right = self.extract_value(&VariableData::Expression(op.right.clone()))?;
-                        self.run_operation(&op.operator, left, right)
+            VariableData::Expression(expr) => self.evaluate(expr),
+            VariableData::Null => Ok(Value::Null),
+        }
+    }
+    /// Builds a nested array of the given sizes whose elements start as the default value of their type
+    fn allocate(&self, element: &Typing, sizes: &[usize]) -> Value {
+        match sizes.split_first() {
+            Some((size, rest)) => Value::Array(vec![self.allocate(element, rest); *size]),
+            None if element.array_dimensions > 0 => Value::Array(Vec::new()),
+            None => self.std_structs.get(&element.name)
+                .and_then(|std_struct| std_struct.default_value())
+                .map_or(Value::Null, Value::StdStruct),
+        }
+    }
+    pub(super) fn evaluate(&mut self, expr: &Expression) -> Result<Value, String> {
+        match expr {
+            Expression::Literal(lit) => {
+                self.extract_value(&VariableData::Literal(lit.clone()))
+            },
+            Expression::Operation(op) => {
+                match op.operator {
+                    Operator::Assignment => {
+                        let value = self.evaluate(&op.right)?;
+                        self.assign(&op.left, value.clone())?;
+                        Ok(value)
+                    }
+                    Operator::MemberAccess => {
+                        let object = self.evaluate(&op.left)?;
+                        let member = Self::member_name(&op.right)?;
+                        if let Value::Null = object {
+                            return Err(self.null_dereference(&op.left));
+                        }
+                        self.get_member(&object, &member)
+                    }
+                    Operator::OptionalMemberAccess => {
+                        let object = self.evaluate(&op.left)?;
+                        let member = Self::member_name(&op.right)?;
+                        match object {
+                            Value::Null => Ok(Value::Null),
+                            object => self.get_member(&object, &member),
+                        }
+                    }
+                    Operator::Coalesce => {
+                        match self.evaluate(&op.left)? {
+                            Value::Null => self.evaluate(&op.right),
+                            value => Ok(value),
+                        }
+                    }
+                    Operator::Yield => {
+                        let value = self.evaluate(&op.left)?;
+                        let yielder = self.yielder.clone().ok_or("'yield' can only be used inside a generator function")?;
+                        yielder.yield_value(value)?;
+                        Ok(Value::Null)
+                    }
+                    Operator::Throw => {
+                        let value = self.evaluate(&op.left)?;
+                        Err(self.throw(value))
                     }
-                    Expression::FunctionCall(func) => {
-                        let function_name = func.name.clone();
-                        let mut args = Vec::new();
-                        for arg in &func.args {
-                            args.push(self.extract_value(&VariableData::Expression(Box::new(arg.clone())))?);
+                    Operator::Try => {
+                        match self.evaluate(&op.left)? {
+                            Value::Enum { name, variant, mut fields } if name == "Result" && fields.len() == 1 => {
+                                let value = fields.remove(0).value;
+                                if variant == "Ok" {
+                                    return Ok(value);
+                                }
+                                let message = format!("Unhandled error propagated with '?': {}", value.get_value());
+                                self.unwinding().propagated = Some(Value::err(value));
+                                Err(message)
+                            }
+                            value => Err(format!("'?' needs a Result, found {}", value.get_name())),
                         }
-                        if let Some(function) = self.functions.get(&function_name) {
-                            let mut function_clone = function.clone();
-                            let res = function_clone.run(self, args);
-                            if res.is_err(){
-                                return Err(res.unwrap_err());
+                    }
+                    _ => {
+                        let left = self.evaluate(&op.left)?;
+                        let right = self.evaluate(&op.right)?;
+                        if Self::dereferences(&op.operator) {
+                            if let Value::Null = left {
+                                return Err(self.null_dereference(&op.left));
+                            }
+                            if let Value::Null = right {
+                                return Err(self.null_dereference(&op.right));
                             }
-                            Ok(res.unwrap())
                         }
-                        else if let Some(std_function) = self.std_functions.get(&function_name){
-                            let std_function_clone = std_function.clone();
-                            let res = std_function_clone.run(self, args);
-                            if res.is_err(){
-                                return Err(res.unwrap_err());
+                        self.run_operation(&op.operator, left, right)
+                    }
+                }
+            }
+            Expression::FunctionCall(func) => {
+                let (args, named) = self.evaluate_arguments(&func.args)?;
+                let caller_line = std::mem::replace(&mut self.call_line, func.line);
+                let result = self.call_named(func, args, named);
+                self.call_line = caller_line;
+                result
+            }
+            Expression::Range(range) => {
+                let mut args = vec![self.evaluate(&range.start)?, self.evaluate(&range.end)?];
+                if let Some(step) = &range.step {
+                    args.push(self.evaluate(step)?);
+                }
+                args.push(self.extract_value(&VariableData::Literal(Literal::Boolean(range.inclusive)))?);
+                self.call_function("range", args)
+            }
+            Expression::Allocation(allocation) => {
+                let mut sizes = Vec::new();
+                for size in &allocation.sizes {
+                    let size = self.evaluate(size)?;
+                    match size.get_value().as_i64() {
+                        Some(n) if size.get_name() == "int" && n >= 0 => sizes.push(n as usize),
+                        _ => return Err(format!("Array size must be a non-negative int, found {}", size.get_value())),
+                    }
+                }
+                let mut value = self.allocate(&allocation.element, &sizes);
+                if let Some(initializer) = &allocation.initializer {
+                    let initial = self.evaluate(initializer)?;
+                    value = match (value, initial) {
+                        (Value::Array(mut elements), Value::Array(items)) => {
+                            if items.len() > elements.len() {
+                                return Err(format!("Too many initializers for an array of size {}: found {}", elements.len(), items.len()));
+                            }
+                            for (element, item) in elements.iter_mut().zip(items) {
+                                *element = item;
                             }
-                            Ok(res.unwrap())
+                            Value::Array(elements)
+                        }
+                        (_, initial) => initial,
+                    };
+                }
+                Ok(value)
+            }
+            Expression::Tuple(elements) => {
+                let mut values = Vec::new();
+                for element in elements {
+                    values.push(self.evaluate(element)?);
+                }
+                Ok(Value::Tuple(values))
+            }
+            Expression::SizeOf(SizeOf::Value(value)) => {
+                let size = match self.evaluate(value)? {
+                    Value::Array(elements) | Value::Tuple(elements) => elements.len(),
+                    Value::Struct(instance) => instance.fields.len(),
+                    Value::StdStruct(s) if s.get_name() == "string" => s.get_value().as_str().unwrap_or_default().chars().count(),
+                    _ => 1,
+                };
+                self.extract_value(&VariableData::Literal(Literal::Integer(size as i64)))
+            }
+            Expression::SizeOf(SizeOf::Unsized(typing)) => {
+                Err(format!("sizeof needs a fixed-size array type, found {}", typing))
+            }
+            Expression::Lambda(function) => {
+                Ok(Value::Closure(Arc::new(Closure {
+                    function: Self::to_execution_function(function),
+                    captured: Arc::new(Mutex::new(self.variables.clone())),
+                })))
+            }
+            Expression::Call(call) => {
+                let callee = self.evaluate(&call.callee)?;
+                let (args, named) = self.evaluate_arguments(&call.args)?;
+                let closure = match callee {
+                    Value::Closure(closure) => closure,
+                    _ => return Err(format!("{} is not a function", callee.get_name())),
+                };
+                let caller_line = std::mem::replace(&mut self.call_line, call.line);
+                let result = closure.run_with(self, args, named);
+                self.call_line = caller_line;
+                result
+            }
+            Expression::NamedArgument(name, _) => {
+                Err(format!("Named argument '{}' can only be passed to a call", name))
+            }
+            Expression::Variable(var, _) => {
+                let variable = self.variables.get(var);
+                if let Some(variable) = variable {
+                    Ok(variable.value.clone())
+                } else if let Some(overloads) = self.functions.get(var) {
+                    // Named functions can be passed around as values too, unless it's unclear which overload is meant
+                    match overloads.as_slice() {
+                        [function] => Ok(Value::Closure(Arc::new(Closure {
+                            function: function.clone(),
+                            captured: Arc::default(),
+                        }))),
+                        _ => Err(format!("'{}' is overloaded, so it cannot be used as a value", var)),
+                    }
+                } else{
+                    Err(self.fail(ErrorKind::Name, format!("Variable '{}' not found", var)))
+                }
+            }
+            Expression::Array(elements) => {
+                let mut array = Vec::new();
+                for element in elements {
+                    array.push(self.evaluate(element)?);
+                }
+                Ok(Value::Array(array))
+            }
+            Expression::Match(match_expr) => {
+                let value = self.evaluate(&match_expr.scrutinee)?;
+                for arm in &match_expr.arms {
+                    let mut bindings = Vec::new();
+                    if !self.match_pattern(&arm.pattern, &value, &mut bindings)? {
+                        continue;
+                    }
+                    // Bindings shadow variables of the same name until the arm is done
+                    let shadowed: Vec<(String, Option<Variable>)> = bindings.iter()
+                        .map(|(name, _)| (name.clone(), self.variables.get(name).cloned()))
+                        .collect();
+                    for (name, bound) in bindings {
+                        self.variables.insert(name.clone(), Variable { name, value: bound });
+                    }
+                    let result = self.run_arm(arm.guard.as_ref(), &arm.body);
+                    for (name, variable) in shadowed.into_iter().rev() {
+                        match variable {
+                            Some(variable) => { self.variables.insert(name, variable); }
+                            None => { self.variables.remove(&name); }
+                        }
+                    }
+                    if let Some(result) = result? {
+                        return Ok(result);
+                    }
+                }
+                Err(format!("No match arm matches the value {}", value.get_value()))
+            }
+            Expression::Variant(constructor) => {
+                let mut args = Vec::new();
+                for arg in &constructor.args {
+                    args.push(self.evaluate(arg)?);
+                }
+                // `vec::dot(a, b)` and `vec::origin` use the symbols of the module imported as `vec`
+                if !self.enums.contains_key(&constructor.enum_name) {
+                    if let Some(module) = self.modules.get(&constructor.enum_name) {
+                        if let (true, Some(variable)) = (args.is_empty(), module.variables.get(&constructor.variant)) {
+                            return Ok(variable.value.clone());
                         }
-                        else if let Some(struct_) = self.structs.get(&function_name){
-                            let field_names: Vec<String> = struct_.fields.iter().map(|f| f.name.clone()).collect();
-                            if field_names.len() != args.len(){
-                                return Err(format!("'{}' expects {} arguments, but {} were provided", function_name, field_names.len(), args.len()));
+                        return self.call_in_module(&constructor.enum_name.clone(), &constructor.variant, args);
+                    }
+                }
+                let enum_ = self.enums.get(&constructor.enum_name)
+                    .ok_or_else(|| self.fail(ErrorKind::Name, format!("Enum '{}' not found", constructor.enum_name)))?;
+                let variant = enum_.variants.iter().find(|v| v.name == constructor.variant)
+                    .ok_or_else(|| self.fail(ErrorKind::Name, format!("Enum '{}' has no variant '{}'", constructor.enum_name, constructor.variant)))?;
+                if variant.fields.len() != args.len() {
+                    return Err(self.fail(ErrorKind::Type, format!("'{}::{}' expects {} arguments, but {} were provided", constructor.enum_name, constructor.variant, variant.fields.len(), args.len())));
+                }
+                let fields = variant.fields.iter().zip(args)
+                    .map(|(field, value)| Input { name: field.name.clone(), value })
+                    .collect();
+                Ok(Value::Enum {
+                    name: constructor.enum_name.clone(),
+                    variant: constructor.variant.clone(),
+                    fields,
+                })
+            }
+        }
+    }
+    /// Evaluates the arguments of a call, splitting them into positional and named ones
+    fn evaluate_arguments(&mut self, args: &[Expression]) -> Result<(Vec<Value>, NamedArguments), String> {
+        let mut positional = Vec::new();
+        let mut named: NamedArguments = Vec::new();
+        for arg in args {
+            match arg {
+                Expression::NamedArgument(name, value) => {
+                    let value = self.evaluate(value)?;
+                    named.push((name.clone(), value));
+                }
+                _ if !named.is_empty() => {
+                    return Err(format!("Positional arguments must come before named ones, found one after '{}'", named[named.len() - 1].0));
+                }
+                _ => positional.push(self.evaluate(arg)?),
+            }
+        }
+        Ok((positional, named))
+    }
+    /// Calls the function, builtin or constructor visible under the given name
+    pub fn call_function(&mut self, function_name: &str, args: Vec<Value>) -> Result<Value, String> {
+        self.call_function_with(function_name, args, Vec::new())
+    }
+    /// Like `call_function`, with named arguments after the positional ones
+    pub fn call_function_with(&mut self, function_name: &str, args: Vec<Value>, named: NamedArguments) -> Result<Value, String> {
+        // A variable holding a function shadows functions of the same name
+        if let Some(Variable { value: Value::Closure(closure), .. }) = self.variables.get(function_name) {
+            let closure = closure.clone();
+            return closure.run_with(self, args, named);
+        }
+        if let Some(overloads) = self.functions.get(function_name) {
+            let mut function = self.resolve_overload(overloads, &args, &named)?;
+            return function.run_with(self, args, named);
+        }
+        let takes_named = self.structs.contains_key(function_name)
+            && !self.imports.contains_key(function_name)
+            && !self.std_functions.contains_key(function_name);
+        if let Some((name, _)) = named.first().filter(|_| !takes_named) {
+            return Err(format!("'{}' does not take named arguments, found '{}'", function_name, name));
+        }
+        if let Some(namespace) = self.imports.get(function_name).cloned() {
+            self.call_in_module(&namespace, function_name, args)
+        }
+        else if let Some(std_function) = self.std_functions.get(function_name){
+            let std_function_clone = std_function.clone();
+            std_function_clone.run(self, args)
+        }
+        else if let Some(struct_) = self.structs.get(function_name){
+            let field_names: Vec<String> = struct_.fields.iter().map(|f| f.name.clone()).collect();
+            if let Some(warning) = Attribute::deprecation(function_name, &struct_.attributes) {
+                self.warn(warning);
+            }
+            self.construct(function_name, field_names, args, named)
+        }
+        else if let Some(std_struct) = self.std_structs.get(function_name){
+            let std_struct = std_struct.clone();
+            Ok(Value::StdStruct(std_struct.construct(self, args)?))
+        }
+        else{
+            Err(self.fail(ErrorKind::Name, format!("Function '{}' not found", function_name)))
+        }
+    }
+    /// Picks the overload the arguments fit best. One that takes them all as declared, without defaults
+    /// or a variadic parameter, is preferred, then the one with the most parameters of a concrete type.
+    fn resolve_overload(&self, overloads: &[Function], args: &[Value], named: &NamedArguments) -> Result<Function, String> {
+        if let [function] = overloads {
+            return Ok(function.clone());
+        }
+        let mut best: Vec<(&Function, (bool, usize))> = Vec::new();
+        for overload in overloads {
+            let Ok(arguments) = overload.bind(self, args.to_vec(), named.clone()) else {
+                continue;
+            };
+            let fits = arguments.iter().zip(&overload.parameter_types)
+                .all(|(arg, typing)| arg.as_ref().is_none_or(|arg| self.accepts(typing, arg)));
+            if !fits {
+                continue;
+            }
+            let exact = !overload.variadic && arguments.iter().all(Option::is_some);
+            let concrete = overload.parameter_types.iter().filter(|typing| !typing.parameter && typing.name != "any").count();
+            let rank = (exact, concrete);
+            match best.first() {
+                Some((_, best_rank)) if *best_rank > rank => {}
+                Some((_, best_rank)) if *best_rank == rank => best.push((overload, rank)),
+                _ => best = vec![(overload, rank)],
+            }
+        }
+        let types: Vec<String> = args.iter().map(Value::get_name)
+            .chain(named.iter().map(|(name, value)| format!("{}: {}", name, value.get_name())))
+            .collect();
+        let candidates = |functions: Vec<&Function>| functions.iter().map(|f| format!("\n    {}", f.signature())).collect::<String>();
+        match best.as_slice() {
+            [(function, _)] => Ok((*function).clone()),
+            [] => Err(format!("No overload of '{}' matches the arguments ({}), the candidates are:{}",
+                overloads[0].name, types.join(", "), candidates(overloads.iter().collect()))),
+            _ => Err(format!("Call to '{}' with arguments ({}) is ambiguous, it matches:{}",
+                overloads[0].name, types.join(", "), candidates(best.iter().map(|(f, _)| *f).collect()))),
+        }
+    }
+    /// Whether a value can be passed where the given type is declared
+    fn accepts(&self, typing: &Typing, value: &Value) -> bool {
+        if typing.parameter || typing.name == "any" {
+            return true;
+        }
+        if typing.array_dimensions > 0 {
+            let mut element = typing.clone();
+            element.array_dimensions -= 1;
+            return match value {
+                Value::Array(elements) => elements.iter().all(|element_value| self.accepts(&element, element_value)),
+                _ => false,
+            };
+        }
+        match (typing.name.as_str(), value) {
+            ("optional", Value::Null) => true,
+            ("optional", _) => self.accepts(&typing.generics[0], value),
+            (_, Value::Null) => false,
+            ("tuple", Value::Tuple(elements)) => elements.len() == typing.generics.len()
+                && typing.generics.iter().zip(elements).all(|(typing, element)| self.accepts(typing, element)),
+            (name, _) if self.interfaces.contains_key(name) => self.interfaces[name].methods.iter()
+                .all(|method| self.get_method(&value.get_name(), method).is_some()),
+            (name, _) => value.get_name() == name,
+        }
+    }
+    /// Builds a struct from its fields in order, followed by fields given by name
+    fn construct(&self, name: &str, field_names: Vec<String>, args: Vec<Value>, named: NamedArguments) -> Result<Value, String> {
+        if args.len() > field_names.len() || named.is_empty() && args.len() != field_names.len() {
+            return Err(self.fail(ErrorKind::Type, format!("'{}' expects {} arguments, but {} were provided", name, field_names.len(), args.len())));
+        }
+        let mut values: Vec<Option<Value>> = vec![None; field_names.len()];
+        for (slot, arg) in values.iter_mut().zip(args) {
+            *slot = Some(arg);
+        }
+        for (field, value) in named {
+            let index = field_names.iter().position(|name| *name == field)
+                .ok_or_else(|| self.fail(ErrorKind::Name, format!("'{}' has no field named '{}'", name, field)))?;
+            if values[index].is_some() {
+                return Err(format!("Field '{}' of '{}' is given more than once", field, name));
+            }
+            values[index] = Some(value);
+        }
+        let mut fields = Vec::new();
+        for (field, value) in field_names.into_iter().zip(values) {
+            match value {
+                Some(value) => fields.push(Input { name: field, value }),
+                None => return Err(format!("Missing value for field '{}' of '{}'", field, name)),
+            }
+        }
+        Ok(Value::Struct(Arc::new(Struct {
+            attributes: Vec::new(),
+            name: name.to_string(),
+            fields,
+            value: serde_json::Value::Null,
+        })))
+    }
+    /// Calls a function value, so builtins can run callbacks passed in by scripts
+    pub fn call_value(&mut self, callee: &Value, args: Vec<Value>) -> Result<Value, String> {
+        match callee {
+            Value::Closure(closure) => closure.run(self, args),
+            _ => Err(format!("{} is not a function", callee.get_name())),
+        }
+    }
+    /// Evaluates the body of a match arm, or returns `None` if its guard rejects the value
+    fn run_arm(&mut self, guard: Option<&Expression>, body: &Expression) -> Result<Option<Value>, String> {
+        if let Some(guard) = guard {
+            if !self.evaluate(guard)?.is_truthy() {
+                return Ok(None);
+            }
+        }
+        self.evaluate(body).map(Some)
+    }
+    /// Assigns the variables of a destructuring pattern, failing if the value doesn't have its shape
+    fn run_destructure(&mut self, destructure: &Destructure) -> Result<(), String> {
+        let value = self.evaluate(&destructure.value)?;
+        let mut bindings = Vec::new();
+        if !self.match_pattern(&destructure.pattern, &value, &mut bindings)? {
+            return Err(self.fail(ErrorKind::Type, format!("Cannot destructure {} {}: it doesn't match the pattern", value.get_name(), value.get_value())));
+        }
+        for (name, value) in bindings {
+            self.assign(&Expression::Variable(name, None), value)?;
+        }
+        Ok(())
+    }
+    /// Checks whether `value` matches `pattern`, collecting the variables it binds
+    fn match_pattern(&mut self, pattern: &Pattern, value: &Value, bindings: &mut Vec<(String, Value)>) -> Result<bool, String> {
+        match pattern {
+            Pattern::Wildcard => Ok(true),
+            Pattern::Binding(name) => {
+                bindings.push((name.clone(), value.clone()));
+                Ok(true)
+            }
+            Pattern::Literal(literal) => {
+                let literal = self.extract_value(&VariableData::Literal(literal.clone()))?;
+                Ok(literal == *value)
+            }
+            Pattern::Variant { enum_name, variant, fields } => {
+                match value {
+                    Value::Enum { name, variant: value_variant, fields: values } if name == enum_name && value_variant == variant => {
+                        if fields.len() != values.len() {
+                            return Err(self.fail(ErrorKind::Type, format!("Pattern '{}::{}' expects {} fields, but {} were provided", enum_name, variant, values.len(), fields.len())));
+                        }
+                        for (field, input) in fields.iter().zip(values) {
+                            if !self.match_pattern(field, &input.value, bindings)? {
+                                return Ok(false);
                             }
-                            let mut fields = Vec::new();
-                            for i in 0..field_names.len() {
-                                fields.push(Input { name: field_names[i].clone(), value: args[i].clone() });
+                        }
+                        Ok(true)
+                    }
+                    _ => Ok(false),
+                }
+            }
+            Pattern::Struct { name, fields } => {
+                match value {
+                    Value::Struct(s) if s.name == *name => {
+                        for (field, pattern) in fields {
+                            let field_value = self.get_member(value, field)?;
+                            if !self.match_pattern(pattern, &field_value, bindings)? {
+                                return Ok(false);
                             }
-                            Ok(Value::Struct(Arc::new(Struct { 
-                                name: function_name.clone(),
-                                fields,
-                                value: serde_json::Value::Null,
-                            })))
                         }
-                        else if let Some(std_struct) = self.std_structs.get(&function_name){
-                            let mut std_struct_clone = std_struct.clone();
-                            let res = if let Some(mut_struct) = Arc::get_mut(&mut std_struct_clone) {
-                                mut_struct.from_value(self, args)
-                            } else {
-                                return Err("Cannot get mutable reference to Arc".to_string());
-                            };
-                            if res.is_err(){
-                                return Err(res.unwrap_err());
+                        Ok(true)
+                    }
+                    _ => Ok(false),
+                }
+            }
+            Pattern::Array(elements) => {
+                let array = match value {
+                    Value::Array(array) => array,
+                    _ => return Ok(false),
+                };
+                let rest = elements.iter().position(|p| matches!(p, Pattern::Rest(_)));
+                let (before, after) = match rest {
+                    Some(index) => (&elements[..index], &elements[index + 1..]),
+                    None => (&elements[..], &elements[elements.len()..]),
+                };
+                let too_short = array.len() < before.len() + after.len();
+                if too_short || (rest.is_none() && array.len() != elements.len()) {
+                    return Ok(false);
+                }
+                let tail = array.len() - after.len();
+                for (pattern, element) in before.iter().zip(array).chain(after.iter().zip(&array[tail..])) {
+                    if !self.match_pattern(pattern, element, bindings)? {
+                        return Ok(false);
+                    }
+                }
+                if let Some(Pattern::Rest(Some(name))) = rest.map(|index| &elements[index]) {
+                    bindings.push((name.clone(), Value::Array(array[before.len()..tail].to_vec())));
+                }
+                Ok(true)
+            }
+            Pattern::Tuple(elements) => {
+                match value {
+                    Value::Tuple(values) if values.len() == elements.len() => {
+                        for (pattern, element) in elements.iter().zip(values) {
+                            if !self.match_pattern(pattern, element, bindings)? {
+                                return Ok(false);
                             }
-                            Ok(Value::StdStruct(std_struct_clone))
                         }
-                        else{
-                            Err(format!("Function '{}' not found", function_name))
+                        Ok(true)
+                    }
+                    _ => Ok(false),
+                }
+            }
+            Pattern::Rest(_) => Err("`..` is only allowed inside an array pattern".to_string()),
+        }
+    }
+    /// Operators that need actual values on both sides, unlike `==`, `&&` or `return`
+    fn dereferences(operator: &Operator) -> bool {
+        Self::operator_method(operator).is_some_and(|method| method != "eq" && method != "neq")
+            || *operator == Operator::ArrayAccess
+    }
+    fn null_dereference(&self, expr: &Expression) -> String {
+        let message = match expr.describe() {
+            Some(name) => format!("Null dereference: '{}' is null", name),
+            None => "Null dereference: the value is null".to_string(),
+        };
+        self.fail(ErrorKind::Null, message)
+    }
+    /// Calls a function by name, or the method of that name of the first argument's type
+    fn call_named(&mut self, func: &FunctionCall, args: Vec<Value>, named: NamedArguments) -> Result<Value, String> {
+        // Interface methods are dispatched on the runtime type of the first argument,
+        // unless a variable holding a function shadows them
+        let shadowed = matches!(self.variables.get(&func.name), Some(Variable { value: Value::Closure(_), .. }));
+        let method = args.first()
+            .filter(|_| !shadowed)
+            .and_then(|receiver| self.get_method(&receiver.get_name(), &func.name));
+        if let Some(method) = method {
+            let mut method_clone = method.clone();
+            let (value, receiver) = method_clone.run_method(self, args, named)?;
+            // A method that changed `self` writes it back to the receiver it was called on
+            if let Some(receiver) = receiver {
+                if Self::is_assignable(&func.args[0]) {
+                    self.assign(&func.args[0], receiver)?;
+                }
+            }
+            return Ok(value);
+        }
+        self.call_function_with(&func.name, args, named)
+    }
+    fn member_name(expr: &Expression) -> Result<String, String> {
+        match expr {
+            Expression::Literal(Literal::String(name)) => Ok(name.clone()),
+            _ => Err("Member name must be an identifier".to_string()),
+        }
+    }
+    fn get_member(&self, object: &Value, member: &str) -> Result<Value, String> {
+        match object {
+            Value::Struct(s) => {
+                match s.fields.iter().find(|f| f.name == member) {
+                    Some(field) => Ok(field.value.clone()),
+                    None => Err(self.fail(ErrorKind::Name, format!("Struct '{}' has no field '{}'", s.name, member))),
+                }
+            }
+            Value::StdStruct(s) => {
+                match s.get_fields().into_iter().find(|f| f.name == member) {
+                    Some(field) => Ok(field.value),
+                    None => Err(self.fail(ErrorKind::Name, format!("Struct '{}' has no field '{}'", s.get_name(), member))),
+                }
+            }
+            Value::Enum { name, variant, fields } => {
+                match fields.iter().find(|f| f.name == member) {
+                    Some(field) => Ok(field.value.clone()),
+                    None => Err(self.fail(ErrorKind::Name, format!("Variant '{}::{}' has no field '{}'", name, variant, member))),
+                }
+            }
+            Value::Tuple(elements) => {
+                match member.parse::<usize>().ok().and_then(|index| elements.get(index)) {
+                    Some(element) => Ok(element.clone()),
+                    None => Err(self.fail(ErrorKind::Name, format!("Tuple of {} elements has no element {}", elements.len(), member))),
+                }
+            }
+            _ => Err(self.fail(ErrorKind::Type, format!("Cannot access member '{}' of {}", member, object.get_name()))),
+        }
+    }
+    fn is_assignable(expr: &Expression) -> bool {
+        match expr {
+            Expression::Variable(..) => true,
+            Expression::Operation(op) => {
+                matches!(op.operator, Operator::MemberAccess | Operator::ArrayAccess) && Self::is_assignable(&op.left)
+            }
+            _ => false,
+        }
+    }
+    fn get_index(&self, index: &Value) -> Result<usize, String> {
+        match index.get_value().as_i64() {
+            Some(i) if index.get_name() == "int" && i >= 0 => Ok(i as usize),
+            _ => Err(self.fail(ErrorKind::Index, format!("Invalid array index: {}", index.get_value()))),
+        }
+    }
+    /// Stores `value` into an assignable expression: a variable, a struct member or an array element
+    fn assign(&mut self, target: &Expression, value: Value) -> Result<(), String> {
+        match target {
+            Expression::Variable(name, _) => {
+                if let Some(kind) = self.immutable.get(name) {
+                    return Err(self.fail(ErrorKind::Type, format!("Cannot assign to {} '{}'", kind.describe(), name)));
+                }
+                self.variables.insert(name.clone(), Variable { name: name.clone(), value });
+                Ok(())
+            }
+            Expression::Operation(op) if op.operator == Operator::MemberAccess => {
+                let member = Self::member_name(&op.right)?;
+                match self.evaluate(&op.left)? {
+                    Value::Struct(s) => {
+                        let mut updated = (*s).clone();
+                        match updated.fields.iter_mut().find(|f| f.name == member) {
+                            Some(field) => field.value = value,
+                            None => return Err(self.fail(ErrorKind::Name, format!("Struct '{}' has no field '{}'", updated.name, member))),
                         }
+                        self.assign(&op.left, Value::Struct(Arc::new(updated)))
                     }
-                    Expression::Variable(var) => {
-                        let variable = self.variables.get(var);
-                        if let Some(variable) = variable {
-                            Ok(variable.value.clone())
-                        } else{
-                            Err(format!("Variable '{}' not found", var))
+                    other => Err(self.fail(ErrorKind::Type, format!("Cannot assign to member '{}' of {}", member, other.get_name()))),
+                }
+            }
+            Expression::Operation(op) if op.operator == Operator::ArrayAccess => {
+                let index = self.evaluate(&op.right)?;
+                let index = self.get_index(&index)?;
+                match self.evaluate(&op.left)? {
+                    Value::Array(mut array) => {
+                        if index >= array.len() {
+                            return Err(self.fail(ErrorKind::Index, format!("Index {} is out of bounds for an array of length {}", index, array.len())));
                         }
+                        array[index] = value;
+                        self.assign(&op.left, Value::Array(array))
                     }
-
+                    other => Err(self.fail(ErrorKind::Type, format!("Cannot index into {}", other.get_name()))),
                 }
-            },
-            VariableData::Null => Ok(Value::Null),
+            }
+            _ => Err(self.fail(ErrorKind::Type, "Invalid assignment target")),
         }
     }
     #[allow(dead_code)]
@@ -364,14 +1539,47 @@ impl Program{
         }
         Value::Null
     }
+    /// The method an impl block can define to overload an operator, named after the matching `StdStruct` hook
+    fn operator_method(operator: &Operator) -> Option<&'static str> {
+        match operator {
+            Operator::Add => Some("add"),
+            Operator::Subtract => Some("sub"),
+            Operator::Multiply => Some("mul"),
+            Operator::Divide => Some("div"),
+            Operator::Modulo => Some("modulo"),
+            Operator::Equal => Some("eq"),
+            Operator::NotEqual => Some("neq"),
+            Operator::Less => Some("less"),
+            Operator::LessEqual => Some("less_eq"),
+            Operator::Greater => Some("greater"),
+            Operator::GreaterEqual => Some("greater_eq"),
+            Operator::BitwiseAnd => Some("bitwise_and"),
+            Operator::BitwiseOr => Some("bitwise_or"),
+            Operator::BitwiseXor => Some("bitwise_xor"),
+            Operator::ShiftLeft => Some("shift_left"),
+            Operator::ShiftRight => Some("shift_right"),
+            Operator::BitwiseNot => Some("bitwise_not"),
+            _ => None,
+        }
+    }
     fn run_operation(&mut self, operator: &Operator, left: Value, right: Value) -> Result<Value, String>{
+        if let Value::Struct(s) = &left {
+            let method = Self::operator_method(operator).and_then(|name| self.get_method(&s.name, name));
+            if let Some(method) = method {
+                let mut method_clone = method.clone();
+                if *operator == Operator::BitwiseNot {
+                    return method_clone.run(self, vec![left]);
+                }
+                return method_clone.run(self, vec![left, right]);
+            }
+        }
         match operator{
             Operator::Add => {
                 match left{
                     Value::StdStruct(s1) => {
                         s1.add(self, right)
                     }
-                    _ => Err("Cannot add non-std structs".to_string()),
+                    _ => Err(self.fail(ErrorKind::Type, "Cannot add non-std structs")),
                 }
             }
             Operator::Subtract => {
@@ -383,7 +1591,7 @@ impl Program{
                         if right.get_value().as_i64().unwrap_or(-1) == 0{
                             return Ok(left);
                         }
-                        Err("Cannot subtract non-std structs".to_string())
+                        Err(self.fail(ErrorKind::Type, "Cannot subtract non-std structs"))
                     },
                 }
             }
@@ -392,7 +1600,7 @@ impl Program{
                     Value::StdStruct(s1) => {
                         s1.mul(self, right)
                     }
-                    _ => Err("Cannot multiply non-std structs".to_string()),
+                    _ => Err(self.fail(ErrorKind::Type, "Cannot multiply non-std structs")),
                 }
             }
             Operator::Divide => {
@@ -400,7 +1608,7 @@ impl Program{
                     Value::StdStruct(s1) => {
                         s1.div(self, right)
                     }
-                    _ => Err("Cannot divide non-std structs".to_string()),
+                    _ => Err(self.fail(ErrorKind::Type, "Cannot divide non-std structs")),
                 }
             }
             Operator::Modulo => {
@@ -408,23 +1616,37 @@ impl Program{
                     Value::StdStruct(s1) => {
                         s1.modulo(self, right)
                     }
-                    _ => Err("Cannot modulo non-std structs".to_string()),
+                    _ => Err(self.fail(ErrorKind::Type, "Cannot modulo non-std structs")),
                 }
             }
             Operator::Equal => {
                 match left{
+                    // Anything can be compared against null
+                    _ if matches!(right, Value::Null) => {
+                        self.extract_value(&VariableData::Literal(Literal::Boolean(left == right)))
+                    }
                     Value::StdStruct(s1) => {
                         s1.eq(self, right)
                     }
-                    _ => Err("Cannot compare non-std structs".to_string()),
+                    Value::Enum { .. } | Value::Tuple(_) | Value::Null => {
+                        self.extract_value(&VariableData::Literal(Literal::Boolean(left == right)))
+                    }
+                    _ => Err(self.fail(ErrorKind::Type, "Cannot compare non-std structs")),
                 }
             }
             Operator::NotEqual => {
                 match left{
+                    // Anything can be compared against null
+                    _ if matches!(right, Value::Null) => {
+                        self.extract_value(&VariableData::Literal(Literal::Boolean(left != right)))
+                    }
                     Value::StdStruct(s1) => {
                         s1.neq(self, right)
                     }
-                    _ => Err("Cannot compare non-std structs".to_string()),
+                    Value::Enum { .. } | Value::Tuple(_) | Value::Null => {
+                        self.extract_value(&VariableData::Literal(Literal::Boolean(left != right)))
+                    }
+                    _ => Err(self.fail(ErrorKind::Type, "Cannot compare non-std structs")),
                 }
             }
             Operator::Less => {
@@ -432,7 +1654,7 @@ impl Program{
                     Value::StdStruct(s1) => {
                         s1.less(self, right)
                     }
-                    _ => Err("Cannot compare non-std structs".to_string()),
+                    _ => Err(self.fail(ErrorKind::Type, "Cannot compare non-std structs")),
                 }
             }
             Operator::LessEqual => {
@@ -440,7 +1662,7 @@ impl Program{
                     Value::StdStruct(s1) => {
                         s1.less_eq(self, right)
                     }
-                    _ => Err("Cannot compare non-std structs".to_string()),
+                    _ => Err(self.fail(ErrorKind::Type, "Cannot compare non-std structs")),
                 }
             }
             Operator::Greater => {
@@ -448,7 +1670,7 @@ impl Program{
                     Value::StdStruct(s1) => {
                         s1.greater(self, right)
                     }
-                    _ => Err("Cannot compare non-std structs".to_string()),
+                    _ => Err(self.fail(ErrorKind::Type, "Cannot compare non-std structs")),
                 }
             }
             Operator::GreaterEqual => {
@@ -456,7 +1678,21 @@ impl Program{
                     Value::StdStruct(s1) => {
                         s1.greater_eq(self, right)
                     }
-                    _ => Err("Cannot compare non-std structs".to_string()),
+                    _ => Err(self.fail(ErrorKind::Type, "Cannot compare non-std structs")),
+                }
+            }
+            Operator::BitwiseAnd | Operator::BitwiseOr | Operator::BitwiseXor |
+            Operator::ShiftLeft | Operator::ShiftRight | Operator::BitwiseNot => {
+                match left{
+                    Value::StdStruct(s1) => match operator {
+                        Operator::BitwiseAnd => s1.bitwise_and(self, right),
+                        Operator::BitwiseOr => s1.bitwise_or(self, right),
+                        Operator::BitwiseXor => s1.bitwise_xor(self, right),
+                        Operator::ShiftLeft => s1.shift_left(self, right),
+                        Operator::ShiftRight => s1.shift_right(self, right),
+                        _ => s1.bitwise_not(self),
+                    },
+                    _ => Err(self.fail(ErrorKind::Type, format!("Bitwise operators are not supported for {}", left.get_name()))),
                 }
             }
             Operator::And => {
@@ -465,25 +1701,35 @@ impl Program{
                         Value::StdStruct(s1) => {
                             s1.add(self, right)
                         }
-                        _ => Err("Cannot and non-std structs".to_string()),
+                        _ => Err(self.fail(ErrorKind::Type, "Cannot and non-std structs")),
                     }
                 } else{
-                    Err("Cannot and non-bool types".to_string())
+                    Err(self.fail(ErrorKind::Type, "Cannot and non-bool types"))
                 }
             }
             Operator::Or => {
                 if (left.get_name() == "bool") && (right.get_name() == "bool"){
                     match left{
                         Value::StdStruct(s1) => {
-                            s1.add(self, right)
+                            s1.sub(self, right)
                         }
-                        _ => Err("Cannot or non-std structs".to_string()),
+                        _ => Err(self.fail(ErrorKind::Type, "Cannot or non-std structs")),
                     }
                 } else{
-                    Err("Cannot or non-bool types".to_string())
+                    Err(self.fail(ErrorKind::Type, "Cannot or non-bool types"))
+                }
+            }
+            Operator::ArrayAccess => {
+                let index = self.get_index(&right)?;
+                match left {
+                    Value::Array(array) => {
+                        array.get(index).cloned()
+                            .ok_or_else(|| self.fail(ErrorKind::Index, format!("Index {} is out of bounds for an array of length {}", index, array.len())))
+                    }
+                    _ => Err(self.fail(ErrorKind::Type, format!("Cannot index into {}", left.get_name()))),
                 }
             }
-            Operator::Comma => {
+            Operator::Comma | Operator::Expression => {
                 Ok(left)
             }
             Operator::Return => {
@@ -492,7 +1738,7 @@ impl Program{
             Operator::Assignment => {
                 Ok(left)
             }
-            _ => Err(format!("Cannot run operation {:?}", operator)),
+            _ => Err(self.fail(ErrorKind::Type, format!("Cannot run operation {:?}", operator))),
         }
     }
 }
@@ -1,18 +1,20 @@
 use std::fmt::Debug;
 use crate::ir::VariableData;
-use crate::execution::Program;
+use crate::execution::{Program, ErrorKind};
 use super::types::{Value, Input};
 use std::sync::Arc;
 
 #[allow(dead_code)]
-pub trait StdFunction: Debug {
+pub trait StdFunction: Debug + Send + Sync {
+    /// Errors returned here abort the script, use `Program::recoverable` for ones scripts can recover from
     fn run(&self, program: &mut Program, args: Vec<Value>) -> Result<Value, String>;
     fn get_name(&self) -> String;
     fn get_parameters(&self, program: &mut Program) -> Vec<Input>;
     fn new() -> Self where Self: Sized;
 }
 
-pub trait StdStruct: Debug {
+#[allow(clippy::wrong_self_convention)]
+pub trait StdStruct: Debug + Send + Sync {
     fn from_data(&mut self, program: &mut Program, args: Vec<VariableData>) -> Result<(), String>;
     fn from_value(&mut self, program: &mut Program, args: Vec<Value>) -> Result<(), String>;
     fn new_default() -> Self where Self: Sized;
@@ -31,4 +33,33 @@ pub trait StdStruct: Debug {
     fn greater(&self, program: &mut Program, other: Value) -> Result<Value, String>;
     fn greater_eq(&self, program: &mut Program, other: Value) -> Result<Value, String>;
     fn clone_with_value(&self, program: &mut Program, value: VariableData) -> Result<Arc<dyn StdStruct>, String>;
+    /// Creates a new value of this type from constructor arguments, e.g. `range(0, 10)`
+    fn construct(&self, program: &mut Program, args: Vec<Value>) -> Result<Arc<dyn StdStruct>, String>;
+    /// The value a declared but uninitialized variable of this type starts with, e.g. each element of `int a[10];`
+    fn default_value(&self) -> Option<Arc<dyn StdStruct>> {
+        None
+    }
+    /// Bitwise operators, which only integer types support
+    fn bitwise_and(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, format!("Bitwise operators are not supported for {}", self.get_name())))
+    }
+    fn bitwise_or(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, format!("Bitwise operators are not supported for {}", self.get_name())))
+    }
+    fn bitwise_xor(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, format!("Bitwise operators are not supported for {}", self.get_name())))
+    }
+    fn shift_left(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, format!("Bitwise operators are not supported for {}", self.get_name())))
+    }
+    fn shift_right(&self, program: &mut Program, _other: Value) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, format!("Bitwise operators are not supported for {}", self.get_name())))
+    }
+    fn bitwise_not(&self, program: &mut Program) -> Result<Value, String> {
+        Err(program.fail(ErrorKind::Type, format!("Bitwise operators are not supported for {}", self.get_name())))
+    }
+    /// The item at `index` when iterating over the value with for-in, or None once there are no more
+    fn nth(&self, program: &mut Program, _index: usize) -> Result<Option<Value>, String> {
+        Err(program.fail(ErrorKind::Type, format!("Cannot iterate over a value of type {}", self.get_name())))
+    }
 } 
\ No newline at end of file
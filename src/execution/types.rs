@@ -1,5 +1,6 @@
-use std::{fmt::Debug, sync::Arc};
-use crate::ir::Action;
+use std::{collections::HashMap, fmt::Debug, sync::{Arc, Mutex}};
+use crate::ir::{Action, Attribute, Expression, Typing};
+use super::generator::Generator;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Input{
@@ -7,25 +8,79 @@ pub struct Input{
     pub value: Value,
 }
 
+/// Arguments passed by name, as in `f(x: 1)`, in the order they were given
+pub type NamedArguments = Vec<(String, Value)>;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Function{
+    pub attributes: Vec<Attribute>,
     pub name: String,
     pub parameters: Vec<Input>,
-    pub body: Vec<Action>
+    /// The declared types of the parameters, which pick between overloads of the same name
+    pub parameter_types: Vec<Typing>,
+    pub body: Vec<Action>,
+    pub generator: bool,
+    /// Default values of the parameters, evaluated when an argument is left out
+    pub defaults: Vec<Option<Expression>>,
+    pub variadic: bool,
+}
+
+/// A function value, with the variables it captured when it was created.
+///
+/// The captured variables are shared by every copy of the closure, so a call assigning one of them,
+/// like a counter's `c = c + 1;`, is seen by the next call.
+#[derive(Debug, Clone)]
+pub struct Closure{
+    pub function: Function,
+    pub captured: Arc<Mutex<HashMap<String, Variable>>>,
+}
+
+impl PartialEq for Closure {
+    fn eq(&self, other: &Self) -> bool {
+        self.function == other.function && Arc::ptr_eq(&self.captured, &other.captured)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Struct{
+    // only set on struct definitions, instances don't carry them
+    pub attributes: Vec<Attribute>,
     pub name: String,
     pub fields: Vec<Input>,
     pub value: serde_json::Value,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enum{
+    pub name: String,
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant{
+    pub name: String,
+    pub fields: Vec<Input>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interface{
+    pub name: String,
+    pub methods: Vec<String>,
+}
+
 #[derive(Debug)]
 pub enum Value{
     StdStruct(Arc<dyn StdStruct>),
     Struct(Arc<Struct>),
     Array(Vec<Value>),
+    Tuple(Vec<Value>),
+    Enum{
+        name: String,
+        variant: String,
+        fields: Vec<Input>,
+    },
+    Closure(Arc<Closure>),
+    Generator(Generator),
     Null,
 }
 
@@ -35,17 +90,37 @@ impl Clone for Value{
             Value::StdStruct(s) => Value::StdStruct(s.clone()),
             Value::Struct(s) => Value::Struct(s.clone()),
             Value::Array(a) => Value::Array(a.clone()),
+            Value::Tuple(t) => Value::Tuple(t.clone()),
+            Value::Enum{ name, variant, fields } => Value::Enum{
+                name: name.clone(),
+                variant: variant.clone(),
+                fields: fields.clone(),
+            },
+            Value::Closure(c) => Value::Closure(c.clone()),
+            Value::Generator(g) => Value::Generator(g.clone()),
             Value::Null => Value::Null,
         }
     }
 }
 
 impl Value{
+    /// `Result::Ok(value)`
+    pub fn ok(value: Value) -> Value{
+        Value::Enum{ name: "Result".to_string(), variant: "Ok".to_string(), fields: vec![Input{ name: "value".to_string(), value }] }
+    }
+    /// `Result::Err(error)`
+    pub fn err(error: Value) -> Value{
+        Value::Enum{ name: "Result".to_string(), variant: "Err".to_string(), fields: vec![Input{ name: "error".to_string(), value: error }] }
+    }
     pub fn get_name(&self) -> String{
         match self{
             Value::StdStruct(s) => s.get_name(),
             Value::Struct(s) => s.name.clone(),
             Value::Array(_) => "Array".to_string(),
+            Value::Tuple(_) => "tuple".to_string(),
+            Value::Enum{ name, .. } => name.clone(),
+            Value::Closure(_) => "fn".to_string(),
+            Value::Generator(_) => "generator".to_string(),
             Value::Null => "Null".to_string(),
         }
     }
@@ -53,18 +128,44 @@ impl Value{
         match self{
             Value::StdStruct(s) => s.get_value(),
             Value::Struct(s) => s.fields.iter().map(|f| f.value.get_value()).collect(),
-            Value::Array(a) => a.iter().map(|v| v.get_value()).collect(),
+            Value::Array(a) | Value::Tuple(a) => a.iter().map(|v| v.get_value()).collect(),
+            Value::Enum{ variant, fields, .. } => {
+                if fields.is_empty(){
+                    return serde_json::Value::String(variant.clone());
+                }
+                let fields: serde_json::Value = fields.iter().map(|f| f.value.get_value()).collect();
+                serde_json::json!({ variant.clone(): fields })
+            },
+            Value::Closure(c) => serde_json::Value::String(format!("fn {}", c.function.name).trim_end().to_string()),
+            Value::Generator(g) => serde_json::Value::String(format!("generator {}", g.name).trim_end().to_string()),
             Value::Null => serde_json::Value::Null,
         }
     }
+    /// Whether the value counts as `true` in a condition
+    pub fn is_truthy(&self) -> bool{
+        match self.get_value(){
+            serde_json::Value::Bool(b) => b,
+            serde_json::Value::Number(n) => n.as_f64().unwrap_or(0.0) != 0.0,
+            serde_json::Value::String(s) => !s.is_empty(),
+            serde_json::Value::Null => false,
+            _ => true,
+        }
+    }
 }
 
 impl PartialEq for Value{
     fn eq(&self, other: &Self) -> bool {
         match (self, other){
-            (Value::StdStruct(s1), Value::StdStruct(s2)) => s1.get_name() == s2.get_name() && s1.get_fields() == s2.get_fields(),
+            (Value::StdStruct(s1), Value::StdStruct(s2)) => s1.get_name() == s2.get_name() && s1.get_value() == s2.get_value(),
             (Value::Struct(s1), Value::Struct(s2)) => s1 == s2,
             (Value::Array(a1), Value::Array(a2)) => a1 == a2,
+            (Value::Tuple(t1), Value::Tuple(t2)) => t1 == t2,
+            (Value::Enum{ name: n1, variant: v1, fields: f1 }, Value::Enum{ name: n2, variant: v2, fields: f2 }) => {
+                n1 == n2 && v1 == v2 && f1 == f2
+            },
+            (Value::Closure(c1), Value::Closure(c2)) => Arc::ptr_eq(c1, c2) || c1 == c2,
+            (Value::Generator(g1), Value::Generator(g2)) => g1 == g2,
+            (Value::Null, Value::Null) => true,
             _ => false,
         }
     }
@@ -1,25 +1,129 @@
-use super::types::{Item, VariableData};
-use super::expressions::{Expression, Operation};
+use super::types::{Attribute, BindingKind, Item, Typing, VariableData};
+use super::expressions::{Expression, Operation, Pattern};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Variable {
     pub name: String,
+    pub typing: Option<Typing>, // the declared type of `int x = 1;`
     pub data: VariableData
 }
 
+/// A `const`, `let` or `var` declaration; only `var` bindings can be assigned to afterwards
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    pub name: String,
+    pub kind: BindingKind,
+    pub typing: Option<Typing>,
+    pub value: Option<Expression>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Function {
+    pub attributes: Vec<Attribute>,
     pub name: String,
+    pub type_params: Vec<String>,
     pub params: Vec<Item>,
+    pub defaults: Vec<Option<Expression>>, // the default value of each parameter, if it has one
+    pub variadic: bool, // the last parameter collects the remaining arguments into an array
+    pub return_type: Option<Typing>, // the type of the yielded values for generators
     pub body: Vec<Action>,
+    pub generator: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Struct {
+    pub attributes: Vec<Attribute>,
     pub name: String,
+    pub type_params: Vec<String>,
     pub fields: Vec<Item>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enum {
+    pub name: String,
+    pub type_params: Vec<String>,
+    pub variants: Vec<Variant>,
+}
+
+impl Function {
+    /// The name and parameters of the function, as in `area(int width, int height = ...)`
+    pub fn signature(&self) -> String {
+        let params: Vec<String> = self.params.iter().zip(&self.defaults).enumerate()
+            .map(|(i, (param, default))| match default {
+                _ if self.variadic && i == self.params.len() - 1 => {
+                    let mut element = param.typing.clone();
+                    element.array_dimensions -= 1;
+                    format!("{} ...{}", element, param.name)
+                }
+                Some(_) => format!("{} {} = ...", param.typing, param.name),
+                None => format!("{} {}", param.typing, param.name),
+            })
+            .collect();
+        format!("{}({})", self.name, params.join(", "))
+    }
+}
+
+impl Struct {
+    /// The built-in `struct Error { string message; string kind; string location; }`,
+    /// which is what `catch` binds runtime errors to
+    pub fn error() -> Struct {
+        let field = |name: &str| Item { name: name.to_string(), typing: Typing::named("string") };
+        Struct {
+            attributes: Vec::new(),
+            name: String::from("Error"),
+            type_params: Vec::new(),
+            fields: vec![field("message"), field("kind"), field("location")],
+        }
+    }
+}
+
+impl Enum {
+    /// The built-in `enum Result<T, E> { Ok(T value), Err(E error) }`, which `?` unwraps
+    pub fn result() -> Enum {
+        let parameter = |name: &str| Typing { parameter: true, ..Typing::named(name) };
+        Enum {
+            name: String::from("Result"),
+            type_params: vec![String::from("T"), String::from("E")],
+            variants: vec![
+                Variant { name: String::from("Ok"), fields: vec![Item { name: String::from("value"), typing: parameter("T") }] },
+                Variant { name: String::from("Err"), fields: vec![Item { name: String::from("error"), typing: parameter("E") }] },
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub name: String,
+    pub fields: Vec<Item>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    pub name: String,
+    pub params: Vec<Item>,
+    pub return_type: Option<Typing>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interface {
+    pub name: String,
+    pub methods: Vec<Signature>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Impl {
+    pub interface: Option<String>,
+    pub target: String,
+    pub methods: Vec<Function>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub path: String,
+    pub names: Vec<String>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Conditional {
     pub condition: Expression,
@@ -27,12 +131,81 @@ pub struct Conditional {
     pub else_actions: Vec<Action>,
 }
 
+/// A `while`, `do`-`while` or C-style `for` loop.
+/// `init` runs once before the loop, `update` after the body and on `continue`,
+/// and `check_first` is false for `do`-`while`, whose body runs once before the condition is tested
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loop {
+    pub label: Option<String>,
+    pub init: Vec<Action>,
+    pub condition: Expression,
+    pub update: Vec<Action>,
+    pub body: Vec<Action>,
+    pub check_first: bool,
+}
+
+/// A C `switch`: the value is evaluated once, running from the first matching case (or `default`)
+/// through the following cases until a `break`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Switch {
+    pub value: Expression,
+    pub cases: Vec<SwitchCase>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchCase {
+    pub label: Option<Expression>, // None for `default`
+    pub body: Vec<Action>,
+}
+
+/// Runs `body` once for every item of `iterable`, with the item bound to `variable`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForIn {
+    pub label: Option<String>,
+    pub variable: String,
+    pub typing: Option<Typing>,
+    pub iterable: Expression,
+    pub body: Vec<Action>,
+}
+
+/// `try { } catch (e) { } finally { }`, `finally` runs however the other blocks end
+#[derive(Debug, Clone, PartialEq)]
+pub struct Try {
+    pub body: Vec<Action>,
+    pub catch: Option<Catch>,
+    pub finally: Option<Vec<Action>>,
+}
+
+/// Runs `body` with the caught error bound to `variable`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Catch {
+    pub variable: String,
+    pub body: Vec<Action>,
+}
+
+/// `(q, r) = divmod(7, 2);`, assigning every variable the pattern binds
+#[derive(Debug, Clone, PartialEq)]
+pub struct Destructure {
+    pub pattern: Pattern,
+    pub value: Expression,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Action {
     Function(Function),
     Variable(Variable),
+    Binding(Binding),
     Struct(Struct),
+    Enum(Enum),
+    Interface(Interface),
+    Impl(Impl),
+    Import(Import),
     Operation(Operation),
     Conditional(Conditional),
+    Loop(Loop),
+    ForIn(ForIn),
+    Switch(Switch),
+    Destructure(Destructure),
+    Try(Try),
     Expression(Expression),
 } 
\ No newline at end of file
@@ -0,0 +1,176 @@
+use super::super::types::{Operator, Literal};
+use super::super::expressions::Expression;
+use super::super::actions::Action;
+use super::{Block, BlockId, Cfg, Statement, Terminator};
+
+/// Where `break` and `continue` go inside a loop or a `switch`
+struct Target {
+    label: Option<String>,
+    break_to: BlockId,
+    // None for a `switch`, which `continue` skips
+    continue_to: Option<BlockId>,
+}
+
+/// Lowers structured actions into basic blocks, keeping the targets of `break` and `continue`, innermost last
+pub(super) struct Builder {
+    statements: Vec<Vec<Statement>>,
+    terminators: Vec<Option<Terminator>>,
+    current: BlockId,
+    targets: Vec<Target>,
+}
+
+impl Builder {
+    pub(super) fn build(params: &[String], actions: &[Action]) -> Cfg {
+        let mut builder = Builder { statements: Vec::new(), terminators: Vec::new(), current: Cfg::ENTRY, targets: Vec::new() };
+        builder.new_block();
+        builder.new_block();
+        builder.terminators[Cfg::EXIT] = Some(Terminator::Exit);
+        for param in params {
+            builder.push(Statement::Define(param.clone()));
+        }
+        builder.lower(actions);
+        // running off the end of the body returns
+        builder.terminate(Terminator::Goto(Cfg::EXIT));
+        let blocks = builder.statements.into_iter().zip(builder.terminators)
+            .map(|(statements, terminator)| Block { statements, terminator: terminator.unwrap_or(Terminator::Goto(Cfg::EXIT)) })
+            .collect();
+        Cfg { blocks }
+    }
+
+    fn new_block(&mut self) -> BlockId {
+        self.statements.push(Vec::new());
+        self.terminators.push(None);
+        self.statements.len() - 1
+    }
+
+    fn push(&mut self, statement: Statement) {
+        self.statements[self.current].push(statement);
+    }
+
+    /// Ends the current block, unless a `return` or a jump already did
+    fn terminate(&mut self, terminator: Terminator) {
+        self.terminators[self.current].get_or_insert(terminator);
+    }
+
+    /// Ends the current block with a jump, and continues in a new block that nothing jumps to yet
+    fn jump(&mut self, terminator: Terminator) {
+        self.terminate(terminator);
+        self.current = self.new_block();
+    }
+
+    fn lower(&mut self, actions: &[Action]) {
+        for action in actions {
+            self.lower_action(action);
+        }
+    }
+
+    fn lower_action(&mut self, action: &Action) {
+        match action {
+            Action::Conditional(conditional) => {
+                let (then_block, else_block, join) = (self.new_block(), self.new_block(), self.new_block());
+                self.terminate(Terminator::Branch { condition: conditional.condition.clone(), then_block, else_block });
+                self.current = then_block;
+                self.lower(&conditional.then_actions);
+                self.terminate(Terminator::Goto(join));
+                self.current = else_block;
+                self.lower(&conditional.else_actions);
+                self.terminate(Terminator::Goto(join));
+                self.current = join;
+            }
+            Action::Loop(loop_) => {
+                self.lower(&loop_.init);
+                let (header, body, update, exit) = (self.new_block(), self.new_block(), self.new_block(), self.new_block());
+                self.terminate(Terminator::Goto(if loop_.check_first { header } else { body }));
+                self.current = header;
+                // a loop like `while (1)` only exits through a `break`
+                match &loop_.condition {
+                    Expression::Literal(literal) if literal.is_truthy() => self.terminate(Terminator::Goto(body)),
+                    condition => self.terminate(Terminator::Branch { condition: condition.clone(), then_block: body, else_block: exit }),
+                }
+                self.current = body;
+                self.targets.push(Target { label: loop_.label.clone(), break_to: exit, continue_to: Some(update) });
+                self.lower(&loop_.body);
+                self.targets.pop();
+                self.terminate(Terminator::Goto(update));
+                self.current = update;
+                self.lower(&loop_.update);
+                self.terminate(Terminator::Goto(header));
+                self.current = exit;
+            }
+            Action::ForIn(for_in) => {
+                self.push(Statement::Action(Action::Expression(for_in.iterable.clone())));
+                let (header, body, exit) = (self.new_block(), self.new_block(), self.new_block());
+                self.terminate(Terminator::Goto(header));
+                self.current = header;
+                self.terminate(Terminator::Choice(vec![body, exit]));
+                self.current = body;
+                self.push(Statement::Define(for_in.variable.clone()));
+                self.targets.push(Target { label: for_in.label.clone(), break_to: exit, continue_to: Some(header) });
+                self.lower(&for_in.body);
+                self.targets.pop();
+                self.terminate(Terminator::Goto(header));
+                self.current = exit;
+            }
+            Action::Switch(switch) => {
+                let cases: Vec<BlockId> = switch.cases.iter().map(|_| self.new_block()).collect();
+                let exit = self.new_block();
+                let mut targets = cases.clone();
+                if switch.cases.iter().all(|case| case.label.is_some()) {
+                    targets.push(exit);
+                }
+                self.terminate(Terminator::Switch { value: switch.value.clone(), targets });
+                self.targets.push(Target { label: None, break_to: exit, continue_to: None });
+                for (index, case) in switch.cases.iter().enumerate() {
+                    self.current = cases[index];
+                    self.lower(&case.body);
+                    // cases fall through to the next one
+                    self.terminate(Terminator::Goto(cases.get(index + 1).copied().unwrap_or(exit)));
+                }
+                self.targets.pop();
+                self.current = exit;
+            }
+            Action::Try(try_) => {
+                // any statement of the body can throw, so the handler can be reached both before
+                // and after the body has run
+                let (body, finally) = (self.new_block(), self.new_block());
+                let handler = try_.catch.as_ref().map(|_| self.new_block());
+                self.terminate(Terminator::Choice([body].into_iter().chain(handler).collect()));
+                self.current = body;
+                self.lower(&try_.body);
+                self.terminate(Terminator::Choice(handler.into_iter().chain([finally]).collect()));
+                if let (Some(catch), Some(handler)) = (&try_.catch, handler) {
+                    self.current = handler;
+                    self.push(Statement::Define(catch.variable.clone()));
+                    self.lower(&catch.body);
+                    self.terminate(Terminator::Goto(finally));
+                }
+                self.current = finally;
+                if let Some(actions) = &try_.finally {
+                    self.lower(actions);
+                }
+            }
+            Action::Operation(operation) => match operation.operator {
+                Operator::Return => self.jump(Terminator::Return((*operation.left).clone())),
+                Operator::Throw => self.jump(Terminator::Throw((*operation.left).clone())),
+                Operator::Break | Operator::Continue => {
+                    let label = match &*operation.left {
+                        Expression::Variable(label, _) => Some(label),
+                        _ => None,
+                    };
+                    let breaking = operation.operator == Operator::Break;
+                    let target = self.targets.iter().rev()
+                        .filter(|target| breaking || target.continue_to.is_some())
+                        .find(|target| label.is_none() || target.label.as_ref() == label)
+                        .map(|target| if breaking { target.break_to } else { target.continue_to.unwrap() });
+                    // a `break` outside of any loop is an error at runtime, which ends the function
+                    self.jump(match target {
+                        Some(target) => Terminator::Goto(target),
+                        None => Terminator::Throw(Expression::Literal(Literal::Null)),
+                    });
+                }
+                _ => self.push(Statement::Action(action.clone())),
+            },
+            action => self.push(Statement::Action(action.clone())),
+        }
+    }
+}
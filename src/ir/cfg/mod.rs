@@ -0,0 +1,191 @@
+mod builder;
+mod names;
+mod ssa;
+mod uninitialized;
+
+use std::collections::HashSet;
+use super::expressions::Expression;
+use super::actions::Action;
+
+pub use uninitialized::uninitialized_variables;
+
+/// The index of a block in its graph
+pub type BlockId = usize;
+
+/// A statement of a basic block, which never transfers control by itself
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    /// An action without control flow of its own
+    Action(Action),
+    /// Gives the variable a value without evaluating anything, like a parameter, the loop variable of `for`
+    /// or the error of `catch`
+    Define(String),
+    /// `variable = φ(operands)`, with the operand of each predecessor, in order. Only found in SSA form
+    Phi { variable: String, operands: Vec<String> },
+}
+
+/// How control leaves a basic block
+#[derive(Debug, Clone, PartialEq)]
+pub enum Terminator {
+    Goto(BlockId),
+    Branch { condition: Expression, then_block: BlockId, else_block: BlockId },
+    /// Jumps to the case matching the value, or to the last target if none does
+    Switch { value: Expression, targets: Vec<BlockId> },
+    /// Jumps to one of the targets, decided at runtime, like whether a `for` loop has another item
+    /// or whether a `try` body throws
+    Choice(Vec<BlockId>),
+    Return(Expression),
+    Throw(Expression),
+    /// Only ends the exit block
+    Exit,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub statements: Vec<Statement>,
+    pub terminator: Terminator,
+}
+
+/// The control flow graph of a function: its body split into basic blocks, straight-line code that
+/// is entered at the top and left at the bottom.
+///
+/// Every graph starts at `Cfg::ENTRY`, and returning, throwing or running off the end all lead to `Cfg::EXIT`.
+/// Nested functions and lambdas are kept whole inside the statements, they have graphs of their own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cfg {
+    pub blocks: Vec<Block>,
+}
+
+impl Terminator {
+    pub fn successors(&self) -> Vec<BlockId> {
+        match self {
+            Terminator::Goto(target) => vec![*target],
+            Terminator::Branch { then_block, else_block, .. } => vec![*then_block, *else_block],
+            Terminator::Switch { targets, .. } | Terminator::Choice(targets) => targets.clone(),
+            Terminator::Return(_) | Terminator::Throw(_) => vec![Cfg::EXIT],
+            Terminator::Exit => Vec::new(),
+        }
+    }
+}
+
+impl Cfg {
+    pub const ENTRY: BlockId = 0;
+    pub const EXIT: BlockId = 1;
+
+    /// Builds the graph of a function body, with its parameters defined on entry
+    pub fn build(params: &[String], actions: &[Action]) -> Self {
+        builder::Builder::build(params, actions)
+    }
+
+    /// The blocks reachable from the entry, in reverse postorder, so a block comes before its successors
+    /// except along the back edges of loops
+    pub fn reverse_postorder(&self) -> Vec<BlockId> {
+        let mut visited = vec![false; self.blocks.len()];
+        let mut order = Vec::new();
+        // each block with the successors it still has to visit, so deep graphs don't overflow the stack
+        let mut stack = vec![(Cfg::ENTRY, self.blocks[Cfg::ENTRY].terminator.successors().into_iter())];
+        visited[Cfg::ENTRY] = true;
+        while let Some((block, successors)) = stack.last_mut() {
+            match successors.find(|successor| !visited[*successor]) {
+                Some(successor) => {
+                    visited[successor] = true;
+                    stack.push((successor, self.blocks[successor].terminator.successors().into_iter()));
+                }
+                None => {
+                    order.push(*block);
+                    stack.pop();
+                }
+            }
+        }
+        order.reverse();
+        order
+    }
+
+    /// The predecessors of every block, only counting the reachable ones
+    pub fn predecessors(&self) -> Vec<Vec<BlockId>> {
+        let mut predecessors = vec![Vec::new(); self.blocks.len()];
+        let mut reachable = self.reverse_postorder();
+        reachable.sort_unstable();
+        for block in reachable {
+            for successor in self.blocks[block].terminator.successors() {
+                if !predecessors[successor].contains(&block) {
+                    predecessors[successor].push(block);
+                }
+            }
+        }
+        predecessors
+    }
+
+    /// The immediate dominator of every block: the closest block that every path from the entry goes through.
+    /// The entry is its own, and unreachable blocks have none.
+    ///
+    /// Uses the iterative algorithm of Cooper, Harvey and Kennedy.
+    pub fn dominators(&self) -> Vec<Option<BlockId>> {
+        let order = self.reverse_postorder();
+        let mut position = vec![usize::MAX; self.blocks.len()];
+        for (index, block) in order.iter().enumerate() {
+            position[*block] = index;
+        }
+        let predecessors = self.predecessors();
+        let mut dominators = vec![None; self.blocks.len()];
+        dominators[Cfg::ENTRY] = Some(Cfg::ENTRY);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &block in order.iter().skip(1) {
+                let mut processed = predecessors[block].iter().copied().filter(|p| dominators[*p].is_some());
+                let Some(first) = processed.next() else { continue };
+                let dominator = processed.fold(first, |mut a, mut b| {
+                    // walks up from both blocks until they meet
+                    while a != b {
+                        while position[a] > position[b] {
+                            a = dominators[a].unwrap();
+                        }
+                        while position[b] > position[a] {
+                            b = dominators[b].unwrap();
+                        }
+                    }
+                    a
+                });
+                if dominators[block] != Some(dominator) {
+                    dominators[block] = Some(dominator);
+                    changed = true;
+                }
+            }
+        }
+        dominators
+    }
+
+    /// The dominance frontier of every block: the blocks where its dominance ends, where a variable
+    /// it assigns may meet another value of the variable
+    pub fn dominance_frontiers(&self, dominators: &[Option<BlockId>]) -> Vec<HashSet<BlockId>> {
+        let mut frontiers = vec![HashSet::new(); self.blocks.len()];
+        for (block, predecessors) in self.predecessors().iter().enumerate() {
+            if predecessors.len() < 2 {
+                continue;
+            }
+            let Some(dominator) = dominators[block] else { continue };
+            for &predecessor in predecessors {
+                let mut runner = predecessor;
+                while runner != dominator {
+                    frontiers[runner].insert(block);
+                    runner = dominators[runner].expect("predecessors are reachable");
+                }
+            }
+        }
+        frontiers
+    }
+
+    /// Converts the graph to SSA form, where every variable is assigned once: each assignment gets a new
+    /// version of the variable, named like `x.1`, and phi nodes merge the versions where paths join.
+    /// Version 0 is the value a variable has before any assignment.
+    pub fn to_ssa(&self) -> Cfg {
+        ssa::to_ssa(self)
+    }
+
+    /// Converts a graph back from SSA form, removing the phi nodes and the versions
+    #[allow(dead_code)]
+    pub fn destruct_ssa(&self) -> Cfg {
+        ssa::destruct_ssa(self)
+    }
+}
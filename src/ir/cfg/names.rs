@@ -0,0 +1,136 @@
+use super::super::types::{Operator, VariableData};
+use super::super::expressions::{Expression, Operation, Pattern, SizeOf};
+use super::super::actions::Action;
+use super::{Statement, Terminator};
+
+/// How a statement touches a variable
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Access {
+    Use,
+    Define,
+}
+
+/// Calls `visit` on every variable a statement reads or assigns, in the order they are evaluated.
+/// Phi nodes are left to the caller, and nested functions and lambdas are not looked into.
+pub fn visit_statement(statement: &mut Statement, visit: &mut impl FnMut(&mut String, Access)) {
+    match statement {
+        Statement::Action(action) => visit_action(action, visit),
+        Statement::Define(name) => visit(name, Access::Define),
+        Statement::Phi { .. } => {}
+    }
+}
+
+pub fn visit_terminator(terminator: &mut Terminator, visit: &mut impl FnMut(&mut String, Access)) {
+    match terminator {
+        Terminator::Branch { condition: expr, .. } | Terminator::Switch { value: expr, .. }
+        | Terminator::Return(expr) | Terminator::Throw(expr) => visit_expression(expr, visit),
+        Terminator::Goto(_) | Terminator::Choice(_) | Terminator::Exit => {}
+    }
+}
+
+fn visit_action(action: &mut Action, visit: &mut impl FnMut(&mut String, Access)) {
+    match action {
+        Action::Variable(variable) => {
+            visit_data(&mut variable.data, visit);
+            visit(&mut variable.name, Access::Define);
+        }
+        Action::Binding(binding) => {
+            if let Some(value) = &mut binding.value {
+                visit_expression(value, visit);
+            }
+            visit(&mut binding.name, Access::Define);
+        }
+        // the label of `break` and `continue` is not a variable
+        Action::Operation(operation) if matches!(operation.operator, Operator::Break | Operator::Continue) => {}
+        Action::Operation(operation) => visit_operation(operation, visit),
+        Action::Expression(expr) => visit_expression(expr, visit),
+        Action::Destructure(destructure) => {
+            visit_expression(&mut destructure.value, visit);
+            visit_pattern(&mut destructure.pattern, visit);
+        }
+        // blocks are split up by the builder, declarations have no variables
+        _ => {}
+    }
+}
+
+fn visit_data(data: &mut VariableData, visit: &mut impl FnMut(&mut String, Access)) {
+    match data {
+        VariableData::Expression(expr) => visit_expression(expr, visit),
+        VariableData::Array(elements) => elements.iter_mut().for_each(|element| visit_data(element, visit)),
+        VariableData::StructInstance(_, fields) => fields.iter_mut().for_each(|(_, field)| visit_data(field, visit)),
+        VariableData::Literal(_) | VariableData::Null => {}
+    }
+}
+
+fn visit_operation(operation: &mut Operation, visit: &mut impl FnMut(&mut String, Access)) {
+    match operation.operator {
+        Operator::Assignment => {
+            visit_expression(&mut operation.right, visit);
+            match &mut *operation.left {
+                Expression::Variable(name, _) => visit(name, Access::Define),
+                // `p.x = 1` and `a[0] = 1` modify a variable that must already have a value
+                target => visit_expression(target, visit),
+            }
+        }
+        Operator::MemberAccess | Operator::OptionalMemberAccess => visit_expression(&mut operation.left, visit),
+        _ => {
+            visit_expression(&mut operation.left, visit);
+            visit_expression(&mut operation.right, visit);
+        }
+    }
+}
+
+fn visit_expression(expr: &mut Expression, visit: &mut impl FnMut(&mut String, Access)) {
+    match expr {
+        Expression::Variable(name, _) => visit(name, Access::Use),
+        Expression::Operation(operation) => visit_operation(operation, visit),
+        Expression::FunctionCall(call) => {
+            call.args.iter_mut().for_each(|arg| visit_expression(arg, visit));
+            // a call by name can call a closure held by a variable
+            visit(&mut call.name, Access::Use);
+        }
+        Expression::Call(call) => {
+            visit_expression(&mut call.callee, visit);
+            call.args.iter_mut().for_each(|arg| visit_expression(arg, visit));
+        }
+        Expression::Array(elements) | Expression::Tuple(elements) => {
+            elements.iter_mut().for_each(|element| visit_expression(element, visit));
+        }
+        Expression::Variant(constructor) => constructor.args.iter_mut().for_each(|arg| visit_expression(arg, visit)),
+        Expression::Match(match_expr) => {
+            visit_expression(&mut match_expr.scrutinee, visit);
+            for arm in &mut match_expr.arms {
+                visit_pattern(&mut arm.pattern, visit);
+                if let Some(guard) = &mut arm.guard {
+                    visit_expression(guard, visit);
+                }
+                visit_expression(&mut arm.body, visit);
+            }
+        }
+        Expression::Range(range) => {
+            visit_expression(&mut range.start, visit);
+            visit_expression(&mut range.end, visit);
+            if let Some(step) = &mut range.step {
+                visit_expression(step, visit);
+            }
+        }
+        Expression::Allocation(allocation) => {
+            allocation.sizes.iter_mut().for_each(|size| visit_expression(size, visit));
+            if let Some(initializer) = &mut allocation.initializer {
+                visit_expression(initializer, visit);
+            }
+        }
+        Expression::SizeOf(SizeOf::Value(value)) | Expression::NamedArgument(_, value) => visit_expression(value, visit),
+        Expression::Lambda(_) | Expression::SizeOf(SizeOf::Unsized(_)) | Expression::Literal(_) => {}
+    }
+}
+
+fn visit_pattern(pattern: &mut Pattern, visit: &mut impl FnMut(&mut String, Access)) {
+    match pattern {
+        Pattern::Binding(name) | Pattern::Rest(Some(name)) => visit(name, Access::Define),
+        Pattern::Variant { fields, .. } => fields.iter_mut().for_each(|field| visit_pattern(field, visit)),
+        Pattern::Struct { fields, .. } => fields.iter_mut().for_each(|(_, field)| visit_pattern(field, visit)),
+        Pattern::Array(elements) | Pattern::Tuple(elements) => elements.iter_mut().for_each(|element| visit_pattern(element, visit)),
+        Pattern::Wildcard | Pattern::Literal(_) | Pattern::Rest(None) => {}
+    }
+}
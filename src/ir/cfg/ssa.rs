@@ -0,0 +1,153 @@
+use std::collections::{HashMap, HashSet};
+use super::names::{visit_statement, visit_terminator, Access};
+use super::{BlockId, Cfg, Statement};
+
+/// The variable a version belongs to, `x` for `x.2`, and the name itself if it isn't a version
+pub fn variable_of(version: &str) -> &str {
+    match version.rsplit_once('.') {
+        Some((variable, number)) if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) => variable,
+        _ => version,
+    }
+}
+
+/// Gives the versions of the variables, walking the dominator tree so the version in scope
+/// at any point is the one on top of the variable's stack
+struct Renamer<'a> {
+    predecessors: &'a [Vec<BlockId>],
+    children: &'a [Vec<BlockId>],
+    variables: &'a HashSet<String>,
+    counters: HashMap<String, usize>,
+    stacks: HashMap<String, Vec<String>>,
+}
+
+impl Renamer<'_> {
+    fn current(&self, variable: &str) -> String {
+        self.stacks.get(variable).and_then(|stack| stack.last().cloned()).unwrap_or_else(|| format!("{}.0", variable))
+    }
+
+    fn define(&mut self, variable: &str) -> String {
+        let counter = self.counters.entry(variable.to_string()).or_insert(0);
+        *counter += 1;
+        let version = format!("{}.{}", variable, counter);
+        self.stacks.entry(variable.to_string()).or_default().push(version.clone());
+        version
+    }
+
+    fn rename(&mut self, cfg: &mut Cfg, block: BlockId) {
+        let mut defined = Vec::new();
+        let mut statements = std::mem::take(&mut cfg.blocks[block].statements);
+        for statement in &mut statements {
+            if let Statement::Phi { variable, .. } = statement {
+                defined.push(variable.clone());
+                *variable = self.define(variable);
+                continue;
+            }
+            visit_statement(statement, &mut |name, access| {
+                if !self.variables.contains(name.as_str()) {
+                    return;
+                }
+                match access {
+                    Access::Use => *name = self.current(name),
+                    Access::Define => {
+                        defined.push(name.clone());
+                        *name = self.define(name);
+                    }
+                }
+            });
+        }
+        cfg.blocks[block].statements = statements;
+        visit_terminator(&mut cfg.blocks[block].terminator, &mut |name, _| {
+            if self.variables.contains(name.as_str()) {
+                *name = self.current(name);
+            }
+        });
+
+        let mut successors = cfg.blocks[block].terminator.successors();
+        successors.dedup();
+        for successor in successors {
+            let index = self.predecessors[successor].iter().position(|p| *p == block).expect("the block is a predecessor");
+            for statement in &mut cfg.blocks[successor].statements {
+                if let Statement::Phi { variable, operands } = statement {
+                    operands[index] = self.current(variable_of(variable));
+                }
+            }
+        }
+        for &child in &self.children[block] {
+            self.rename(cfg, child);
+        }
+        for variable in defined {
+            self.stacks.get_mut(&variable).map(Vec::pop);
+        }
+    }
+}
+
+pub fn to_ssa(cfg: &Cfg) -> Cfg {
+    let mut ssa = cfg.clone();
+    let dominators = cfg.dominators();
+    let frontiers = cfg.dominance_frontiers(&dominators);
+    let predecessors = cfg.predecessors();
+
+    // the blocks assigning each variable, only counting reachable ones
+    let mut assignments: HashMap<String, Vec<BlockId>> = HashMap::new();
+    for (block, dominator) in dominators.iter().enumerate() {
+        if dominator.is_none() {
+            continue;
+        }
+        for mut statement in cfg.blocks[block].statements.iter().cloned() {
+            visit_statement(&mut statement, &mut |name, access| {
+                if access == Access::Define {
+                    assignments.entry(name.clone()).or_default().push(block);
+                }
+            });
+        }
+    }
+
+    // a variable needs a phi node wherever an assignment's dominance ends, which is an assignment too
+    let mut phis: Vec<Vec<String>> = vec![Vec::new(); cfg.blocks.len()];
+    let mut sorted: Vec<_> = assignments.iter().collect();
+    sorted.sort();
+    for (variable, blocks) in sorted {
+        let mut worklist = blocks.clone();
+        while let Some(block) = worklist.pop() {
+            let mut frontier: Vec<_> = frontiers[block].iter().copied().collect();
+            frontier.sort_unstable();
+            for join in frontier {
+                if !phis[join].contains(variable) {
+                    phis[join].push(variable.clone());
+                    worklist.push(join);
+                }
+            }
+        }
+    }
+    for (block, variables) in phis.into_iter().enumerate() {
+        let operands = predecessors[block].len();
+        let nodes = variables.into_iter().map(|variable| Statement::Phi { operands: vec![variable.clone(); operands], variable });
+        ssa.blocks[block].statements.splice(0..0, nodes);
+    }
+
+    let mut children = vec![Vec::new(); cfg.blocks.len()];
+    for (block, dominator) in dominators.iter().enumerate() {
+        match dominator {
+            Some(dominator) if block != Cfg::ENTRY => children[*dominator].push(block),
+            _ => {}
+        }
+    }
+    let variables = assignments.into_keys().collect();
+    let mut renamer = Renamer { predecessors: &predecessors, children: &children, variables: &variables, counters: HashMap::new(), stacks: HashMap::new() };
+    renamer.rename(&mut ssa, Cfg::ENTRY);
+    ssa
+}
+
+/// Removes the versions, which is exact as long as two versions of a variable never hold a value
+/// at the same time, as is the case for graphs made by `to_ssa`
+#[allow(dead_code)]
+pub fn destruct_ssa(ssa: &Cfg) -> Cfg {
+    let mut cfg = ssa.clone();
+    let strip = &mut |name: &mut String, _| *name = variable_of(name).to_string();
+    for block in &mut cfg.blocks {
+        block.statements.retain(|statement| !matches!(statement, Statement::Phi { .. }));
+        block.statements.iter_mut().for_each(|statement| visit_statement(statement, strip));
+        visit_terminator(&mut block.terminator, strip);
+    }
+    cfg
+}
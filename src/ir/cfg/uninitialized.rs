@@ -0,0 +1,184 @@
+use std::collections::HashSet;
+use super::super::types::VariableData;
+use super::super::expressions::{Expression, SizeOf};
+use super::super::actions::{Action, Function};
+use super::names::{visit_statement, visit_terminator, Access};
+use super::ssa::variable_of;
+use super::{Cfg, Statement, Terminator};
+
+/// Warns about variables that are read before they are assigned, in the top-level code and in every function and lambda.
+///
+/// Only variables assigned in the function itself are checked: the others come from the scopes the
+/// function is called from. A variable is also skipped if an enclosing scope assigns it, as the function
+/// then sees that one until it assigns its own.
+pub fn uninitialized_variables(actions: &[Action]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    analyze(None, &[], actions, &HashSet::new(), &mut warnings);
+    warnings
+}
+
+fn analyze(function: Option<&str>, params: &[String], actions: &[Action], outer: &HashSet<String>, warnings: &mut Vec<String>) {
+    let ssa = Cfg::build(params, actions).to_ssa();
+
+    // the versions that may have no value: version 0, and the phi nodes merging one
+    let phis: Vec<_> = ssa.blocks.iter().flat_map(|block| &block.statements).filter_map(|statement| match statement {
+        Statement::Phi { variable, operands } => Some((variable, operands)),
+        _ => None,
+    }).collect();
+    let mut unassigned: HashSet<&String> = HashSet::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (variable, operands) in &phis {
+            if !unassigned.contains(variable) && operands.iter().any(|operand| operand.ends_with(".0") || unassigned.contains(operand)) {
+                unassigned.insert(variable);
+                changed = true;
+            }
+        }
+    }
+
+    let suffix = match function {
+        Some("") => ", in anonymous function".to_string(),
+        Some(name) => format!(", in function '{}'", name),
+        None => String::new(),
+    };
+    let mut reported = HashSet::new();
+    let mut locals = outer.clone();
+    for block in ssa.reverse_postorder() {
+        let block = &ssa.blocks[block];
+        let mut check = |name: &mut String, access: Access| {
+            let variable = variable_of(name);
+            if access == Access::Define {
+                locals.insert(variable.to_string());
+                return;
+            }
+            if variable == name || outer.contains(variable) || reported.contains(variable) {
+                return;
+            }
+            let warning = if name.ends_with(".0") {
+                format!("Variable '{}' is used before it is assigned{}", variable, suffix)
+            } else if unassigned.contains(name) {
+                format!("Variable '{}' may be used before it is assigned{}", variable, suffix)
+            } else {
+                return;
+            };
+            reported.insert(variable.to_string());
+            warnings.push(warning);
+        };
+        for statement in &block.statements {
+            visit_statement(&mut statement.clone(), &mut check);
+        }
+        visit_terminator(&mut block.terminator.clone(), &mut check);
+    }
+
+    // nested functions and lambdas see the variables of this one
+    let mut lambdas = Vec::new();
+    for block in &ssa.blocks {
+        for statement in &block.statements {
+            if let Statement::Action(action) = statement {
+                action_lambdas(action, &mut lambdas);
+            }
+        }
+        if let Terminator::Branch { condition: expr, .. } | Terminator::Switch { value: expr, .. }
+            | Terminator::Return(expr) | Terminator::Throw(expr) = &block.terminator {
+            expression_lambdas(expr, &mut lambdas);
+        }
+    }
+    for function in nested_functions(actions).into_iter().chain(lambdas) {
+        let params: Vec<String> = function.params.iter().map(|param| param.name.clone()).collect();
+        analyze(Some(&function.name), &params, &function.body, &locals, warnings);
+    }
+}
+
+/// The functions and methods declared by the actions, including in nested blocks but not in nested functions
+fn nested_functions(actions: &[Action]) -> Vec<&Function> {
+    let mut functions = Vec::new();
+    for action in actions {
+        match action {
+            Action::Function(function) => functions.push(function),
+            Action::Impl(implementation) => functions.extend(&implementation.methods),
+            Action::Conditional(conditional) => {
+                functions.extend(nested_functions(&conditional.then_actions));
+                functions.extend(nested_functions(&conditional.else_actions));
+            }
+            Action::Loop(loop_) => {
+                functions.extend(nested_functions(&loop_.init));
+                functions.extend(nested_functions(&loop_.body));
+                functions.extend(nested_functions(&loop_.update));
+            }
+            Action::ForIn(for_in) => functions.extend(nested_functions(&for_in.body)),
+            Action::Switch(switch) => switch.cases.iter().for_each(|case| functions.extend(nested_functions(&case.body))),
+            Action::Try(try_) => {
+                functions.extend(nested_functions(&try_.body));
+                if let Some(catch) = &try_.catch {
+                    functions.extend(nested_functions(&catch.body));
+                }
+                if let Some(finally) = &try_.finally {
+                    functions.extend(nested_functions(finally));
+                }
+            }
+            _ => {}
+        }
+    }
+    functions
+}
+
+/// The lambdas a block-free action creates, not counting those created inside them
+fn action_lambdas<'a>(action: &'a Action, lambdas: &mut Vec<&'a Function>) {
+    match action {
+        Action::Variable(variable) => data_lambdas(&variable.data, lambdas),
+        Action::Binding(binding) => binding.value.iter().for_each(|value| expression_lambdas(value, lambdas)),
+        Action::Operation(operation) => {
+            expression_lambdas(&operation.left, lambdas);
+            expression_lambdas(&operation.right, lambdas);
+        }
+        Action::Expression(expr) => expression_lambdas(expr, lambdas),
+        Action::Destructure(destructure) => expression_lambdas(&destructure.value, lambdas),
+        // blocks are split up by the builder, nested functions are found by `nested_functions`
+        _ => {}
+    }
+}
+
+fn data_lambdas<'a>(data: &'a VariableData, lambdas: &mut Vec<&'a Function>) {
+    match data {
+        VariableData::Expression(expr) => expression_lambdas(expr, lambdas),
+        VariableData::Array(elements) => elements.iter().for_each(|element| data_lambdas(element, lambdas)),
+        VariableData::StructInstance(_, fields) => fields.iter().for_each(|(_, field)| data_lambdas(field, lambdas)),
+        VariableData::Literal(_) | VariableData::Null => {}
+    }
+}
+
+fn expression_lambdas<'a>(expr: &'a Expression, lambdas: &mut Vec<&'a Function>) {
+    match expr {
+        Expression::Lambda(function) => lambdas.push(function),
+        Expression::Operation(operation) => {
+            expression_lambdas(&operation.left, lambdas);
+            expression_lambdas(&operation.right, lambdas);
+        }
+        Expression::FunctionCall(call) => call.args.iter().for_each(|arg| expression_lambdas(arg, lambdas)),
+        Expression::Call(call) => {
+            expression_lambdas(&call.callee, lambdas);
+            call.args.iter().for_each(|arg| expression_lambdas(arg, lambdas));
+        }
+        Expression::Array(elements) | Expression::Tuple(elements) => elements.iter().for_each(|element| expression_lambdas(element, lambdas)),
+        Expression::Variant(constructor) => constructor.args.iter().for_each(|arg| expression_lambdas(arg, lambdas)),
+        Expression::Match(match_expr) => {
+            expression_lambdas(&match_expr.scrutinee, lambdas);
+            for arm in &match_expr.arms {
+                arm.guard.iter().for_each(|guard| expression_lambdas(guard, lambdas));
+                expression_lambdas(&arm.body, lambdas);
+            }
+        }
+        Expression::Range(range) => {
+            expression_lambdas(&range.start, lambdas);
+            expression_lambdas(&range.end, lambdas);
+            range.step.iter().for_each(|step| expression_lambdas(step, lambdas));
+        }
+        Expression::Allocation(allocation) => {
+            allocation.sizes.iter().for_each(|size| expression_lambdas(size, lambdas));
+            allocation.initializer.iter().for_each(|initializer| expression_lambdas(initializer, lambdas));
+        }
+        Expression::SizeOf(SizeOf::Value(value)) | Expression::NamedArgument(_, value) => expression_lambdas(value, lambdas),
+        Expression::SizeOf(SizeOf::Unsized(_)) | Expression::Literal(_) | Expression::Variable(..) => {}
+    }
+}
@@ -0,0 +1,1471 @@
+use std::collections::HashMap;
+use super::ir::IR;
+use super::types::{Operator, Literal, Typing, VariableData, BindingKind, Attribute, Item};
+use super::expressions::{Expression, Operation, FunctionCall, VariantConstructor, Match, Pattern, Range, Allocation, SizeOf};
+use super::actions::{Action, Function, Struct, Enum, Variant, Interface, ForIn, Switch, Destructure, Try};
+
+/// The type checker walks the IR before execution and validates everything whose type is known statically:
+/// - arguments passed to functions, struct constructors and enum variants, and which overload of a function is called
+/// - instantiation of generic functions and structs, explicit (`first<int>(xs)`) or inferred (`first(xs)`)
+/// - returned values against the declared return type
+/// - impl blocks against the interface they implement, and values passed where an interface is expected
+/// - match patterns against the value they destructure
+/// - assignments to constants and `let` bindings, and that constants have a value known before running
+/// - values assigned to variables, parameters and struct fields declared with a type, which they keep
+/// - attributes on functions and structs, and the arguments they take
+/// - that `break` and `continue` are inside a loop, and that their labels name an enclosing loop
+/// - that switch case labels are distinct constants of the switched value's type
+///
+/// Problems that don't stop the program from running, like a match missing enum variants, are collected as warnings.
+///
+/// Values are dynamically typed at runtime, so anything the checker cannot infer is left to the `Program`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TypeChecker {
+    // overloads of each function, in the order they were declared
+    functions: HashMap<String, Vec<Function>>,
+    structs: HashMap<String, Struct>,
+    enums: HashMap<String, Enum>,
+    interfaces: HashMap<String, Interface>,
+    // methods from impl blocks, and the interfaces implemented, by the name of the implementing type
+    methods: HashMap<String, HashMap<String, Function>>,
+    implementations: HashMap<String, Vec<String>>,
+    variables: HashMap<String, Typing>,
+    // the types variables were declared with, like `int x`, which assigning them must keep to
+    declared: HashMap<String, Typing>,
+    // bindings that can't be assigned to, and the values of constants
+    immutable: HashMap<String, BindingKind>,
+    constants: HashMap<String, Literal>,
+    return_type: Option<Typing>,
+    // whether the code being checked is in a function, which `?` can return from
+    in_function: bool,
+    // the type of the values yielded by the generator being checked, None outside of generators
+    yields: Option<Option<Typing>>,
+    // labels of the loops enclosing the code being checked, innermost last
+    loops: Vec<Option<String>>,
+    // how many switches enclose the code being checked, an unlabeled `break` can leave them too
+    switches: usize,
+    // namespaces of imported modules, whose symbols are only known at runtime
+    modules: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl TypeChecker {
+    pub fn new() -> Self {
+        let mut checker = TypeChecker::default();
+        checker.enums.insert(String::from("Result"), Enum::result());
+        checker.structs.insert(String::from("Error"), Struct::error());
+        checker
+    }
+
+    /// Functions are declared before the code around them is checked and their bodies are checked after it,
+    /// so they can be called before they are written, and see the bindings of the scope they are declared in
+    pub fn check(&mut self, actions: &[Action]) -> Result<(), String> {
+        let mut functions = Vec::new();
+        for action in actions {
+            if let Action::Function(function) = action {
+                self.declare_function(function)?;
+                functions.push(function);
+            }
+        }
+        for action in actions {
+            self.check_action(action)?;
+        }
+        for function in functions {
+            self.check_function(function)
+                .map_err(|err| format!("In function '{}': {}", function.name, err))?;
+        }
+        Ok(())
+    }
+
+    /// Checks a nested block, after which the constants and `let` bindings it declared are out of scope
+    fn check_block(&mut self, actions: &[Action]) -> Result<(), String> {
+        let immutable = self.immutable.clone();
+        let constants = self.constants.clone();
+        let result = self.check(actions);
+        self.immutable = immutable;
+        self.constants = constants;
+        result
+    }
+
+    fn declare_function(&mut self, function: &Function) -> Result<(), String> {
+        let overloads = self.functions.entry(function.name.clone()).or_default();
+        let typings = |f: &Function| f.params.iter().map(|p| p.typing.clone()).collect::<Vec<Typing>>();
+        if overloads.iter().any(|overload| typings(overload) == typings(function)) {
+            return Err(format!("Function '{}' is already defined", function.signature()));
+        }
+        overloads.push(function.clone());
+        Ok(())
+    }
+
+    fn check_action(&mut self, action: &Action) -> Result<(), String> {
+        match action {
+            // declared and checked by `check`
+            Action::Function(_) => Ok(()),
+            Action::Struct(structure) => {
+                self.structs.insert(structure.name.clone(), structure.clone());
+                Self::check_attributes(&structure.attributes, "struct")
+                    .map_err(|err| format!("In struct '{}': {}", structure.name, err))?;
+                for field in &structure.fields {
+                    self.check_typing(&field.typing)?;
+                }
+                Ok(())
+            }
+            Action::Enum(enumeration) => {
+                self.enums.insert(enumeration.name.clone(), enumeration.clone());
+                for variant in &enumeration.variants {
+                    for field in &variant.fields {
+                        self.check_typing(&field.typing)?;
+                    }
+                }
+                Ok(())
+            }
+            Action::Import(import) => {
+                self.modules.push(crate::modules::namespace(&import.path));
+                Ok(())
+            }
+            Action::Interface(interface) => {
+                self.interfaces.insert(interface.name.clone(), interface.clone());
+                Ok(())
+            }
+            Action::Impl(implementation) => {
+                if let Some(interface) = &implementation.interface {
+                    self.check_implementation(interface, &implementation.target, &implementation.methods)?;
+                }
+                let methods = self.methods.entry(implementation.target.clone()).or_default();
+                for method in &implementation.methods {
+                    methods.insert(method.name.clone(), method.clone());
+                }
+                for method in &implementation.methods {
+                    self.check_function(method)
+                        .map_err(|err| format!("In method '{}' of {}: {}", method.name, implementation.target, err))?;
+                }
+                Ok(())
+            }
+            Action::Binding(binding) => {
+                if self.immutable.get(&binding.name) == Some(&BindingKind::Const) {
+                    return Err(format!("Constant '{}' is already defined", binding.name));
+                }
+                let value = match &binding.value {
+                    Some(value) => value,
+                    None if binding.kind == BindingKind::Var => {
+                        self.declare(&binding.name, binding.typing.clone());
+                        self.set_variable(&binding.name, binding.typing.clone());
+                        self.immutable.remove(&binding.name);
+                        self.constants.remove(&binding.name);
+                        return Ok(());
+                    }
+                    None => return Err(format!("The {} '{}' needs a value", binding.kind.describe(), binding.name)),
+                };
+                let typing = self.infer(value)?;
+                if let Some(expected) = &binding.typing {
+                    self.check_typing(expected)?;
+                    if let Some(actual) = &typing {
+                        Self::check_assignable(&format!("{} '{}'", binding.kind.describe(), binding.name), expected, actual)?;
+                    }
+                }
+                match binding.kind {
+                    BindingKind::Const => {
+                        let constant = self.constant_value(value)
+                            .ok_or(format!("The value of constant '{}' must be a constant expression", binding.name))?;
+                        self.constants.insert(binding.name.clone(), constant);
+                    }
+                    _ => { self.constants.remove(&binding.name); }
+                }
+                match binding.kind {
+                    BindingKind::Var => { self.immutable.remove(&binding.name); }
+                    _ => { self.immutable.insert(binding.name.clone(), binding.kind.clone()); }
+                }
+                self.declare(&binding.name, binding.typing.clone());
+                self.set_variable(&binding.name, binding.typing.clone().or(typing));
+                Ok(())
+            }
+            Action::Variable(variable) => {
+                self.check_mutable(&Expression::Variable(variable.name.clone(), None))?;
+                let typing = match &variable.data {
+                    VariableData::Expression(expr) => self.infer(expr)?,
+                    VariableData::Literal(literal) => Some(Self::literal_typing(literal)),
+                    _ => None,
+                };
+                self.constants.remove(&variable.name);
+                match &variable.typing {
+                    Some(expected) => {
+                        self.check_typing(expected)?;
+                        if let Some(actual) = &typing {
+                            Self::check_assignable(&format!("variable '{}'", variable.name), expected, actual)?;
+                        }
+                        self.declare(&variable.name, Some(expected.clone()));
+                        self.set_variable(&variable.name, Some(expected.clone()));
+                        Ok(())
+                    }
+                    None => self.assign_variable(&variable.name, typing),
+                }
+            }
+            Action::Operation(operation) if matches!(operation.operator, Operator::Break | Operator::Continue) => {
+                let keyword = if operation.operator == Operator::Break { "break" } else { "continue" };
+                match &*operation.left {
+                    Expression::Variable(label, _) if !self.loops.contains(&Some(label.clone())) => {
+                        Err(format!("Unknown loop label '{}' in '{} {}'", label, keyword, label))
+                    }
+                    Expression::Variable(..) => Ok(()),
+                    _ if operation.operator == Operator::Break && self.switches > 0 => Ok(()),
+                    _ if self.loops.is_empty() => Err(format!("'{}' outside of a loop", keyword)),
+                    _ => Ok(()),
+                }
+            }
+            Action::Operation(operation) => {
+                self.infer(&Expression::Operation(operation.clone()))?;
+                Ok(())
+            }
+            Action::Expression(expr) => {
+                self.infer(expr)?;
+                Ok(())
+            }
+            Action::Conditional(conditional) => {
+                self.infer(&conditional.condition)?;
+                // `if (p != null)` lets the branch use `p` as its wrapped type, and `if (p == null)` the else branch
+                let narrowed = match &conditional.condition {
+                    Expression::Operation(op) if matches!(op.operator, Operator::Equal | Operator::NotEqual)
+                        && *op.right == Expression::Literal(Literal::Null) => match &*op.left {
+                        Expression::Variable(name, _) => self.variables.get(name)
+                            .filter(|typing| typing.is_optional())
+                            .map(|typing| (name.clone(), typing.clone(), op.operator == Operator::NotEqual)),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                let (then_narrowed, else_narrowed) = match &narrowed {
+                    Some((_, _, not_null)) => (*not_null, !*not_null),
+                    None => (false, false),
+                };
+                self.check_narrowed(&narrowed, then_narrowed, &conditional.then_actions)?;
+                self.check_narrowed(&narrowed, else_narrowed, &conditional.else_actions)
+            }
+            Action::Loop(looped) => {
+                self.check(&looped.init)?;
+                self.infer(&looped.condition)?;
+                self.enter_loop(&looped.label)?;
+                let result = self.check_block(&looped.body).and_then(|_| self.check(&looped.update));
+                self.loops.pop();
+                result
+            }
+            Action::ForIn(for_in) => self.check_for_in(for_in),
+            Action::Switch(switch) => self.check_switch(switch),
+            Action::Destructure(destructure) => self.check_destructure(destructure),
+            Action::Try(try_) => self.check_try(try_),
+        }
+    }
+
+    /// Anything can be thrown, so the caught error is untyped and only visible inside the catch block
+    fn check_try(&mut self, try_: &Try) -> Result<(), String> {
+        self.check_block(&try_.body)?;
+        if let Some(catch) = &try_.catch {
+            let shadowed = self.variables.remove(&catch.variable);
+            let declared = self.declared.remove(&catch.variable);
+            let immutable = self.immutable.remove(&catch.variable);
+            let result = self.check_block(&catch.body);
+            self.set_variable(&catch.variable, shadowed);
+            self.declare(&catch.variable, declared);
+            if let Some(kind) = immutable {
+                self.immutable.insert(catch.variable.clone(), kind);
+            }
+            result?;
+        }
+        if let Some(finally) = &try_.finally {
+            self.check_block(finally)?;
+        }
+        Ok(())
+    }
+
+    /// Checks a branch, with the optional variable from its condition unwrapped if the branch only runs when it isn't null
+    fn check_narrowed(&mut self, narrowed: &Option<(String, Typing, bool)>, unwrap: bool, actions: &[Action]) -> Result<(), String> {
+        let (name, optional) = match narrowed {
+            Some((name, optional, _)) if unwrap => (name, optional),
+            _ => return self.check_block(actions),
+        };
+        self.set_variable(name, Some(optional.generics[0].clone()));
+        let result = self.check_block(actions);
+        // The branch may have assigned something else, so only restore the optional type if it didn't
+        if self.variables.get(name) == Some(&optional.generics[0]) {
+            self.set_variable(name, Some(optional.clone()));
+        }
+        result
+    }
+
+    /// Null can only be stored where an optional type is expected
+    fn check_assignable(context: &str, expected: &Typing, actual: &Typing) -> Result<(), String> {
+        if expected.accepts(actual) {
+            Ok(())
+        } else if actual.is_null() {
+            Err(format!("Null cannot be assigned to {} of non-optional type {}", context, expected))
+        } else {
+            Err(format!("Type mismatch in {}: expected {}, found {}", context, expected, actual))
+        }
+    }
+
+    /// Operators other than `==` and `!=` need optional values to be unwrapped first
+    fn check_unwrapped(expr: &Expression, typing: &Option<Typing>) -> Result<(), String> {
+        match typing {
+            Some(typing) if typing.is_optional() || typing.is_null() => {
+                let name = expr.describe().map_or("A value".to_string(), |name| format!("'{}'", name));
+                Err(format!("{} of type {} may be null, unwrap it with '??' or check it against null first", name, typing))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Every variable a destructuring assignment binds must be assignable, and the pattern must fit the value
+    fn check_destructure(&mut self, destructure: &Destructure) -> Result<(), String> {
+        let typing = self.infer(&destructure.value)?;
+        for name in destructure.pattern.bindings() {
+            self.check_mutable(&Expression::Variable(name.clone(), None))?;
+            self.constants.remove(name);
+        }
+        self.bind_pattern(&destructure.pattern, typing)?;
+        for name in destructure.pattern.bindings() {
+            let typing = self.variables.get(name).cloned();
+            self.assign_variable(name, typing)?;
+        }
+        Ok(())
+    }
+
+    fn check_switch(&mut self, switch: &Switch) -> Result<(), String> {
+        let value = self.infer(&switch.value)?;
+        let mut labels = Vec::new();
+        let mut has_default = false;
+        for case in &switch.cases {
+            let label = match &case.label {
+                Some(label) => label,
+                None if has_default => return Err("A switch can only have one 'default'".to_string()),
+                None => {
+                    has_default = true;
+                    continue;
+                }
+            };
+            let constant = self.constant_value(label)
+                .ok_or("Case labels must be constant expressions".to_string())?;
+            if labels.contains(&constant) {
+                return Err(format!("Duplicate case label {} in switch", constant));
+            }
+            let typing = Self::literal_typing(&constant);
+            if let Some(value) = &value {
+                if !value.parameter && *value != typing {
+                    return Err(format!("Case label {} of type {} doesn't match the switch value of type {}", constant, typing, value));
+                }
+            }
+            labels.push(constant);
+        }
+        self.switches += 1;
+        let result = switch.cases.iter().try_for_each(|case| self.check_block(&case.body));
+        self.switches -= 1;
+        result
+    }
+
+    fn enter_loop(&mut self, label: &Option<String>) -> Result<(), String> {
+        if let Some(name) = label {
+            if self.loops.contains(label) {
+                return Err(format!("Loop label '{}' is already used by an enclosing loop", name));
+            }
+        }
+        self.loops.push(label.clone());
+        Ok(())
+    }
+
+    /// Checks a for-in loop, with the loop variable typed as the items of the iterable while checking the body
+    fn check_for_in(&mut self, for_in: &ForIn) -> Result<(), String> {
+        let iterable = self.infer(&for_in.iterable)?;
+        let item = match &iterable {
+            Some(typing) => Some(self.item_typing(typing)?),
+            None => None,
+        }.flatten();
+        let item = match (&for_in.typing, item) {
+            (Some(declared), Some(item)) => {
+                self.unify(&format!("for-in variable '{}'", for_in.variable), declared, &item, &mut HashMap::new())?;
+                Some(declared.clone())
+            }
+            (declared, item) => declared.clone().or(item),
+        };
+
+        let saved_variable = self.variables.get(&for_in.variable).cloned();
+        let saved_declared = self.declared.remove(&for_in.variable);
+        let saved_immutable = self.immutable.remove(&for_in.variable);
+        let saved_constant = self.constants.remove(&for_in.variable);
+        self.set_variable(&for_in.variable, item);
+        let result = self.enter_loop(&for_in.label).and_then(|_| {
+            let result = self.check_block(&for_in.body);
+            self.loops.pop();
+            result
+        });
+        self.set_variable(&for_in.variable, saved_variable);
+        self.declare(&for_in.variable, saved_declared);
+        if let Some(kind) = saved_immutable {
+            self.immutable.insert(for_in.variable.clone(), kind);
+        }
+        if let Some(constant) = saved_constant {
+            self.constants.insert(for_in.variable.clone(), constant);
+        }
+        result
+    }
+
+    /// The type of the items a for-in loop gets from a value of the given type, if it is known
+    fn item_typing(&self, iterable: &Typing) -> Result<Option<Typing>, String> {
+        if iterable.array_dimensions > 0 {
+            let mut item = iterable.clone();
+            item.array_dimensions -= 1;
+            return Ok(Some(item));
+        }
+        match iterable.name.as_str() {
+            "string" => Ok(Some(Typing::named("char"))),
+            "range" => Ok(Some(Typing::named("int"))),
+            "generator" => Ok(iterable.generics.first().cloned()),
+            "int" | "float" | "char" | "bool" => Err(format!("Cannot iterate over a value of type {}", iterable)),
+            _ if iterable.parameter => Ok(None),
+            _ => match self.method_signature(iterable, "get") {
+                Some(method) => Ok(method.return_type),
+                None if self.structs.contains_key(&iterable.name) || self.enums.contains_key(&iterable.name) => {
+                    Err(format!("Cannot iterate over a value of type {}, it needs `len` and `get` methods", iterable))
+                }
+                None => Ok(None),
+            },
+        }
+    }
+
+    fn check_range(&mut self, range: &Range) -> Result<Option<Typing>, String> {
+        let bounds = [Some(&range.start), Some(&range.end), range.step.as_ref()];
+        for bound in bounds.into_iter().flatten() {
+            if let Some(typing) = self.infer(bound)? {
+                if typing != Typing::named("int") {
+                    return Err(format!("Range bounds must be int, found {}", typing));
+                }
+            }
+        }
+        if let Some(step) = &range.step {
+            if self.constant_value(step) == Some(Literal::Integer(0)) {
+                return Err("Range step cannot be zero".to_string());
+            }
+        }
+        Ok(Some(Typing::named("range")))
+    }
+
+    /// Array sizes must be constant, non-negative ints, and an initializer can't have more elements than the array
+    fn check_allocation(&mut self, allocation: &Allocation) -> Result<Option<Typing>, String> {
+        let mut sizes = Vec::new();
+        for size in &allocation.sizes {
+            if let Some(typing) = self.infer(size)? {
+                if typing != Typing::named("int") {
+                    return Err(format!("Array size must be an int, found {}", typing));
+                }
+            }
+            match self.constant_value(size) {
+                Some(Literal::Integer(size)) if size < 0 => return Err(format!("Array size {} is negative", size)),
+                Some(Literal::Integer(size)) => sizes.push(size),
+                _ => return Err("Array size must be a constant expression".to_string()),
+            }
+        }
+        let mut typing = allocation.element.clone();
+        typing.array_dimensions += sizes.len();
+        if let Some(initializer) = &allocation.initializer {
+            if let (Some(size), Expression::Array(elements)) = (sizes.first(), &**initializer) {
+                if elements.len() as i64 > *size {
+                    return Err(format!("Too many initializers for an array of size {}: found {}", size, elements.len()));
+                }
+            }
+            if let Some(actual) = self.infer(initializer)? {
+                if actual != typing {
+                    return Err(format!("Type mismatch in array initializer: expected {}, found {}", typing, actual));
+                }
+            }
+        }
+        Ok(Some(typing))
+    }
+
+    /// Validates the attributes on a declaration of the given kind ("function" or "struct")
+    fn check_attributes(attributes: &[Attribute], kind: &str) -> Result<(), String> {
+        let mut seen = Vec::new();
+        for attribute in attributes {
+            if seen.contains(&&attribute.name) {
+                return Err(format!("Attribute '@{}' is repeated", attribute.name));
+            }
+            seen.push(&attribute.name);
+            match attribute.name.as_str() {
+                "deprecated" => {
+                    if attribute.arguments.len() > 1 {
+                        return Err("'@deprecated' takes at most one note".to_string());
+                    }
+                }
+                "test" | "inline" | "memoize" | "export" => {
+                    if !attribute.arguments.is_empty() {
+                        return Err(format!("'@{}' takes no arguments", attribute.name));
+                    }
+                    if kind != "function" && attribute.name != "export" {
+                        return Err(format!("'@{}' can only be used on functions", attribute.name));
+                    }
+                }
+                _ => return Err(format!("Unknown attribute '@{}'", attribute.name)),
+            }
+        }
+        Ok(())
+    }
+
+    fn check_function(&mut self, function: &Function) -> Result<(), String> {
+        Self::check_attributes(&function.attributes, "function")?;
+        for param in &function.params {
+            self.check_typing(&param.typing)?;
+        }
+        if let Some(return_type) = &function.return_type {
+            self.check_typing(return_type)?;
+        }
+
+        // The body is checked in its own scope, with the parameters typed as declared
+        let saved_variables = self.variables.clone();
+        let saved_declared = self.declared.clone();
+        let saved_immutable = self.immutable.clone();
+        let saved_constants = self.constants.clone();
+        let saved_return_type = self.return_type.take();
+        let saved_in_function = std::mem::replace(&mut self.in_function, true);
+        let saved_yields = self.yields.take();
+        let saved_loops = std::mem::take(&mut self.loops);
+        let saved_switches = std::mem::take(&mut self.switches);
+        for param in &function.params {
+            // The arguments collected by an untyped variadic parameter can be of any type
+            if param.typing.name == "any" {
+                self.variables.remove(&param.name);
+                self.declared.remove(&param.name);
+            } else {
+                self.variables.insert(param.name.clone(), param.typing.clone());
+                self.declared.insert(param.name.clone(), param.typing.clone());
+            }
+            self.immutable.remove(&param.name);
+            self.constants.remove(&param.name);
+        }
+        // The declared type of a generator is the type of what it yields, it returns nothing
+        if function.generator {
+            self.yields = Some(function.return_type.clone());
+        } else {
+            self.return_type = function.return_type.clone();
+        }
+        let result = self.check_defaults(function).and_then(|_| self.check(&function.body));
+        self.variables = saved_variables;
+        self.declared = saved_declared;
+        self.immutable = saved_immutable;
+        self.constants = saved_constants;
+        self.return_type = saved_return_type;
+        self.in_function = saved_in_function;
+        self.yields = saved_yields;
+        self.loops = saved_loops;
+        self.switches = saved_switches;
+        result
+    }
+
+    /// Checks the default values of the parameters, which are evaluated in the scope of the function
+    fn check_defaults(&mut self, function: &Function) -> Result<(), String> {
+        for (param, default) in function.params.iter().zip(&function.defaults) {
+            if let Some(typing) = default.as_ref().map(|default| self.infer(default)).transpose()?.flatten() {
+                Self::check_assignable(&format!("default value of parameter '{}'", param.name), &param.typing, &typing)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that an impl block provides every method of the interface with a matching signature
+    fn check_implementation(&mut self, interface: &str, target: &str, methods: &[Function]) -> Result<(), String> {
+        let interface = self.interfaces.get(interface).cloned()
+            .ok_or(format!("Interface '{}' not found", interface))?;
+        for signature in &interface.methods {
+            let method = methods.iter().find(|m| m.name == signature.name)
+                .ok_or(format!("impl {} for {} is missing method '{}'", interface.name, target, signature.name))?;
+            let expected: Vec<Typing> = signature.params.iter().map(|p| Self::bind_self(&p.typing, target)).collect();
+            let actual: Vec<Typing> = method.params.iter().map(|p| p.typing.clone()).collect();
+            let expected_return = signature.return_type.as_ref().map(|t| Self::bind_self(t, target));
+            if expected != actual || expected_return != method.return_type {
+                return Err(format!("Method '{}' in impl {} for {} does not match its signature in the interface", method.name, interface.name, target));
+            }
+        }
+        self.implementations.entry(target.to_string()).or_default().push(interface.name.clone());
+        Ok(())
+    }
+
+    /// Replaces `Self` in an interface method signature with the implementing type
+    fn bind_self(typing: &Typing, target: &str) -> Typing {
+        let mut result = typing.clone();
+        if result.name == "Self" && !result.parameter {
+            result.name = target.to_string();
+        }
+        result.generics = typing.generics.iter().map(|g| Self::bind_self(g, target)).collect();
+        result
+    }
+
+    /// Finds the signature of a method callable on a value of the receiver's type
+    fn method_signature(&self, receiver: &Typing, name: &str) -> Option<Function> {
+        if receiver.array_dimensions > 0 || receiver.parameter {
+            return None;
+        }
+        if let Some(interface) = self.interfaces.get(&receiver.name) {
+            let signature = interface.methods.iter().find(|m| m.name == name)?;
+            return Some(Function {
+                attributes: Vec::new(),
+                name: signature.name.clone(),
+                type_params: Vec::new(),
+                params: signature.params.iter()
+                    .map(|p| Item { name: p.name.clone(), typing: Self::bind_self(&p.typing, &receiver.name) })
+                    .collect(),
+                defaults: vec![None; signature.params.len()],
+                variadic: false,
+                return_type: signature.return_type.as_ref().map(|t| Self::bind_self(t, &receiver.name)),
+                body: Vec::new(),
+                generator: false,
+            });
+        }
+        self.methods.get(&receiver.name)?.get(name).cloned()
+    }
+
+    fn implements(&self, typing: &Typing, interface: &str) -> bool {
+        typing.name == interface || self.implementations.get(&typing.name)
+            .is_some_and(|interfaces| interfaces.iter().any(|i| i == interface))
+    }
+
+    /// Checks that a declared type gives a generic struct the right number of type arguments
+    fn check_typing(&self, typing: &Typing) -> Result<(), String> {
+        let type_params = self.structs.get(&typing.name).map(|s| &s.type_params)
+            .or_else(|| self.enums.get(&typing.name).map(|e| &e.type_params));
+        if let Some(type_params) = type_params {
+            if !typing.generics.is_empty() && typing.generics.len() != type_params.len() {
+                return Err(format!("'{}' expects {} type arguments, but {} were provided", typing.name, type_params.len(), typing.generics.len()));
+            }
+        }
+        for generic in &typing.generics {
+            self.check_typing(generic)?;
+        }
+        Ok(())
+    }
+
+    /// Records the type a variable is declared with, None for an untyped declaration like `var x = 1;`
+    fn declare(&mut self, name: &str, typing: Option<Typing>) {
+        match typing {
+            Some(typing) => { self.declared.insert(name.to_string(), typing); }
+            None => { self.declared.remove(name); }
+        }
+    }
+
+    /// Gives a variable the type of the value assigned to it, unless it was declared with a type the value must fit
+    fn assign_variable(&mut self, name: &str, typing: Option<Typing>) -> Result<(), String> {
+        match self.declared.get(name).cloned() {
+            Some(declared) => {
+                if let Some(actual) = &typing {
+                    Self::check_assignable(&format!("variable '{}'", name), &declared, actual)?;
+                }
+                self.set_variable(name, Some(declared));
+            }
+            None => self.set_variable(name, typing),
+        }
+        Ok(())
+    }
+
+    fn set_variable(&mut self, name: &str, typing: Option<Typing>) {
+        match typing {
+            Some(typing) => { self.variables.insert(name.to_string(), typing); }
+            None => { self.variables.remove(name); }
+        }
+    }
+
+    fn literal_typing(literal: &Literal) -> Typing {
+        match literal {
+            Literal::Integer(_) => Typing::named("int"),
+            Literal::Float(_) => Typing::named("float"),
+            Literal::String(_) => Typing::named("string"),
+            Literal::Character(_) => Typing::named("char"),
+            Literal::Boolean(_) => Typing::named("bool"),
+            Literal::Null => Typing::named("null"),
+        }
+    }
+
+    /// Infers the type of an expression, returning `None` when it can't be known before running
+    fn infer(&mut self, expr: &Expression) -> Result<Option<Typing>, String> {
+        match expr {
+            Expression::Literal(literal) => Ok(Some(Self::literal_typing(literal))),
+            Expression::Variable(name, _) => {
+                if !self.variables.contains_key(name) && self.functions.contains_key(name) {
+                    return Ok(Some(Typing::named("fn")));
+                }
+                Ok(self.variables.get(name).cloned())
+            }
+            Expression::Lambda(function) => {
+                self.check_function(function).map_err(|err| match function.name.as_str() {
+                    "" => format!("In anonymous function: {}", err),
+                    name => format!("In function '{}': {}", name, err),
+                })?;
+                Ok(Some(Typing::named("fn")))
+            }
+            Expression::Range(range) => self.check_range(range),
+            Expression::Allocation(allocation) => self.check_allocation(allocation),
+            Expression::Tuple(elements) => {
+                let mut typings = Vec::new();
+                for element in elements {
+                    typings.push(self.infer(element)?);
+                }
+                Ok(typings.into_iter().collect::<Option<Vec<Typing>>>().map(Typing::tuple))
+            }
+            Expression::SizeOf(SizeOf::Value(value)) => {
+                self.infer(value)?;
+                Ok(Some(Typing::named("int")))
+            }
+            Expression::SizeOf(SizeOf::Unsized(typing)) => {
+                Err(format!("sizeof needs a fixed-size array type, found {}", typing))
+            }
+            Expression::Call(call) => {
+                self.infer(&call.callee)?;
+                for arg in &call.args {
+                    self.infer(arg)?;
+                }
+                Ok(None)
+            }
+            Expression::Array(elements) => {
+                let mut element_typing = None;
+                for element in elements {
+                    let typing = self.infer(element)?;
+                    if element_typing.is_none() {
+                        element_typing = typing;
+                    }
+                }
+                Ok(element_typing.map(|mut typing| {
+                    typing.array_dimensions += 1;
+                    typing
+                }))
+            }
+            Expression::FunctionCall(call) => self.check_call(call),
+            Expression::NamedArgument(_, value) => self.infer(value),
+            Expression::Variant(constructor) => self.check_variant(constructor),
+            Expression::Match(match_expr) => self.check_match(match_expr),
+            Expression::Operation(op) => {
+                match op.operator {
+                    Operator::Assignment => {
+                        self.check_mutable(&op.left)?;
+                        let typing = self.infer(&op.right)?;
+                        match &*op.left {
+                            Expression::Variable(name, _) => self.assign_variable(name, typing.clone())?,
+                            Expression::Operation(member) if member.operator == Operator::MemberAccess => {
+                                if let (Some(expected), Some(actual)) = (self.infer(&op.left)?, &typing) {
+                                    let name = op.left.describe().unwrap_or_default();
+                                    Self::check_assignable(&format!("member '{}'", name), &expected, actual)?;
+                                }
+                            }
+                            _ => { self.infer(&op.left)?; }
+                        }
+                        Ok(typing)
+                    }
+                    Operator::MemberAccess => {
+                        let object = self.infer(&op.left)?;
+                        let member = match &*op.right {
+                            Expression::Literal(Literal::String(member)) => member,
+                            _ => return Ok(None),
+                        };
+                        if object.as_ref().is_some_and(|typing| typing.is_optional() || typing.is_null()) {
+                            let name = op.left.describe().map_or("a value".to_string(), |name| format!("'{}'", name));
+                            return Err(format!("Cannot access '{}' because {} may be null, use '?.' instead", member, name));
+                        }
+                        self.member_typing(object, member)
+                    }
+                    Operator::OptionalMemberAccess => {
+                        let object = self.infer(&op.left)?;
+                        let member = match &*op.right {
+                            Expression::Literal(Literal::String(member)) => member,
+                            _ => return Ok(None),
+                        };
+                        let (object, nullable) = match object {
+                            Some(typing) if typing.is_optional() => (Some(typing.generics[0].clone()), true),
+                            Some(typing) if typing.is_null() => return Ok(Some(typing)),
+                            object => (object, false),
+                        };
+                        let typing = self.member_typing(object, member)?;
+                        Ok(typing.map(|typing| if nullable && !typing.is_optional() { Typing::optional(typing) } else { typing }))
+                    }
+                    Operator::Coalesce => {
+                        let left = self.infer(&op.left)?;
+                        let right = self.infer(&op.right)?;
+                        Ok(match (left, right) {
+                            (Some(left), Some(right)) if left.is_optional() && left.generics[0] == right => Some(right),
+                            (Some(left), right) if left.is_null() => right,
+                            (Some(left), Some(right)) if left == right => Some(left),
+                            _ => None,
+                        })
+                    }
+                    Operator::Yield => {
+                        let typing = self.infer(&op.left)?;
+                        match (&self.yields, &typing) {
+                            (None, _) => Err("'yield' can only be used inside a generator function, declared with 'fn*'".to_string()),
+                            (Some(Some(expected)), Some(actual)) if !expected.accepts(actual) => {
+                                Err(format!("Expected a yielded value of type {}, found {}", expected, actual))
+                            }
+                            _ => Ok(None),
+                        }
+                    }
+                    Operator::Try => {
+                        let typing = self.infer(&op.left)?;
+                        if !self.in_function {
+                            return Err("'?' can only be used inside a function".to_string());
+                        }
+                        if self.yields.is_some() {
+                            return Err("'?' cannot return from a generator, use 'throw' instead".to_string());
+                        }
+                        let is_result = |typing: &Typing| typing.name == "Result" && typing.array_dimensions == 0 && !typing.parameter;
+                        if let Some(expected) = &self.return_type {
+                            if !is_result(expected) {
+                                return Err(format!("'?' can only be used in a function that returns a Result, not {}", expected));
+                            }
+                        }
+                        let typing = match typing {
+                            Some(typing) if !is_result(&typing) => return Err(format!("'?' needs a Result, found {}", typing)),
+                            Some(typing) if typing.generics.len() == 2 => typing,
+                            _ => return Ok(None),
+                        };
+                        if let Some(expected) = self.return_type.as_ref().filter(|t| t.generics.len() == 2) {
+                            if expected.generics[1] != typing.generics[1] {
+                                return Err(format!("'?' cannot return an error of type {} from a function returning {}", typing.generics[1], expected));
+                            }
+                        }
+                        Ok(Some(typing.generics[0].clone()))
+                    }
+                    Operator::ArrayAccess => {
+                        let array = self.infer(&op.left)?;
+                        self.infer(&op.right)?;
+                        Ok(array.and_then(|mut typing| {
+                            if typing.array_dimensions == 0 {
+                                return None;
+                            }
+                            typing.array_dimensions -= 1;
+                            Some(typing)
+                        }))
+                    }
+                    Operator::Return => {
+                        let typing = self.infer(&op.left)?;
+                        if let (Some(expected), Some(actual)) = (&self.return_type, &typing) {
+                            if !expected.accepts(actual) {
+                                return Err(format!("Expected a return value of type {}, found {}", expected, actual));
+                            }
+                        }
+                        Ok(typing)
+                    }
+                    Operator::BitwiseAnd | Operator::BitwiseOr | Operator::BitwiseXor |
+                    Operator::ShiftLeft | Operator::ShiftRight | Operator::BitwiseNot => self.check_bitwise(op),
+                    Operator::Add | Operator::Subtract | Operator::Multiply | Operator::Divide | Operator::Modulo => {
+                        let left = self.infer(&op.left)?;
+                        let right = self.infer(&op.right)?;
+                        Self::check_unwrapped(&op.left, &left)?;
+                        Self::check_unwrapped(&op.right, &right)?;
+                        if left == right {
+                            Ok(left)
+                        } else {
+                            Ok(None)
+                        }
+                    }
+                    Operator::Less | Operator::LessEqual | Operator::Greater | Operator::GreaterEqual => {
+                        let left = self.infer(&op.left)?;
+                        let right = self.infer(&op.right)?;
+                        Self::check_unwrapped(&op.left, &left)?;
+                        Self::check_unwrapped(&op.right, &right)?;
+                        Ok(None)
+                    }
+                    _ => {
+                        self.infer(&op.left)?;
+                        self.infer(&op.right)?;
+                        Ok(None)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Bitwise operators take ints, or user types that overload them, and shifts need an amount that fits an int
+    fn check_bitwise(&mut self, op: &Operation) -> Result<Option<Typing>, String> {
+        let left = self.infer(&op.left)?;
+        let right = self.infer(&op.right)?;
+        Self::check_unwrapped(&op.left, &left)?;
+        Self::check_unwrapped(&op.right, &right)?;
+        let operands = if op.operator == Operator::BitwiseNot { vec![&left] } else { vec![&left, &right] };
+        for typing in operands.into_iter().flatten() {
+            let scalar = matches!(typing.name.as_str(), "float" | "string" | "char" | "bool");
+            if typing.array_dimensions > 0 || scalar {
+                return Err(format!("Bitwise operators need int operands, found {}", typing));
+            }
+        }
+        if matches!(op.operator, Operator::ShiftLeft | Operator::ShiftRight) {
+            if let Some(Literal::Integer(amount)) = self.constant_value(&op.right) {
+                if !(0..64).contains(&amount) {
+                    return Err(format!("Shift amount {} is out of range, it must be between 0 and 63", amount));
+                }
+            }
+        }
+        if op.operator == Operator::BitwiseNot || left == right {
+            Ok(left)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Rejects assignments to constants and `let` bindings, including to their members and elements
+    fn check_mutable(&self, target: &Expression) -> Result<(), String> {
+        match target {
+            Expression::Variable(name, _) => match self.immutable.get(name) {
+                Some(kind) => Err(format!("Cannot assign to {} '{}'", kind.describe(), name)),
+                None => Ok(()),
+            },
+            Expression::Operation(op) if matches!(op.operator, Operator::MemberAccess | Operator::ArrayAccess) => {
+                self.check_mutable(&op.left)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Evaluates an expression made only of literals and constants, returning `None` for anything else
+    pub fn constant_value(&self, expr: &Expression) -> Option<Literal> {
+        match expr {
+            Expression::Literal(literal) => Some(literal.clone()),
+            Expression::Variable(name, _) => self.constants.get(name).cloned(),
+            Expression::Operation(op) => {
+                let left = self.constant_value(&op.left)?;
+                let right = self.constant_value(&op.right)?;
+                match (left, right) {
+                    (Literal::Integer(a), Literal::Integer(b)) => match op.operator {
+                        Operator::Add => a.checked_add(b).map(Literal::Integer),
+                        Operator::Subtract => a.checked_sub(b).map(Literal::Integer),
+                        Operator::Multiply => a.checked_mul(b).map(Literal::Integer),
+                        Operator::Divide => a.checked_div(b).map(Literal::Integer),
+                        Operator::Modulo => a.checked_rem(b).map(Literal::Integer),
+                        Operator::BitwiseAnd => Some(Literal::Integer(a & b)),
+                        Operator::BitwiseOr => Some(Literal::Integer(a | b)),
+                        Operator::BitwiseXor => Some(Literal::Integer(a ^ b)),
+                        Operator::ShiftLeft if (0..64).contains(&b) => Some(Literal::Integer(a << b)),
+                        Operator::ShiftRight if (0..64).contains(&b) => Some(Literal::Integer(a >> b)),
+                        Operator::BitwiseNot => Some(Literal::Integer(!a)),
+                        _ => None,
+                    },
+                    (Literal::Float(a), Literal::Float(b)) => match op.operator {
+                        Operator::Add => Some(Literal::Float(a + b)),
+                        Operator::Subtract => Some(Literal::Float(a - b)),
+                        Operator::Multiply => Some(Literal::Float(a * b)),
+                        Operator::Divide => Some(Literal::Float(a / b)),
+                        _ => None,
+                    },
+                    (Literal::String(a), Literal::String(b)) if op.operator == Operator::Add => Some(Literal::String(a + &b)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn member_typing(&self, object: Option<Typing>, member: &str) -> Result<Option<Typing>, String> {
+        let object = match object {
+            Some(object) if object.array_dimensions == 0 && !object.parameter => object,
+            _ => return Ok(None),
+        };
+        if object.name == "tuple" {
+            return member.parse::<usize>().ok()
+                .and_then(|index| object.generics.get(index).cloned())
+                .map(Some)
+                .ok_or(format!("Tuple {} has no element {}", object, member));
+        }
+        let structure = match self.structs.get(&object.name) {
+            Some(structure) => structure,
+            None => return Ok(None),
+        };
+        let field = structure.fields.iter().find(|f| f.name == member)
+            .ok_or(format!("Struct '{}' has no field '{}'", structure.name, member))?;
+        let bindings: HashMap<String, Typing> = structure.type_params.iter().cloned()
+            .zip(object.generics.iter().cloned())
+            .collect();
+        Ok(Self::substitute(&field.typing, &bindings))
+    }
+
+    /// Checks the arguments of a call to a function or a struct constructor, and returns the resulting type
+    fn check_call(&mut self, call: &FunctionCall) -> Result<Option<Typing>, String> {
+        let mut arg_typings = Vec::new();
+        for arg in &call.args {
+            arg_typings.push(self.infer(arg)?);
+        }
+        for type_arg in &call.type_args {
+            self.check_typing(type_arg)?;
+        }
+
+        // Calls through a variable holding a function can't be checked until the function is known
+        if self.variables.get(&call.name).is_some_and(|typing| typing.name == "fn") {
+            return Ok(None);
+        }
+
+        // Methods are dispatched on the type of the first argument, like in the `Program`
+        if let Some(Some(receiver)) = arg_typings.first() {
+            if let Some(method) = self.method_signature(receiver, &call.name) {
+                let context = format!("call to method '{}'", call.name);
+                let (params, args) = Self::align_arguments(&context, "parameter", &method.params, &method.defaults, method.variadic, &call.args, arg_typings)?;
+                self.instantiate(&context, &[], &params, &call.type_args, &args)?;
+                return Ok(method.return_type);
+            }
+        }
+
+        if let Some(overloads) = self.functions.get(&call.name) {
+            return self.check_overloads(call, overloads, arg_typings);
+        }
+        if let Some(structure) = self.structs.get(&call.name) {
+            let context = format!("constructor '{}'", structure.name);
+            let (fields, args) = Self::align_arguments(&context, "field", &structure.fields, &[], false, &call.args, arg_typings)?;
+            let bindings = self.instantiate(&context, &structure.type_params, &fields, &call.type_args, &args)?;
+            let mut typing = Typing::named(&structure.name);
+            if structure.type_params.iter().all(|p| bindings.contains_key(p)) {
+                typing.generics = structure.type_params.iter().map(|p| bindings[p].clone()).collect();
+            }
+            return Ok(Some(typing));
+        }
+        Ok(None)
+    }
+
+    /// Resolves a call to one of the overloads of a function, the same way the `Program` does:
+    /// an overload taking the arguments as declared is preferred, then the one with the most concrete parameter types.
+    /// Calls that more than one overload fits are left to the `Program` when some argument types are unknown.
+    fn check_overloads(&self, call: &FunctionCall, overloads: &[Function], arg_typings: Vec<Option<Typing>>) -> Result<Option<Typing>, String> {
+        if let [function] = overloads {
+            return self.check_function_call(call, function, arg_typings);
+        }
+        let mut best: Vec<(&Function, Option<Typing>, (bool, usize))> = Vec::new();
+        for overload in overloads {
+            let Ok(typing) = self.check_function_call(call, overload, arg_typings.clone()) else {
+                continue;
+            };
+            let exact = !overload.variadic && call.args.len() == overload.params.len();
+            let concrete = overload.params.iter().filter(|p| !p.typing.parameter && p.typing.name != "any").count();
+            let rank = (exact, concrete);
+            match best.first() {
+                Some((_, _, best_rank)) if *best_rank > rank => {}
+                Some((_, _, best_rank)) if *best_rank == rank => best.push((overload, typing, rank)),
+                _ => best = vec![(overload, typing, rank)],
+            }
+        }
+        let types: Vec<String> = call.args.iter().zip(&arg_typings)
+            .map(|(arg, typing)| {
+                let typing = typing.as_ref().map_or("unknown".to_string(), Typing::to_string);
+                match arg {
+                    Expression::NamedArgument(name, _) => format!("{}: {}", name, typing),
+                    _ => typing,
+                }
+            })
+            .collect();
+        let candidates = |functions: Vec<&Function>| functions.iter().map(|f| format!("\n    {}", f.signature())).collect::<String>();
+        match best.as_slice() {
+            [(_, typing, _)] => Ok(typing.clone()),
+            [] => Err(format!("No overload of '{}' matches the arguments ({}), the candidates are:{}",
+                call.name, types.join(", "), candidates(overloads.iter().collect()))),
+            _ if arg_typings.iter().any(Option::is_none) => Ok(None),
+            _ => Err(format!("Call to '{}' with arguments ({}) is ambiguous, it matches:{}",
+                call.name, types.join(", "), candidates(best.iter().map(|(f, _, _)| *f).collect()))),
+        }
+    }
+
+    /// Checks the arguments of a call to a function against its parameters, and returns the type of its result
+    fn check_function_call(&self, call: &FunctionCall, function: &Function, arg_typings: Vec<Option<Typing>>) -> Result<Option<Typing>, String> {
+        let context = format!("call to '{}'", function.name);
+        let (params, args) = Self::align_arguments(&context, "parameter", &function.params, &function.defaults, function.variadic, &call.args, arg_typings)?;
+        let bindings = self.instantiate(&context, &function.type_params, &params, &call.type_args, &args)?;
+        let return_type = function.return_type.as_ref().and_then(|t| Self::substitute(t, &bindings));
+        if function.generator {
+            let mut generator = Typing::named("generator");
+            generator.generics = return_type.into_iter().collect();
+            return Ok(Some(generator));
+        }
+        Ok(return_type)
+    }
+
+    /// Lines the arguments of a call up with the parameters they are passed to, leaving out the parameters
+    /// that take their default value. Arguments collected by a variadic parameter are matched with its element type.
+    fn align_arguments(context: &str, what: &str, params: &[Item], defaults: &[Option<Expression>], variadic: bool, args: &[Expression], typings: Vec<Option<Typing>>) -> Result<(Vec<Typing>, Vec<Option<Typing>>), String> {
+        let is_named = |arg: &Expression| matches!(arg, Expression::NamedArgument(..));
+        let positional = args.iter().take_while(|arg| !is_named(arg)).count();
+        if !args[positional..].iter().all(is_named) {
+            return Err(format!("Positional arguments must come before named ones in {}", context));
+        }
+        let fixed = params.len() - usize::from(variadic);
+        let required = (0..fixed).filter(|&i| defaults.get(i).is_none_or(Option::is_none)).count();
+        if positional > fixed && !variadic || positional == args.len() && positional < required {
+            let expected = if variadic {
+                format!("at least {}", required)
+            } else if required == fixed {
+                fixed.to_string()
+            } else {
+                format!("between {} and {}", required, fixed)
+            };
+            return Err(format!("{} expects {} arguments, but {} were provided", context, expected, positional));
+        }
+
+        let mut slots: Vec<Option<Option<Typing>>> = vec![None; fixed];
+        let mut rest = Vec::new();
+        for (i, (arg, typing)) in args.iter().zip(typings).enumerate() {
+            let index = match arg {
+                Expression::NamedArgument(name, _) => params.iter().position(|param| param.name == *name)
+                    .ok_or(format!("Unknown {} '{}' in {}", what, name, context))?,
+                _ if i >= fixed => {
+                    rest.push(typing);
+                    continue;
+                }
+                _ => i,
+            };
+            let slot = slots.get_mut(index)
+                .ok_or(format!("Variadic parameter '{}' cannot be passed by name in {}", params[index].name, context))?;
+            if slot.is_some() {
+                return Err(format!("The {} '{}' is given more than once in {}", what, params[index].name, context));
+            }
+            *slot = Some(typing);
+        }
+
+        let mut aligned_params = Vec::new();
+        let mut aligned_args = Vec::new();
+        for (i, slot) in slots.into_iter().enumerate() {
+            match slot {
+                Some(typing) => {
+                    aligned_params.push(params[i].typing.clone());
+                    aligned_args.push(typing);
+                }
+                None if defaults.get(i).is_some_and(Option::is_some) => {}
+                None => return Err(format!("Missing argument for {} '{}' in {}", what, params[i].name, context)),
+            }
+        }
+        if let Some(param) = params.last().filter(|_| variadic) {
+            let mut element = param.typing.clone();
+            element.array_dimensions -= 1;
+            if element.name != "any" {
+                aligned_params.extend(std::iter::repeat_n(element, rest.len()));
+                aligned_args.extend(rest);
+            }
+        }
+        Ok((aligned_params, aligned_args))
+    }
+
+    /// Checks the arguments of an enum variant constructor against the fields of the variant
+    fn check_variant(&mut self, constructor: &VariantConstructor) -> Result<Option<Typing>, String> {
+        let mut arg_typings = Vec::new();
+        for arg in &constructor.args {
+            arg_typings.push(self.infer(arg)?);
+        }
+        if !self.enums.contains_key(&constructor.enum_name) && self.modules.contains(&constructor.enum_name) {
+            return Ok(None);
+        }
+        let enumeration = self.enums.get(&constructor.enum_name)
+            .ok_or(format!("Enum '{}' not found", constructor.enum_name))?;
+        let variant = enumeration.variants.iter().find(|v| v.name == constructor.variant)
+            .ok_or(format!("Enum '{}' has no variant '{}'", enumeration.name, constructor.variant))?;
+        let fields: Vec<Typing> = variant.fields.iter().map(|f| f.typing.clone()).collect();
+        let context = format!("constructor '{}::{}'", enumeration.name, variant.name);
+        let bindings = self.instantiate(&context, &enumeration.type_params, &fields, &[], &arg_typings)?;
+        // `Result::Ok(1)` alone doesn't say what the error type is, so its type is left unknown
+        if !enumeration.type_params.iter().all(|p| bindings.contains_key(p)) {
+            return Ok(None);
+        }
+        let mut typing = Typing::named(&enumeration.name);
+        typing.generics = enumeration.type_params.iter().map(|p| bindings[p].clone()).collect();
+        Ok(Some(typing))
+    }
+
+    /// Checks every arm of a match, and returns the type of the arms if they all agree
+    fn check_match(&mut self, match_expr: &Match) -> Result<Option<Typing>, String> {
+        let scrutinee = self.infer(&match_expr.scrutinee)?;
+        let mut result: Option<Option<Typing>> = None;
+        for arm in &match_expr.arms {
+            // Bindings are only visible inside their arm
+            let saved_variables = self.variables.clone();
+            let saved_declared = self.declared.clone();
+            for name in arm.pattern.bindings() {
+                self.declared.remove(name);
+            }
+            let typing = self.bind_pattern(&arm.pattern, scrutinee.clone())
+                .and_then(|_| match &arm.guard {
+                    Some(guard) => self.infer(guard).map(|_| ()),
+                    None => Ok(()),
+                })
+                .and_then(|_| self.infer(&arm.body));
+            self.variables = saved_variables;
+            self.declared = saved_declared;
+            let typing = typing?;
+            result = match result {
+                None => Some(typing),
+                Some(previous) => Some(if previous == typing { previous } else { None }),
+            };
+        }
+        self.check_exhaustive(match_expr, scrutinee.as_ref());
+        Ok(result.flatten())
+    }
+
+    /// Types the variables a pattern binds, given the type of the value it is matched against
+    fn bind_pattern(&mut self, pattern: &Pattern, typing: Option<Typing>) -> Result<(), String> {
+        match pattern {
+            Pattern::Wildcard => Ok(()),
+            Pattern::Binding(name) => {
+                self.set_variable(name, typing);
+                Ok(())
+            }
+            Pattern::Literal(literal) => {
+                let literal_typing = Self::literal_typing(literal);
+                match typing {
+                    Some(typing) if !typing.parameter && !typing.accepts(&literal_typing) => {
+                        Err(format!("Pattern of type {} cannot match a value of type {}", literal_typing, typing))
+                    }
+                    _ => Ok(()),
+                }
+            }
+            Pattern::Variant { enum_name, variant, fields } => {
+                let enumeration = self.enums.get(enum_name)
+                    .ok_or(format!("Enum '{}' not found", enum_name))?;
+                let declared = enumeration.variants.iter().find(|v| &v.name == variant)
+                    .ok_or(format!("Enum '{}' has no variant '{}'", enum_name, variant))?;
+                if declared.fields.len() != fields.len() {
+                    return Err(format!("Pattern '{}::{}' expects {} fields, but {} were provided", enum_name, variant, declared.fields.len(), fields.len()));
+                }
+                if let Some(typing) = &typing {
+                    if !typing.parameter && (typing.name != *enum_name || typing.array_dimensions != 0) {
+                        return Err(format!("Pattern '{}::{}' cannot match a value of type {}", enum_name, variant, typing));
+                    }
+                }
+                // The fields of a generic enum take their types from the generics of the value matched
+                let bindings: HashMap<String, Typing> = match &typing {
+                    Some(typing) if typing.generics.len() == enumeration.type_params.len() => {
+                        enumeration.type_params.iter().cloned().zip(typing.generics.iter().cloned()).collect()
+                    }
+                    _ => HashMap::new(),
+                };
+                let field_typings: Vec<Option<Typing>> = declared.fields.iter()
+                    .map(|f| Self::substitute(&f.typing, &bindings))
+                    .collect();
+                for (field, field_typing) in fields.iter().zip(field_typings) {
+                    self.bind_pattern(field, field_typing)?;
+                }
+                Ok(())
+            }
+            Pattern::Struct { name, fields } => {
+                if !self.structs.contains_key(name) {
+                    return Err(format!("Struct type {} not found", name));
+                }
+                let object = match typing {
+                    Some(typing) if !typing.parameter && (typing.name != *name || typing.array_dimensions != 0) => {
+                        return Err(format!("Pattern '{}' cannot match a value of type {}", name, typing));
+                    }
+                    Some(typing) if !typing.parameter => typing,
+                    _ => Typing::named(name),
+                };
+                for (field, pattern) in fields {
+                    let field_typing = self.member_typing(Some(object.clone()), field)?;
+                    self.bind_pattern(pattern, field_typing)?;
+                }
+                Ok(())
+            }
+            Pattern::Array(elements) => {
+                let element_typing = match &typing {
+                    Some(typing) if typing.array_dimensions > 0 => {
+                        let mut element = typing.clone();
+                        element.array_dimensions -= 1;
+                        Some(element)
+                    }
+                    Some(typing) if !typing.parameter => {
+                        return Err(format!("Array pattern cannot match a value of type {}", typing));
+                    }
+                    _ => None,
+                };
+                if elements.iter().filter(|p| matches!(p, Pattern::Rest(_))).count() > 1 {
+                    return Err("An array pattern can only have one `..`".to_string());
+                }
+                for element in elements {
+                    match element {
+                        Pattern::Rest(Some(name)) => self.set_variable(name, typing.clone()),
+                        Pattern::Rest(None) => {}
+                        element => self.bind_pattern(element, element_typing.clone())?,
+                    }
+                }
+                Ok(())
+            }
+            Pattern::Tuple(elements) => {
+                let element_typings = match &typing {
+                    Some(typing) if typing.name == "tuple" && typing.array_dimensions == 0 && !typing.parameter => {
+                        if typing.generics.len() != elements.len() {
+                            return Err(format!("Tuple pattern with {} elements cannot match a value of type {}", elements.len(), typing));
+                        }
+                        typing.generics.iter().cloned().map(Some).collect()
+                    }
+                    Some(typing) if !typing.parameter => {
+                        return Err(format!("Tuple pattern cannot match a value of type {}", typing));
+                    }
+                    _ => vec![None; elements.len()],
+                };
+                for (element, element_typing) in elements.iter().zip(element_typings) {
+                    self.bind_pattern(element, element_typing)?;
+                }
+                Ok(())
+            }
+            Pattern::Rest(_) => Err("`..` is only allowed inside an array pattern".to_string()),
+        }
+    }
+
+    /// Warns when a match on an enum has no arm for some of its variants
+    fn check_exhaustive(&mut self, match_expr: &Match, scrutinee: Option<&Typing>) {
+        let enum_name = match scrutinee {
+            Some(typing) if typing.array_dimensions == 0 && self.enums.contains_key(&typing.name) => typing.name.clone(),
+            _ => {
+                let variant_pattern = match_expr.arms.iter().find_map(|arm| match &arm.pattern {
+                    Pattern::Variant { enum_name, .. } => Some(enum_name.clone()),
+                    _ => None,
+                });
+                match variant_pattern {
+                    Some(enum_name) => enum_name,
+                    None => return,
+                }
+            }
+        };
+        let enumeration = match self.enums.get(&enum_name) {
+            Some(enumeration) => enumeration,
+            None => return,
+        };
+
+        // Guarded arms can fall through, so they don't cover anything
+        let rows: Vec<Vec<&Pattern>> = match_expr.arms.iter()
+            .filter(|arm| arm.guard.is_none())
+            .map(|arm| vec![&arm.pattern])
+            .collect();
+        let missing: Vec<String> = enumeration.variants.iter()
+            .filter_map(|variant| self.missing_variant(&enumeration.name, variant, &rows, 1))
+            .map(|mut witness| witness.remove(0))
+            .collect();
+        if !missing.is_empty() {
+            self.warnings.push(format!("Match on '{}' is not exhaustive, missing {}", enum_name, missing.join(", ")));
+        }
+    }
+
+    /// A list of values, one per column, that none of the rows of patterns matches, written like patterns,
+    /// or None if the rows cover every value. Columns of enums are split by variant and their fields checked
+    /// in turn, while any other column is only covered by a wildcard or a binding.
+    fn missing_values(&self, rows: &[Vec<&Pattern>], columns: usize) -> Option<Vec<String>> {
+        if columns == 0 {
+            return rows.is_empty().then(Vec::new);
+        }
+        let enum_name = rows.iter().find_map(|row| match row[0] {
+            Pattern::Variant { enum_name, .. } => Some(enum_name),
+            _ => None,
+        });
+        match enum_name.and_then(|name| self.enums.get(name)) {
+            Some(enumeration) => enumeration.variants.iter()
+                .find_map(|variant| self.missing_variant(&enumeration.name, variant, rows, columns)),
+            None => {
+                let rest: Vec<Vec<&Pattern>> = rows.iter()
+                    .filter(|row| row[0].is_irrefutable())
+                    .map(|row| row[1..].to_vec())
+                    .collect();
+                let mut witness = self.missing_values(&rest, columns - 1)?;
+                witness.insert(0, "_".to_string());
+                Some(witness)
+            }
+        }
+    }
+
+    /// The values of the first column's variant that none of the rows matches, as in `missing_values`
+    fn missing_variant(&self, enum_name: &str, variant: &Variant, rows: &[Vec<&Pattern>], columns: usize) -> Option<Vec<String>> {
+        const WILDCARD: &Pattern = &Pattern::Wildcard;
+        let arity = variant.fields.len();
+        // the rows matching the variant, with its fields in place of the first column
+        let specialized: Vec<Vec<&Pattern>> = rows.iter().filter_map(|row| {
+            let fields: Vec<&Pattern> = match row[0] {
+                pattern if pattern.is_irrefutable() => vec![WILDCARD; arity],
+                Pattern::Variant { variant: name, fields, .. } if *name == variant.name && fields.len() == arity => fields.iter().collect(),
+                _ => return None,
+            };
+            Some(fields.into_iter().chain(row[1..].iter().copied()).collect())
+        }).collect();
+        let mut witness = self.missing_values(&specialized, arity + columns - 1)?;
+        let fields: Vec<String> = witness.drain(..arity).collect();
+        // a variant missing whatever its fields hold is written without them
+        let value = match fields.iter().all(|field| field == "_") {
+            true => format!("{}::{}", enum_name, variant.name),
+            false => format!("{}::{}({})", enum_name, variant.name, fields.join(", ")),
+        };
+        witness.insert(0, value);
+        Some(witness)
+    }
+
+    /// Binds the type parameters of a generic function or struct from the explicit type arguments
+    /// and the types of the given arguments
+    fn instantiate(&self, context: &str, type_params: &[String], params: &[Typing], type_args: &[Typing], args: &[Option<Typing>]) -> Result<HashMap<String, Typing>, String> {
+        if !type_args.is_empty() && type_args.len() != type_params.len() {
+            return Err(format!("{} expects {} type arguments, but {} were provided", context, type_params.len(), type_args.len()));
+        }
+        if params.len() != args.len() {
+            return Err(format!("{} expects {} arguments, but {} were provided", context, params.len(), args.len()));
+        }
+        let mut bindings: HashMap<String, Typing> = type_params.iter().cloned()
+            .zip(type_args.iter().cloned())
+            .collect();
+        for (param, arg) in params.iter().zip(args) {
+            if let Some(arg) = arg {
+                self.unify(context, param, arg, &mut bindings)?;
+            }
+        }
+        Ok(bindings)
+    }
+
+    fn unify(&self, context: &str, expected: &Typing, actual: &Typing, bindings: &mut HashMap<String, Typing>) -> Result<(), String> {
+        if expected.parameter {
+            if actual.array_dimensions < expected.array_dimensions {
+                return Err(format!("Type mismatch in {}: expected {}, found {}", context, expected, actual));
+            }
+            let mut bound = actual.clone();
+            bound.array_dimensions -= expected.array_dimensions;
+            match bindings.get(&expected.name) {
+                Some(existing) if *existing != bound => {
+                    Err(format!("Conflicting types for type parameter '{}' in {}: {} and {}", expected.name, context, existing, bound))
+                }
+                Some(_) => Ok(()),
+                None => {
+                    bindings.insert(expected.name.clone(), bound);
+                    Ok(())
+                }
+            }
+        } else if expected.is_optional() && (actual.is_null() || !actual.is_optional()) {
+            // An optional parameter takes null or a value of the type it wraps
+            if actual.is_null() {
+                Ok(())
+            } else {
+                self.unify(context, &expected.generics[0], actual, bindings)
+            }
+        } else if self.interfaces.contains_key(&expected.name) && expected.array_dimensions == actual.array_dimensions && !actual.parameter {
+            if self.implements(actual, &expected.name) {
+                Ok(())
+            } else {
+                Err(format!("Type mismatch in {}: {} does not implement {}", context, actual, expected))
+            }
+        } else if expected.name != actual.name || expected.array_dimensions != actual.array_dimensions || actual.parameter {
+            Err(format!("Type mismatch in {}: expected {}, found {}", context, expected, actual))
+        } else {
+            if expected.generics.len() == actual.generics.len() {
+                for (expected, actual) in expected.generics.iter().zip(&actual.generics) {
+                    self.unify(context, expected, actual, bindings)?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Replaces type parameters with their bound types, or returns `None` if one of them is unbound
+    fn substitute(typing: &Typing, bindings: &HashMap<String, Typing>) -> Option<Typing> {
+        if typing.parameter {
+            let mut bound = bindings.get(&typing.name)?.clone();
+            bound.array_dimensions += typing.array_dimensions;
+            return Some(bound);
+        }
+        let mut result = typing.clone();
+        result.generics = typing.generics.iter()
+            .map(|g| Self::substitute(g, bindings))
+            .collect::<Option<Vec<Typing>>>()?;
+        Some(result)
+    }
+}
+
+impl IR {
+    /// Runs the type checker over the IR, returning the warnings it found if there are no errors,
+    /// along with the variables that can be read before they are assigned
+    pub fn type_check(&self) -> Result<Vec<String>, String> {
+        let mut checker = TypeChecker::new();
+        checker.check(&self.actions)?;
+        checker.warnings.extend(super::cfg::uninitialized_variables(&self.actions));
+        Ok(checker.warnings)
+    }
+}
@@ -1,4 +1,6 @@
-use super::types::{Operator, Literal};
+use super::types::{Operator, Literal, Typing};
+use super::actions::Function;
+use super::resolver::SymbolId;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Operation {
@@ -10,13 +12,139 @@ pub struct Operation {
 #[derive(Debug, Clone, PartialEq)]
 pub struct FunctionCall {
     pub name: String,
+    pub type_args: Vec<Typing>,
     pub args: Vec<Expression>,
+    /// The line of the call in the source, None for calls the compiler made up
+    pub line: Option<usize>,
+    /// The function it calls, set by the resolver; None for methods only known at runtime
+    pub symbol: Option<SymbolId>,
+}
+
+/// A call through a value rather than a name, e.g. `make_adder(1)(2)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    pub callee: Box<Expression>,
+    pub args: Vec<Expression>,
+    pub line: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariantConstructor {
+    pub enum_name: String,
+    pub variant: String,
+    pub args: Vec<Expression>,
+}
+
+/// `start..end` or `start..=end`, counting by `step` (1 when omitted)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range {
+    pub start: Box<Expression>,
+    pub end: Box<Expression>,
+    pub step: Option<Box<Expression>>,
+    pub inclusive: bool,
+}
+
+/// A typed declaration without a value, or a fixed-size array like `int a[2][3] = {..};`, filled with default values
+#[derive(Debug, Clone, PartialEq)]
+pub struct Allocation {
+    pub element: Typing,
+    pub sizes: Vec<Expression>, // outermost first, empty for a single value
+    pub initializer: Option<Box<Expression>>,
+}
+
+/// `sizeof` of a value; fixed-size array types and other types are resolved to their size when lowering
+#[derive(Debug, Clone, PartialEq)]
+pub enum SizeOf {
+    Value(Box<Expression>),
+    Unsized(Typing), // an array type without a size, which has no size to report
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub scrutinee: Box<Expression>,
+    pub arms: Vec<MatchArm>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expression>,
+    pub body: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Wildcard,
+    Binding(String),
+    Literal(Literal),
+    Variant{
+        enum_name: String,
+        variant: String,
+        fields: Vec<Pattern>,
+    },
+    Struct{
+        name: String,
+        fields: Vec<(String, Pattern)>,
+    },
+    Array(Vec<Pattern>),
+    Rest(Option<String>),
+    Tuple(Vec<Pattern>),
+}
+
+impl Expression {
+    /// How a variable, member or element is written in the source, e.g. `p.next`, for error messages
+    pub fn describe(&self) -> Option<String> {
+        match self {
+            Expression::Variable(name, _) => Some(name.clone()),
+            Expression::Operation(op) => {
+                let member = match &*op.right {
+                    Expression::Literal(Literal::String(member)) => Some(member),
+                    _ => None,
+                };
+                match (&op.operator, member) {
+                    (Operator::MemberAccess, Some(member)) => Some(format!("{}.{}", op.left.describe()?, member)),
+                    (Operator::OptionalMemberAccess, Some(member)) => Some(format!("{}?.{}", op.left.describe()?, member)),
+                    (Operator::ArrayAccess, _) => Some(format!("{}[]", op.left.describe()?)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Pattern {
+    /// Whether the pattern matches every value, so it never needs a fallback arm
+    pub fn is_irrefutable(&self) -> bool {
+        matches!(self, Pattern::Wildcard | Pattern::Binding(_))
+    }
+
+    /// The names of the variables the pattern binds
+    pub fn bindings(&self) -> Vec<&String> {
+        match self {
+            Pattern::Binding(name) | Pattern::Rest(Some(name)) => vec![name],
+            Pattern::Variant { fields, .. } => fields.iter().flat_map(Pattern::bindings).collect(),
+            Pattern::Struct { fields, .. } => fields.iter().flat_map(|(_, p)| p.bindings()).collect(),
+            Pattern::Array(elements) | Pattern::Tuple(elements) => elements.iter().flat_map(Pattern::bindings).collect(),
+            Pattern::Wildcard | Pattern::Literal(_) | Pattern::Rest(None) => Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
     Literal(Literal),
     Operation(Operation),
-    Variable(String),
+    Variable(String, Option<SymbolId>), // the symbol is set by the resolver
     FunctionCall(FunctionCall),
+    Array(Vec<Expression>),
+    Variant(VariantConstructor),
+    Match(Match),
+    Lambda(Box<Function>),
+    Call(Call),
+    Range(Range),
+    Allocation(Allocation),
+    SizeOf(SizeOf),
+    Tuple(Vec<Expression>),
+    NamedArgument(String, Box<Expression>), // only found among the arguments of a call
 } 
\ No newline at end of file
@@ -1,25 +1,32 @@
 use std::collections::HashMap;
-use crate::parsing::{StructDecl, Token, FunctionDecl, VariableDecl, Statement, ExpressionDecl, Type};
-use super::types::{Operator, Literal, Item, Typing, VariableData};
-use super::expressions::{Expression, Operation, FunctionCall};
-use super::actions::{Action, Function, Variable, Struct, Conditional};
+use crate::parsing::{StructDecl, Token, FunctionDecl, VariableDecl, SizeOfOperand, Statement, ExpressionDecl, Type, EnumDecl, InterfaceDecl, ImplDecl, ImportDecl, MatchExpression};
+use super::types::{Operator, Literal, Item, Typing, VariableData, BindingKind, Attribute};
+use super::expressions::{Expression, Operation, FunctionCall, Call, VariantConstructor, Match, MatchArm, Pattern, Range, Allocation, SizeOf};
+use super::resolver::{Resolver, Symbol};
+use super::actions::{Action, Function, Variable, Struct, Enum, Variant, Conditional, Signature, Interface, Impl, Import, Binding, ForIn, Loop, Switch, SwitchCase, Destructure, Try, Catch};
 
 /// The IR, aka "Intermediate Representation", is the intermediate representation of the source code.
 /// It contains 4 parts:
-/// - functions: the functions in the source code
-/// - variables: the variables in the source code
-/// - operations: the operations in the source code
-/// - actions: the actions to be performed
+/// - functions: the top-level functions in the source code, with their overloads
+/// - variables: the top-level variables, as first declared or assigned
+/// - operations: the other top-level assignments, like `p.x = 1`, by what they assign to
+/// - actions: the actions to be performed, with declarations hoisted before the code that uses them
+///
+/// Once resolved, it also holds the symbol table, which `symbol_of` looks up the variables and calls in.
 #[derive(Debug, Clone, PartialEq)]
 pub struct IR {
-    functions: HashMap<String, Function>,
+    functions: HashMap<String, Vec<Function>>,
     variables: HashMap<String, Variable>,
     operations: HashMap<String, Operation>,
     structs: HashMap<String, Struct>,
+    pub(super) symbols: Vec<Symbol>,
     pub actions: Vec<Action>,
     expressions: Vec<Expression>,
+    aliases: HashMap<String, Type>, // typedefs, already resolved
+    sizes: HashMap<String, usize>, // what sizeof reports for declared struct and enum types
 }
 
+#[allow(clippy::wrong_self_convention)]
 impl IR {
     pub fn new() -> Self {
         IR {
@@ -27,23 +34,118 @@ impl IR {
             variables: HashMap::new(),
             operations: HashMap::new(),
             structs: HashMap::new(),
+            symbols: Vec::new(),
             actions: Vec::new(),
             expressions: Vec::new(),
+            aliases: HashMap::new(),
+            sizes: HashMap::new(),
         }
     }
 
     pub fn from_tokens(tokens: Vec<Token>) -> Self {
         let mut ir = IR::new();
+        ir.declare_types(&tokens);
         ir.actions = ir.to_actions(tokens).unwrap();
+        Self::hoist(&mut ir.actions);
+        ir.declare_symbols();
         ir
     }
 
+    /// Moves declarations before the rest of the code, so they can be used before they are written:
+    /// imports first, then types and functions, then impl blocks, each kept in the order they were written
+    fn hoist(actions: &mut [Action]) {
+        actions.sort_by_key(|action| match action {
+            Action::Import(_) => 0,
+            Action::Struct(_) | Action::Enum(_) | Action::Interface(_) | Action::Function(_) => 1,
+            Action::Impl(_) => 2,
+            _ => 3,
+        });
+    }
+
+    /// Fills the tables of top-level functions, structs and variables
+    pub(super) fn declare_symbols(&mut self) {
+        self.functions.clear();
+        self.structs.clear();
+        self.variables.clear();
+        self.operations.clear();
+        for action in &self.actions {
+            match action {
+                Action::Function(function) => {
+                    self.functions.entry(function.name.clone()).or_default().push(function.clone());
+                }
+                Action::Struct(structure) => {
+                    self.structs.insert(structure.name.clone(), structure.clone());
+                }
+                Action::Variable(variable) => {
+                    self.variables.entry(variable.name.clone()).or_insert_with(|| variable.clone());
+                }
+                // assignments are expression statements, wrapped in the operation that discards their value
+                Action::Operation(statement) if matches!(statement.operator, Operator::Comma | Operator::Expression) => {
+                    if let Expression::Operation(operation) = &*statement.left {
+                        if let (Operator::Assignment, Some(target)) = (&operation.operator, operation.left.describe()) {
+                            self.operations.entry(target).or_insert_with(|| operation.clone());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Resolves every variable and call to the symbol it refers to, failing on names that are never declared
+    pub fn resolve(&mut self, builtins: &[String]) -> Result<(), String> {
+        let mut resolver = Resolver::new(builtins);
+        resolver.resolve(&mut self.actions)?;
+        self.symbols = resolver.symbols;
+        Ok(())
+    }
+
+    /// The symbol a resolved variable or function call refers to, None for other expressions and unresolved calls
+    pub fn symbol_of(&self, expr: &Expression) -> Option<&Symbol> {
+        let id = match expr {
+            Expression::Variable(_, symbol) => *symbol,
+            Expression::FunctionCall(call) => call.symbol,
+            _ => None,
+        }?;
+        self.symbols.get(id)
+    }
+
     pub fn from_actions(actions: Vec<Action>) -> Self {
         let mut ir = IR::new();
         ir.actions = actions;
         ir
     }
 
+    /// Records the top-level typedefs, in order so an alias can use an earlier one, and the sizes of declared types
+    fn declare_types(&mut self, tokens: &[Token]) {
+        for token in tokens {
+            match token {
+                Token::Typedef(typedef) => {
+                    let resolved = self.resolve_type(typedef.type_info.clone());
+                    self.aliases.insert(typedef.name.clone(), resolved);
+                }
+                Token::Struct(structure) => { self.sizes.insert(structure.name.clone(), structure.fields.len()); }
+                Token::Enum(enumeration) => { self.sizes.insert(enumeration.name.clone(), 1); }
+                _ => {}
+            }
+        }
+    }
+
+    /// Replaces typedef names with the types they stand for
+    fn resolve_type(&self, type_info: Type) -> Type {
+        match type_info {
+            Type::Struct(name) => self.aliases.get(&name).cloned().unwrap_or(Type::Struct(name)),
+            Type::Array(inner, size) => Type::Array(Box::new(self.resolve_type(*inner)), size),
+            Type::Tuple(elements) => Type::Tuple(elements.into_iter().map(|t| self.resolve_type(t)).collect()),
+            Type::Optional(inner) => Type::Optional(Box::new(self.resolve_type(*inner))),
+            Type::Generic(name, arguments) => Type::Generic(
+                name,
+                arguments.into_iter().map(|t| self.resolve_type(t)).collect(),
+            ),
+            other => other,
+        }
+    }
+
     pub fn to_actions(&self, tokens: Vec<Token>) -> Result<Vec<Action>, String> {
         let mut actions = Vec::new();
         for token in tokens {
@@ -53,20 +155,29 @@ impl IR {
                     actions.push(action);
                 }
                 Token::Function(function) => {
-                    let action = self.from_function(function);
-                    if action.is_err() {
-                        return Err(action.unwrap_err());
-                    }
-                    actions.push(action.unwrap());
+                    let action = self.from_function(function)?;
+                    actions.push(action);
                 }
                 Token::Variable(variable) => {
                     let action = self.from_variable(variable);
                     actions.push(action);
                 }
-                Token::Statement(statement) => {
-                    let action = self.from_statement(statement);
+                Token::Enum(enumeration) => {
+                    actions.push(self.from_enum(enumeration));
+                }
+                Token::Interface(interface) => {
+                    actions.push(self.from_interface(interface));
+                }
+                Token::Impl(implementation) => {
+                    let action = self.from_impl(implementation)?;
                     actions.push(action);
                 }
+                Token::Import(import) => {
+                    actions.push(self.from_import(import));
+                }
+                Token::Statement(statement) => {
+                    actions.extend(self.from_block(statement));
+                }
                 Token::Expression(expression) => {
                     let expr = self.from_expression(expression)?;
                     actions.push(Action::Operation(Operation {
@@ -75,6 +186,7 @@ impl IR {
                         right: Box::new(Expression::Literal(Literal::Integer(0))),
                     }));
                 }
+                Token::Typedef(_) => {}
                 Token::Type(_) => {
                     unreachable!("there should be no case where type should be parsed as an action");
                 }
@@ -83,51 +195,47 @@ impl IR {
         Ok(actions)
     }
 
+    fn to_typing(&self, type_info: Type) -> Typing {
+        match self.resolve_type(type_info) {
+            Type::Array(base_type, _) => {
+                // For array types, we need to count the dimensions
+                let mut typing = self.to_typing(*base_type);
+                typing.array_dimensions += 1;
+                typing
+            },
+            Type::Struct(name) => Typing::named(&name),
+            Type::Generic(name, arguments) => {
+                let mut typing = Typing::named(&name);
+                typing.generics = arguments.into_iter().map(|t| self.to_typing(t)).collect();
+                typing
+            },
+            Type::Parameter(name) => {
+                let mut typing = Typing::named(&name);
+                typing.parameter = true;
+                typing
+            },
+            Type::Tuple(elements) => Typing::tuple(elements.into_iter().map(|t| self.to_typing(t)).collect()),
+            Type::Optional(inner) => Typing::optional(self.to_typing(*inner)),
+        }
+    }
+
     fn from_struct(&self, structure: StructDecl) -> Action {
         // Convert struct declaration to IR struct
         let mut fields = Vec::new();
         
         // Process each field in the struct
         for field in structure.fields {
-            let typing = match field.type_info {
-                Type::Array(base_type) => {
-                    // For array types, we need to count the dimensions
-                    let mut array_dimensions = 1;
-                    let mut current_type = *base_type;
-                    
-                    while let Type::Array(next_type) = current_type {
-                        array_dimensions += 1;
-                        current_type = *next_type;
-                    }
-                    
-                    // Get the base type name
-                    let type_name = match current_type {
-                        Type::Struct(name) => name,
-                        _ => panic!("Unsupported base type for array"),
-                    };
-                    
-                    Typing {
-                        name: type_name,
-                        array_dimensions,
-                    }
-                },
-                Type::Struct(name) => {
-                    Typing {
-                        name,
-                        array_dimensions: 0,
-                    }
-                },
-            };
-            
             fields.push(Item {
                 name: field.name,
-                typing,
+                typing: self.to_typing(field.type_info),
             });
         }
         
         // Create the struct
         let ir_struct = Struct {
+            attributes: Self::to_attributes(structure.attributes),
             name: structure.name,
+            type_params: structure.type_parameters,
             fields,
         };
         
@@ -135,51 +243,133 @@ impl IR {
     }
 
     fn from_function(&self, function: FunctionDecl) -> Result<Action, String> {
+        Ok(Action::Function(self.to_function(function)?))
+    }
+
+    fn to_function(&self, function: FunctionDecl) -> Result<Function, String> {
         // Convert function declaration to IR function
         let mut params = Vec::new();
-        
+        let mut defaults = Vec::new();
+        let variadic = function.parameters.last().is_some_and(|param| param.variadic);
+
         // Process each parameter in the function
-        for param in &function.parameters {
-            let typing = Typing {
-                name: param.name.clone().unwrap_or_default(),
-                array_dimensions: 0,
-            };
-            
+        for param in function.parameters {
+            let mut typing = self.to_typing(param.type_info);
+            if param.variadic {
+                typing.array_dimensions += 1;
+            }
             params.push(Item {
-                name: param.name.clone().unwrap_or_default(),
+                name: param.name.unwrap_or_default(),
                 typing,
             });
+            defaults.push(param.default.map(|value| self.from_expression(value)).transpose()?);
         }
         
         // Process function body
-        let body = self.to_actions(function.body);
-
-        if body.is_err() {
-            return Err(body.unwrap_err());
-        }
+        let mut body = self.to_actions(function.body)?;
+        Self::hoist(&mut body);
         
         // Create the function
-        Ok(Action::Function(Function {
-            name: function.name.clone(),
+        Ok(Function {
+            attributes: Self::to_attributes(function.attributes),
+            name: function.name,
+            type_params: function.type_parameters,
             params,
-            body: body.unwrap(),
+            defaults,
+            variadic,
+            return_type: function.return_type.map(|t| self.to_typing(t)),
+            body,
+            generator: function.generator,
+        })
+    }
+
+    fn to_attributes(attributes: Vec<crate::parsing::Attribute>) -> Vec<Attribute> {
+        attributes.into_iter()
+            .map(|attribute| Attribute { name: attribute.name, arguments: attribute.arguments })
+            .collect()
+    }
+
+    fn from_enum(&self, enumeration: EnumDecl) -> Action {
+        let variants = enumeration.variants.into_iter().map(|variant| Variant {
+            name: variant.name,
+            fields: variant.fields.into_iter().map(|field| Item {
+                name: field.name,
+                typing: self.to_typing(field.type_info),
+            }).collect(),
+        }).collect();
+
+        Action::Enum(Enum {
+            name: enumeration.name,
+            type_params: enumeration.type_parameters,
+            variants,
+        })
+    }
+
+    fn from_interface(&self, interface: InterfaceDecl) -> Action {
+        let methods = interface.methods.into_iter().map(|method| Signature {
+            name: method.name,
+            params: method.parameters.into_iter().map(|param| Item {
+                name: param.name.unwrap_or_default(),
+                typing: self.to_typing(param.type_info),
+            }).collect(),
+            return_type: method.return_type.map(|t| self.to_typing(t)),
+        }).collect();
+
+        Action::Interface(Interface {
+            name: interface.name,
+            methods,
+        })
+    }
+
+    fn from_impl(&self, implementation: ImplDecl) -> Result<Action, String> {
+        let mut methods = Vec::new();
+        for method in implementation.methods {
+            methods.push(self.to_function(method)?);
+        }
+
+        Ok(Action::Impl(Impl {
+            interface: implementation.interface,
+            target: implementation.target,
+            methods,
         }))
     }
 
+    fn from_import(&self, import: ImportDecl) -> Action {
+        Action::Import(Import {
+            path: import.path,
+            names: import.names,
+        })
+    }
+
     fn from_variable(&self, variable: VariableDecl) -> Action {
-        let data = match variable.initializer {
-            Some(initializer) => {
-                match self.from_expression(initializer) {
-                    Ok(expr) => VariableData::Expression(Box::new(expr)),
-                    Err(_) => VariableData::Null,
+        let initializer = variable.initializer.and_then(|initializer| self.from_expression(initializer).ok());
+        let typing = variable.type_info.clone().map(|t| self.to_typing(t));
+        let data = match (variable.type_info, initializer) {
+            (Some(type_info), initializer) => {
+                // Peel the sized arrays off the declared type, what remains is the type of each element
+                let mut element = self.resolve_type(type_info);
+                let mut sizes = Vec::new();
+                while let Type::Array(inner, Some(size)) = element {
+                    sizes.push(self.from_expression(*size).unwrap_or(Expression::Literal(Literal::Integer(0))));
+                    element = *inner;
+                }
+                match initializer {
+                    Some(expr) if sizes.is_empty() => VariableData::Expression(Box::new(expr)),
+                    initializer => VariableData::Expression(Box::new(Expression::Allocation(Allocation {
+                        element: self.to_typing(element),
+                        sizes,
+                        initializer: initializer.map(Box::new),
+                    }))),
                 }
             }
-            None => VariableData::Null,
+            (None, Some(expr)) => VariableData::Expression(Box::new(expr)),
+            (None, None) => VariableData::Null,
         };
 
         // Create the variable
         let ir_variable = Variable {
             name: variable.name,
+            typing,
             data,
         };
         
@@ -196,13 +386,12 @@ impl IR {
                 };
                 
                 // Process the then branch
-                let mut then_actions = Vec::new();
-                then_actions.push(self.from_statement(*if_stmt.then_branch));
+                let then_actions = self.from_block(*if_stmt.then_branch);
                 
                 // Process the else branch if it exists
                 let mut else_actions = Vec::new();
                 if let Some(else_branch) = if_stmt.else_branch {
-                    else_actions.push(self.from_statement(*else_branch));
+                    else_actions = self.from_block(*else_branch);
                 }
                 
                 Action::Conditional(Conditional {
@@ -249,18 +438,17 @@ impl IR {
                     Err(_) => Expression::Literal(Literal::Boolean(false)),
                 };
                 
-                let mut body_actions = Vec::new();
-                body_actions.push(self.from_statement(*while_stmt.body));
-                
-                Action::Conditional(Conditional {
+                Action::Loop(Loop {
+                    label: while_stmt.label,
+                    init: Vec::new(),
                     condition,
-                    then_actions: body_actions,
-                    else_actions: Vec::new(),
+                    update: Vec::new(),
+                    body: self.from_block(*while_stmt.body),
+                    check_first: true,
                 })
             },
             Statement::For(for_stmt) => {
-                let mut init_actions = Vec::new();
-                init_actions.push(self.from_statement(*for_stmt.initializer));
+                let init = self.from_block(*for_stmt.initializer);
                 
                 let condition = match for_stmt.condition {
                     Some(expr) => match self.from_expression(expr) {
@@ -270,118 +458,153 @@ impl IR {
                     None => Expression::Literal(Literal::Boolean(true)),
                 };
                 
-                let mut increment_actions = Vec::new();
+                let mut update = Vec::new();
                 if let Some(increment) = for_stmt.increment {
                     if let Ok(expr) = self.from_expression(increment) {
-                        increment_actions.push(Action::Expression(expr));
+                        update.push(Action::Expression(expr));
                     }
                 }
                 
-                let mut body_actions = Vec::new();
-                body_actions.push(self.from_statement(*for_stmt.body));
-                
-                let mut all_actions = init_actions;
-                all_actions.push(Action::Conditional(Conditional {
+                Action::Loop(Loop {
+                    label: for_stmt.label,
+                    init,
                     condition,
-                    then_actions: body_actions,
-                    else_actions: Vec::new(),
-                }));
-                all_actions.extend(increment_actions);
-                
-                if all_actions.is_empty() {
-                    Action::Operation(Operation {
-                        operator: Operator::Expression,
-                        left: Box::new(Expression::Literal(Literal::Integer(0))),
-                        right: Box::new(Expression::Literal(Literal::Integer(0))),
-                    })
-                } else {
-                    all_actions.remove(0)
-                }
+                    update,
+                    body: self.from_block(*for_stmt.body),
+                    check_first: true,
+                })
+            },
+            Statement::ForIn(for_in) => {
+                let iterable = match self.from_expression(for_in.iterable) {
+                    Ok(expr) => expr,
+                    Err(_) => Expression::Array(Vec::new()),
+                };
+
+                Action::ForIn(ForIn {
+                    label: for_in.label,
+                    variable: for_in.variable,
+                    typing: for_in.type_info.map(|t| self.to_typing(t)),
+                    iterable,
+                    body: self.from_block(*for_in.body),
+                })
             },
             Statement::DoWhile(do_while_stmt) => {
-                let mut body_actions = Vec::new();
-                body_actions.push(self.from_statement(*do_while_stmt.body));
+                let body = self.from_block(*do_while_stmt.body);
                 
                 let condition = match self.from_expression(do_while_stmt.condition) {
                     Ok(expr) => expr,
                     Err(_) => Expression::Literal(Literal::Boolean(false)),
                 };
                 
-                Action::Conditional(Conditional {
+                Action::Loop(Loop {
+                    label: do_while_stmt.label,
+                    init: Vec::new(),
                     condition,
-                    then_actions: body_actions,
-                    else_actions: Vec::new(),
+                    update: Vec::new(),
+                    body,
+                    check_first: false,
                 })
             },
             Statement::Switch(switch_stmt) => {
-                let expr = match self.from_expression(switch_stmt.expression) {
+                let value = match self.from_expression(switch_stmt.expression) {
                     Ok(expr) => expr,
                     Err(_) => Expression::Literal(Literal::Integer(0)),
                 };
                 
-                let mut case_actions = Vec::new();
-                for case in switch_stmt.cases {
-                    let case_value = match self.from_expression(case.value) {
+                let cases = switch_stmt.cases.into_iter().map(|case| {
+                    let label = case.value.map(|label| match self.from_expression(label) {
                         Ok(expr) => expr,
                         Err(_) => Expression::Literal(Literal::Integer(0)),
-                    };
-                    
-                    let mut body_actions = Vec::new();
-                    for stmt in case.statements {
-                        body_actions.push(self.from_statement(stmt));
-                    }
-                    
-                    let case_condition = Operation {
-                        operator: Operator::Equal,
-                        left: Box::new(expr.clone()),
-                        right: Box::new(case_value),
-                    };
-                    
-                    case_actions.push(Action::Conditional(Conditional {
-                        condition: Expression::Operation(case_condition),
-                        then_actions: body_actions,
-                        else_actions: Vec::new(),
-                    }));
-                }
-                
-                let mut default_actions = Vec::new();
-                if let Some(default_statements) = switch_stmt.default {
-                    for stmt in default_statements {
-                        default_actions.push(self.from_statement(stmt));
-                    }
-                }
+                    });
+                    let body = case.statements.into_iter()
+                        .flat_map(|stmt| self.from_block(stmt))
+                        .collect();
+                    SwitchCase { label, body }
+                }).collect();
                 
-                if case_actions.is_empty() {
-                    if default_actions.is_empty() {
-                        Action::Operation(Operation {
-                            operator: Operator::Expression,
-                            left: Box::new(Expression::Literal(Literal::Integer(0))),
-                            right: Box::new(Expression::Literal(Literal::Integer(0))),
-                        })
-                    } else {
-                        default_actions.remove(0)
-                    }
-                } else {
-                    case_actions.remove(0)
-                }
+                Action::Switch(Switch { value, cases })
             },
-            Statement::Break => {
+            Statement::Break(label) => {
                 Action::Operation(Operation {
                     operator: Operator::Break,
-                    left: Box::new(Expression::Literal(Literal::Integer(0))),
+                    left: Box::new(Self::to_label(label)),
                     right: Box::new(Expression::Literal(Literal::Integer(0))),
                 })
             },
-            Statement::Continue => {
+            Statement::Continue(label) => {
                 Action::Operation(Operation {
                     operator: Operator::Continue,
-                    left: Box::new(Expression::Literal(Literal::Integer(0))),
+                    left: Box::new(Self::to_label(label)),
                     right: Box::new(Expression::Literal(Literal::Integer(0))),
                 })
             },
             Statement::Declaration(decl) => {
                 self.from_variable(decl)
             },
+            Statement::Destructure(pattern, value) => {
+                Action::Destructure(Destructure {
+                    pattern: self.from_pattern(pattern),
+                    value: self.from_expression(value).unwrap_or(Expression::Literal(Literal::Integer(0))),
+                })
+            },
+            Statement::Try(try_stmt) => {
+                Action::Try(Try {
+                    body: self.from_block(*try_stmt.body),
+                    catch: try_stmt.catch.map(|(variable, body)| Catch { variable, body: self.from_block(*body) }),
+                    finally: try_stmt.finally.map(|body| self.from_block(*body)),
+                })
+            },
+            Statement::Throw(value) => {
+                Action::Operation(Operation {
+                    operator: Operator::Throw,
+                    left: Box::new(self.from_expression(value).unwrap_or(Expression::Literal(Literal::Null))),
+                    right: Box::new(Expression::Literal(Literal::Integer(0))),
+                })
+            },
+            Statement::Yield(value) => {
+                Action::Operation(Operation {
+                    operator: Operator::Yield,
+                    left: Box::new(self.from_expression(value).unwrap_or(Expression::Literal(Literal::Null))),
+                    right: Box::new(Expression::Literal(Literal::Integer(0))),
+                })
+            },
+            Statement::Binding(binding) => {
+                let kind = match binding.kind {
+                    crate::parsing::BindingKind::Const => BindingKind::Const,
+                    crate::parsing::BindingKind::Let => BindingKind::Let,
+                    crate::parsing::BindingKind::Var => BindingKind::Var,
+                };
+                let value = match binding.initializer.map(|value| self.from_expression(value)) {
+                    Some(Ok(value)) => Some(value),
+                    Some(Err(_)) => Some(Expression::Literal(Literal::Integer(0))),
+                    None => None,
+                };
+
+                Action::Binding(Binding {
+                    name: binding.name,
+                    kind,
+                    typing: binding.type_info.map(|t| self.to_typing(t)),
+                    value,
+                })
+            },
+            Statement::Function(function) => {
+                // A nested function is a closure over the enclosing scope, stored in a variable of the same name
+                let name = function.name.clone();
+                let lambda = match self.to_function(*function) {
+                    Ok(function) => Expression::Lambda(Box::new(function)),
+                    Err(_) => Expression::Literal(Literal::Integer(0)),
+                };
+
+                Action::Operation(Operation {
+                    operator: Operator::Expression,
+                    left: Box::new(Expression::Operation(Operation {
+                        operator: Operator::Assignment,
+                        left: Box::new(Expression::Variable(name, None)),
+                        right: Box::new(lambda),
+                    })),
+                    right: Box::new(Expression::Literal(Literal::Integer(0))),
+                })
+            },
             Statement::Compound(statements) => {
                 let mut actions = Vec::new();
                 for stmt in statements {
@@ -401,18 +624,31 @@ impl IR {
         }
     }
 
+    /// Lowers a statement into the list of actions it expands to, flattening compound statements
+    /// The loop label of a `break` or `continue`, kept as the operation's left side
+    fn to_label(label: Option<String>) -> Expression {
+        match label {
+            Some(label) => Expression::Variable(label, None),
+            None => Expression::Literal(Literal::Integer(0)),
+        }
+    }
+
+    fn from_block(&self, statement: Statement) -> Vec<Action> {
+        match statement {
+            Statement::Compound(statements) => {
+                statements.into_iter().flat_map(|stmt| self.from_block(stmt)).collect()
+            },
+            statement => vec![self.from_statement(statement)],
+        }
+    }
+
     fn from_expression(&self, expression: ExpressionDecl) -> Result<Expression, String> {
         match expression {
             ExpressionDecl::Literal(literal) => {
-                match literal {
-                    crate::parsing::Literal::Integer(i) => Ok(Expression::Literal(Literal::Integer(i))),
-                    crate::parsing::Literal::Float(f) => Ok(Expression::Literal(Literal::Float(f))),
-                    crate::parsing::Literal::String(s) => Ok(Expression::Literal(Literal::String(s))),
-                    crate::parsing::Literal::Char(c) => Ok(Expression::Literal(Literal::Character(c))),
-                }
+                Ok(Expression::Literal(self.to_literal(literal)))
             },
             ExpressionDecl::Identifier(name) => {
-                Ok(Expression::Variable(name))
+                Ok(Expression::Variable(name, None))
             },
             ExpressionDecl::BinaryOp(op, left, right) => {
                 let left_expr = self.from_expression(*left)?;
@@ -424,6 +660,17 @@ impl IR {
                     right: Box::new(right_expr),
                 }))
             },
+            ExpressionDecl::UnaryOp(crate::parsing::UnaryOperator::Negate, expr) => {
+                match self.from_expression(*expr)? {
+                    Expression::Literal(Literal::Integer(i)) => Ok(Expression::Literal(Literal::Integer(-i))),
+                    Expression::Literal(Literal::Float(f)) => Ok(Expression::Literal(Literal::Float(-f))),
+                    expr_result => Ok(Expression::Operation(Operation {
+                        operator: Operator::Subtract,
+                        left: Box::new(Expression::Literal(Literal::Integer(0))),
+                        right: Box::new(expr_result),
+                    })),
+                }
+            },
             ExpressionDecl::UnaryOp(op, expr) => {
                 let operator = self.to_unary_operator(&op);
                 let expr_result = self.from_expression(*expr)?;
@@ -434,10 +681,45 @@ impl IR {
                     right: Box::new(Expression::Literal(Literal::Integer(0))),
                 }))
             },
-            ExpressionDecl::Call(func, args) => {
+            ExpressionDecl::Call(func, args, line) => {
                 let name = match *func {
                     ExpressionDecl::Identifier(name) => name,
-                    _ => return Err("Function call must have an identifier".to_string()),
+                    // `p.len()` calls the method `len` with the receiver as its first argument
+                    ExpressionDecl::MemberAccess(receiver, method) => {
+                        let mut processed_args = vec![self.from_expression(*receiver)?];
+                        for arg in args {
+                            processed_args.push(self.from_expression(arg)?);
+                        }
+                        return Ok(Expression::FunctionCall(FunctionCall {
+                            name: method,
+                            type_args: Vec::new(),
+                            args: processed_args,
+                            line: Some(line),
+                            symbol: None,
+                        }));
+                    }
+                    ExpressionDecl::Variant(enum_name, variant) => {
+                        let mut processed_args = Vec::new();
+                        for arg in args {
+                            processed_args.push(self.from_expression(arg)?);
+                        }
+                        return Ok(Expression::Variant(VariantConstructor {
+                            enum_name,
+                            variant,
+                            args: processed_args,
+                        }));
+                    }
+                    callee => {
+                        let mut processed_args = Vec::new();
+                        for arg in args {
+                            processed_args.push(self.from_expression(arg)?);
+                        }
+                        return Ok(Expression::Call(Call {
+                            callee: Box::new(self.from_expression(callee)?),
+                            args: processed_args,
+                            line: Some(line),
+                        }));
+                    }
                 };
                 
                 if let Some(_function) = self.lookup_function(&name) {
@@ -448,7 +730,10 @@ impl IR {
                     
                     Ok(Expression::FunctionCall(FunctionCall {
                         name,
+                        type_args: Vec::new(),
                         args: processed_args,
+                        line: Some(line),
+                        symbol: None,
                     }))
                 } else {
                     let mut processed_args = Vec::new();
@@ -458,10 +743,35 @@ impl IR {
                     
                     Ok(Expression::FunctionCall(FunctionCall {
                         name,
+                        type_args: Vec::new(),
                         args: processed_args,
+                        line: Some(line),
+                        symbol: None,
                     }))
                 }
             },
+            ExpressionDecl::GenericCall(func, type_args, args, line) => {
+                let mut processed_args = Vec::new();
+                let name = match *func {
+                    ExpressionDecl::Identifier(name) => name,
+                    ExpressionDecl::MemberAccess(receiver, method) => {
+                        processed_args.push(self.from_expression(*receiver)?);
+                        method
+                    }
+                    _ => return Err("Function call must have an identifier".to_string()),
+                };
+                for arg in args {
+                    processed_args.push(self.from_expression(arg)?);
+                }
+
+                Ok(Expression::FunctionCall(FunctionCall {
+                    name,
+                    type_args: type_args.into_iter().map(|t| self.to_typing(t)).collect(),
+                    args: processed_args,
+                    line: Some(line),
+                    symbol: None,
+                }))
+            },
             ExpressionDecl::Cast(_, expr) => {
                 self.from_expression(*expr)
             },
@@ -484,6 +794,23 @@ impl IR {
                     right: Box::new(Expression::Literal(Literal::String(member))),
                 }))
             },
+            ExpressionDecl::OptionalMemberAccess(obj, member) => {
+                Ok(Expression::Operation(Operation {
+                    operator: Operator::OptionalMemberAccess,
+                    left: Box::new(self.from_expression(*obj)?),
+                    right: Box::new(Expression::Literal(Literal::String(member))),
+                }))
+            },
+            ExpressionDecl::NamedArgument(name, value) => {
+                Ok(Expression::NamedArgument(name, Box::new(self.from_expression(*value)?)))
+            },
+            ExpressionDecl::Try(value) => {
+                Ok(Expression::Operation(Operation {
+                    operator: Operator::Try,
+                    left: Box::new(self.from_expression(*value)?),
+                    right: Box::new(Expression::Literal(Literal::Integer(0))),
+                }))
+            },
             ExpressionDecl::Assignment(left, right) => {
                 let left_expr = self.from_expression(*left)?;
                 let right_expr = self.from_expression(*right)?;
@@ -510,22 +837,128 @@ impl IR {
                 }))
             },
             ExpressionDecl::ArrayLiteral(elements) => {
-                let mut result = Expression::Literal(Literal::Integer(0));
-                
-                for element in elements.into_iter().rev() {
-                    let element_expr = self.from_expression(element)?;
-                    result = Expression::Operation(Operation {
-                        operator: Operator::Comma,
-                        left: Box::new(element_expr),
-                        right: Box::new(result),
-                    });
+                let mut result = Vec::new();
+                for element in elements {
+                    result.push(self.from_expression(element)?);
                 }
-                
-                Ok(result)
+                Ok(Expression::Array(result))
+            },
+            ExpressionDecl::Variant(enum_name, variant) => {
+                Ok(Expression::Variant(VariantConstructor {
+                    enum_name,
+                    variant,
+                    args: Vec::new(),
+                }))
+            },
+            ExpressionDecl::Struct(name, _) => {
+                Err(format!("Struct '{}' cannot be declared inside an expression", name))
+            },
+            ExpressionDecl::Match(match_expr) => {
+                self.from_match(match_expr)
+            },
+            ExpressionDecl::Lambda(function) => {
+                Ok(Expression::Lambda(Box::new(self.to_function(*function)?)))
+            },
+            ExpressionDecl::Range(range) => {
+                let step = match range.step {
+                    Some(step) => Some(Box::new(self.from_expression(*step)?)),
+                    None => None,
+                };
+                Ok(Expression::Range(Range {
+                    start: Box::new(self.from_expression(*range.start)?),
+                    end: Box::new(self.from_expression(*range.end)?),
+                    step,
+                    inclusive: range.inclusive,
+                }))
+            },
+            ExpressionDecl::SizeOf(SizeOfOperand::Expression(expr)) => {
+                Ok(Expression::SizeOf(SizeOf::Value(Box::new(self.from_expression(*expr)?))))
+            },
+            ExpressionDecl::SizeOf(SizeOfOperand::Type(type_info)) => self.from_sizeof_type(type_info),
+            ExpressionDecl::Tuple(elements) => {
+                let elements = elements.into_iter()
+                    .map(|element| self.from_expression(element))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Expression::Tuple(elements))
+            },
+        }
+    }
+
+    /// `sizeof(T)` is the length of a fixed-size array, the number of fields of a struct and 1 for other types.
+    /// `sizeof(a)` and `sizeof(a[0])` parse as types too, so they become values unless they name a known type
+    fn from_sizeof_type(&self, type_info: Type) -> Result<Expression, String> {
+        const BUILTIN_TYPES: [&str; 6] = ["int", "float", "string", "char", "bool", "range"];
+        let mut base = &type_info;
+        let mut indices = Vec::new();
+        while let Type::Array(inner, size) = base {
+            indices.push(size.clone());
+            base = inner;
+        }
+        if let Type::Struct(name) = base {
+            let is_type = BUILTIN_TYPES.contains(&name.as_str()) || self.aliases.contains_key(name) || self.sizes.contains_key(name);
+            if !is_type && indices.iter().all(Option::is_some) {
+                // The suffixes are array accesses, written in the same order they were peeled off
+                let value = indices.into_iter().flatten()
+                    .fold(ExpressionDecl::Identifier(name.clone()), |array, index| ExpressionDecl::ArrayAccess(Box::new(array), index));
+                return Ok(Expression::SizeOf(SizeOf::Value(Box::new(self.from_expression(value)?))));
+            }
+        }
+        match self.resolve_type(type_info) {
+            Type::Array(_, Some(size)) => self.from_expression(*size),
+            array @ Type::Array(_, None) => Ok(Expression::SizeOf(SizeOf::Unsized(self.to_typing(array)))),
+            Type::Struct(name) | Type::Generic(name, _) => {
+                let size = self.sizes.get(&name).copied().unwrap_or(1);
+                Ok(Expression::Literal(Literal::Integer(size as i64)))
+            }
+            Type::Tuple(elements) => Ok(Expression::Literal(Literal::Integer(elements.len() as i64))),
+            Type::Optional(inner) => self.from_sizeof_type(*inner),
+            Type::Parameter(_) => Ok(Expression::Literal(Literal::Integer(1))),
+        }
+    }
+
+    fn to_literal(&self, literal: crate::parsing::Literal) -> Literal {
+        match literal {
+            crate::parsing::Literal::Integer(i) => Literal::Integer(i),
+            crate::parsing::Literal::Float(f) => Literal::Float(f),
+            crate::parsing::Literal::String(s) => Literal::String(s),
+            crate::parsing::Literal::Char(c) => Literal::Character(c),
+            crate::parsing::Literal::Null => Literal::Null,
+        }
+    }
+
+    fn from_match(&self, match_expr: MatchExpression) -> Result<Expression, String> {
+        let scrutinee = self.from_expression(*match_expr.scrutinee)?;
+        let mut arms = Vec::new();
+        for arm in match_expr.arms {
+            arms.push(MatchArm {
+                pattern: self.from_pattern(arm.pattern),
+                guard: arm.guard.map(|guard| self.from_expression(guard)).transpose()?,
+                body: self.from_expression(arm.body)?,
+            });
+        }
+        Ok(Expression::Match(Match {
+            scrutinee: Box::new(scrutinee),
+            arms,
+        }))
+    }
+
+    fn from_pattern(&self, pattern: crate::parsing::Pattern) -> Pattern {
+        match pattern {
+            crate::parsing::Pattern::Wildcard => Pattern::Wildcard,
+            crate::parsing::Pattern::Binding(name) => Pattern::Binding(name),
+            crate::parsing::Pattern::Literal(literal) => Pattern::Literal(self.to_literal(literal)),
+            crate::parsing::Pattern::Variant(enum_name, variant, fields) => Pattern::Variant {
+                enum_name,
+                variant,
+                fields: fields.into_iter().map(|p| self.from_pattern(p)).collect(),
             },
-            ExpressionDecl::Struct(name, fields) => {
-                self.from_expression(ExpressionDecl::Struct(name.clone(), fields))
+            crate::parsing::Pattern::Struct(name, fields) => Pattern::Struct {
+                name,
+                fields: fields.into_iter().map(|(field, p)| (field, self.from_pattern(p))).collect(),
             },
+            crate::parsing::Pattern::Array(elements) => Pattern::Array(elements.into_iter().map(|p| self.from_pattern(p)).collect()),
+            crate::parsing::Pattern::Rest(name) => Pattern::Rest(name),
+            crate::parsing::Pattern::Tuple(elements) => Pattern::Tuple(elements.into_iter().map(|p| self.from_pattern(p)).collect()),
         }
     }
 
@@ -544,6 +977,12 @@ impl IR {
             crate::parsing::BinaryOperator::GreaterEqual => Operator::GreaterEqual,
             crate::parsing::BinaryOperator::And => Operator::And,
             crate::parsing::BinaryOperator::Or => Operator::Or,
+            crate::parsing::BinaryOperator::BitwiseAnd => Operator::BitwiseAnd,
+            crate::parsing::BinaryOperator::BitwiseOr => Operator::BitwiseOr,
+            crate::parsing::BinaryOperator::BitwiseXor => Operator::BitwiseXor,
+            crate::parsing::BinaryOperator::ShiftLeft => Operator::ShiftLeft,
+            crate::parsing::BinaryOperator::ShiftRight => Operator::ShiftRight,
+            crate::parsing::BinaryOperator::Coalesce => Operator::Coalesce,
         }
     }
 
@@ -551,12 +990,14 @@ impl IR {
         match op {
             crate::parsing::UnaryOperator::Negate => Operator::Subtract,
             crate::parsing::UnaryOperator::Not => Operator::NotEqual,
+            crate::parsing::UnaryOperator::BitwiseNot => Operator::BitwiseNot,
             _ => panic!("Unsupported unary operator: {:?}", op),
         }
     }
 
-    pub fn lookup_function(&self, name: &str) -> Option<&Function> {
-        self.functions.get(name)
+    /// The overloads of a top-level function, in the order they were declared
+    pub fn lookup_function(&self, name: &str) -> Option<&[Function]> {
+        self.functions.get(name).map(Vec::as_slice)
     }
 }
 
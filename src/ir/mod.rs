@@ -1,9 +1,15 @@
 mod types;
 mod expressions;
 mod actions;
+#[allow(clippy::module_inception)]
 mod ir;
+mod checker;
+pub mod resolver;
+mod passes;
+pub mod cfg;
 
-pub use types::{Operator, Literal, VariableData};
-pub use expressions::Expression;
-pub use actions::{Action, Function, Variable, Struct};
-pub use ir::IR;
\ No newline at end of file
+pub use types::{Operator, Literal, VariableData, BindingKind, Attribute, Typing};
+pub use expressions::{Expression, FunctionCall, Pattern, SizeOf};
+pub use actions::{Action, Function, Variable, Binding, Struct, Enum, Interface, Impl, Import, ForIn, Loop, Switch, Destructure, Try};
+pub use ir::IR;
+pub use passes::{PassManager, OptimizationLevel};
\ No newline at end of file
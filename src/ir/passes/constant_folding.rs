@@ -0,0 +1,82 @@
+use crate::ir::resolver::Symbol;
+use super::{Pass, Visitor, walk_block};
+use crate::ir::types::{Literal, Operator};
+use crate::ir::expressions::Expression;
+use crate::ir::actions::Action;
+
+/// Computes operations on literals ahead of time, e.g. `2 * 3` becomes `6`.
+/// Operations that would fail at runtime, like a division by zero or an overflow, are left for the program to report
+/// as an ArithmeticError.
+pub struct ConstantFolding;
+
+impl Pass for ConstantFolding {
+    fn run(&self, actions: &mut Vec<Action>, _symbols: &[Symbol]) -> bool {
+        let mut folder = Folder { changed: false };
+        walk_block(&mut folder, actions);
+        folder.changed
+    }
+}
+
+struct Folder {
+    changed: bool,
+}
+
+impl Visitor for Folder {
+    fn visit_expression(&mut self, expr: &mut Expression) {
+        let Expression::Operation(operation) = expr else {
+            return;
+        };
+        let (Expression::Literal(left), Expression::Literal(right)) = (&*operation.left, &*operation.right) else {
+            return;
+        };
+        if let Some(folded) = ConstantFolding::fold(&operation.operator, left, right) {
+            *expr = Expression::Literal(folded);
+            self.changed = true;
+        }
+    }
+}
+
+impl ConstantFolding {
+    /// The result of the operation, computed the way the builtin types do it
+    fn fold(operator: &Operator, left: &Literal, right: &Literal) -> Option<Literal> {
+        // comparing ints gives 1 or 0
+        let flag = |condition: bool| Some(Literal::Integer(i64::from(condition)));
+        match (left, right) {
+            (Literal::Integer(a), Literal::Integer(b)) => match operator {
+                Operator::Add => a.checked_add(*b).map(Literal::Integer),
+                Operator::Subtract => a.checked_sub(*b).map(Literal::Integer),
+                Operator::Multiply => a.checked_mul(*b).map(Literal::Integer),
+                Operator::Divide => a.checked_div(*b).map(Literal::Integer),
+                Operator::Modulo => a.checked_rem(*b).map(Literal::Integer),
+                Operator::BitwiseAnd => Some(Literal::Integer(a & b)),
+                Operator::BitwiseOr => Some(Literal::Integer(a | b)),
+                Operator::BitwiseXor => Some(Literal::Integer(a ^ b)),
+                Operator::ShiftLeft if (0..64).contains(b) => Some(Literal::Integer(a << b)),
+                Operator::ShiftRight if (0..64).contains(b) => Some(Literal::Integer(a >> b)),
+                Operator::BitwiseNot => Some(Literal::Integer(!a)),
+                Operator::Equal => flag(a == b),
+                Operator::NotEqual => flag(a != b),
+                Operator::Less => flag(a < b),
+                Operator::LessEqual => flag(a <= b),
+                Operator::Greater => flag(a > b),
+                Operator::GreaterEqual => flag(a >= b),
+                _ => None,
+            },
+            (Literal::Float(a), Literal::Float(b)) => match operator {
+                Operator::Add => Some(Literal::Float(a + b)),
+                Operator::Subtract => Some(Literal::Float(a - b)),
+                Operator::Multiply => Some(Literal::Float(a * b)),
+                Operator::Divide if *b != 0.0 => Some(Literal::Float(a / b)),
+                Operator::Modulo if *b != 0.0 => Some(Literal::Float(a % b)),
+                _ => None,
+            },
+            (Literal::String(a), Literal::String(b)) => match operator {
+                Operator::Add => Some(Literal::String(format!("{}{}", a, b))),
+                Operator::Equal => Some(Literal::Boolean(a == b)),
+                Operator::NotEqual => Some(Literal::Boolean(a != b)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use crate::ir::resolver::Symbol;
+use super::{Pass, Visitor, walk_block};
+use crate::ir::expressions::Expression;
+use crate::ir::actions::Action;
+
+/// Replaces an `if` whose condition is a literal with the branch it always takes,
+/// and removes loops whose condition is false before the first iteration
+pub struct DeadBranches;
+
+impl Pass for DeadBranches {
+    fn run(&self, actions: &mut Vec<Action>, _symbols: &[Symbol]) -> bool {
+        let mut eliminator = Eliminator { changed: false };
+        walk_block(&mut eliminator, actions);
+        eliminator.changed
+    }
+}
+
+struct Eliminator {
+    changed: bool,
+}
+
+impl Visitor for Eliminator {
+    fn visit_block(&mut self, actions: &mut Vec<Action>) {
+        let mut i = 0;
+        while i < actions.len() {
+            // Branches run in the scope of the block around them, so their actions can take the place of the `if`
+            let replacement = match &mut actions[i] {
+                Action::Conditional(conditional) => match &conditional.condition {
+                    Expression::Literal(literal) if literal.is_truthy() => Some(std::mem::take(&mut conditional.then_actions)),
+                    Expression::Literal(_) => Some(std::mem::take(&mut conditional.else_actions)),
+                    _ => None,
+                },
+                Action::Loop(loop_) if loop_.check_first => match &loop_.condition {
+                    Expression::Literal(literal) if !literal.is_truthy() => Some(std::mem::take(&mut loop_.init)),
+                    _ => None,
+                },
+                _ => None,
+            };
+            match replacement {
+                Some(replacement) => {
+                    let length = replacement.len();
+                    actions.splice(i..=i, replacement);
+                    i += length;
+                    self.changed = true;
+                }
+                None => i += 1,
+            }
+        }
+    }
+}
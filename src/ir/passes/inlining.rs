@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use crate::ir::resolver::{Symbol, SymbolKind};
+use super::{Pass, Visitor, walk_block, walk_expression, function_counts};
+use crate::ir::types::{Operator, Attribute};
+use crate::ir::expressions::{Expression, Operation};
+use crate::ir::actions::{Action, Function};
+
+/// Replaces calls to small top-level functions, whose body is a single `return` of an expression
+/// without calls or assignments, with that expression.
+///
+/// The arguments must be literals or variables, so substituting them for the parameters neither
+/// repeats nor skips any work. Such a function can't be recursive, as it calls nothing.
+///
+/// `@inline` inlines a function whatever the size of its expression, while `@deprecated` and `@memoize`
+/// functions are never inlined, as they need the call to run.
+pub struct Inlining;
+
+impl Inlining {
+    // the most nodes an expression can have to be inlined
+    const MAX_SIZE: usize = 16;
+}
+
+impl Pass for Inlining {
+    fn run(&self, actions: &mut Vec<Action>, symbols: &[Symbol]) -> bool {
+        let counts = function_counts(actions);
+        let functions: HashMap<String, (Vec<String>, Expression)> = actions.iter()
+            .filter_map(|action| match action {
+                Action::Function(function) if counts.get(&function.name) == Some(&1) => inlined_body(function)
+                    .map(|body| (function.name.clone(), (function.params.iter().map(|param| param.name.clone()).collect(), body.clone()))),
+                _ => None,
+            })
+            .collect();
+        if functions.is_empty() {
+            return false;
+        }
+        let mut inliner = Inliner { functions, symbols, changed: false };
+        walk_block(&mut inliner, actions);
+        inliner.changed
+    }
+}
+
+/// The expression a function returns, if the function can be inlined
+fn inlined_body(function: &Function) -> Option<&Expression> {
+    if function.generator || function.variadic || ["deprecated", "memoize"].iter().any(|name| Attribute::has(&function.attributes, name)) {
+        return None;
+    }
+    let forced = Attribute::has(&function.attributes, "inline");
+    match function.body.as_slice() {
+        [Action::Operation(Operation { operator: Operator::Return, left, .. })] => {
+            size(left).filter(|size| forced || *size <= Inlining::MAX_SIZE).map(|_| &**left)
+        }
+        _ => None,
+    }
+}
+
+/// The number of nodes of an expression made of literals, variables and operators, None for anything else
+fn size(expr: &Expression) -> Option<usize> {
+    match expr {
+        Expression::Literal(_) | Expression::Variable(..) => Some(1),
+        Expression::Operation(operation) => match operation.operator {
+            Operator::Assignment | Operator::Conditional | Operator::Comma | Operator::Try | Operator::Return
+            | Operator::Throw | Operator::Yield | Operator::Break | Operator::Continue | Operator::Expression => None,
+            _ => Some(1 + size(&operation.left)? + size(&operation.right)?),
+        },
+        Expression::Array(elements) | Expression::Tuple(elements) => {
+            elements.iter().try_fold(1, |total, element| Some(total + size(element)?))
+        }
+        _ => None,
+    }
+}
+
+struct Inliner<'a> {
+    // the parameters and the returned expression of each function to inline
+    functions: HashMap<String, (Vec<String>, Expression)>,
+    symbols: &'a [Symbol],
+    changed: bool,
+}
+
+impl Visitor for Inliner<'_> {
+    fn visit_expression(&mut self, expr: &mut Expression) {
+        let Expression::FunctionCall(call) = expr else { return };
+        // a parameter, variable or nested function of the same name shadows the top-level function
+        let top_level = call.symbol.and_then(|id| self.symbols.get(id))
+            .is_some_and(|symbol| symbol.kind == SymbolKind::Function && symbol.function.is_none());
+        if !top_level {
+            return;
+        }
+        let Some((params, body)) = self.functions.get(&call.name) else { return };
+        if call.args.len() != params.len() || !call.args.iter().all(|arg| matches!(arg, Expression::Literal(_) | Expression::Variable(..))) {
+            return;
+        }
+        let mut inlined = body.clone();
+        let mut substitution = Substitution { arguments: params.iter().cloned().zip(call.args.iter().cloned()).collect() };
+        walk_expression(&mut substitution, &mut inlined);
+        *expr = inlined;
+        self.changed = true;
+    }
+}
+
+/// Replaces the parameters of an inlined function with the arguments of the call
+struct Substitution {
+    arguments: HashMap<String, Expression>,
+}
+
+impl Visitor for Substitution {
+    fn visit_expression(&mut self, expr: &mut Expression) {
+        if let Expression::Variable(name, _) = expr {
+            if let Some(argument) = self.arguments.get(name) {
+                *expr = argument.clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+mod constant_folding;
+mod dead_branches;
+mod unreachable_code;
+mod tail_calls;
+mod inlining;
+
+use std::collections::HashMap;
+use super::ir::IR;
+use super::types::VariableData;
+use super::expressions::{Expression, SizeOf};
+use super::actions::{Action, Function};
+use super::resolver::Symbol;
+
+pub use constant_folding::ConstantFolding;
+pub use dead_branches::DeadBranches;
+pub use unreachable_code::UnreachableCode;
+pub use tail_calls::TailCalls;
+pub use inlining::Inlining;
+
+/// A transformation of the IR that keeps the behavior of the program
+pub trait Pass {
+    /// Transforms the actions, returning whether anything changed. The symbols are the table the resolved
+    /// variables and calls refer to
+    fn run(&self, actions: &mut Vec<Action>, symbols: &[Symbol]) -> bool;
+}
+
+/// How much the IR is optimized before it runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum OptimizationLevel {
+    /// Runs the code as written
+    None,
+    /// Folds constants, removes code that can never run and turns self-recursive tail calls into loops
+    #[default]
+    Basic,
+    /// Also inlines small functions, so errors raised in them are reported where they are called
+    Full,
+}
+
+/// Runs passes over the IR, over and over until none of them changes it, as one pass can open
+/// opportunities for another: folding `1 > 2` to `0` lets `DeadBranches` remove an `if`
+#[derive(Default)]
+pub struct PassManager {
+    passes: Vec<Box<dyn Pass>>,
+}
+
+impl PassManager {
+    // rounds after which the passes stop even if they still change the IR
+    const MAX_ROUNDS: usize = 8;
+
+    pub fn new() -> Self {
+        PassManager::default()
+    }
+
+    /// The passes run at the given optimization level
+    pub fn for_level(level: OptimizationLevel) -> Self {
+        let mut manager = PassManager::new();
+        if level >= OptimizationLevel::Full {
+            manager.add(Inlining);
+        }
+        if level >= OptimizationLevel::Basic {
+            manager.add(ConstantFolding);
+            manager.add(DeadBranches);
+            manager.add(UnreachableCode);
+            manager.add(TailCalls);
+        }
+        manager
+    }
+
+    pub fn add(&mut self, pass: impl Pass + 'static) {
+        self.passes.push(Box::new(pass));
+    }
+
+    pub fn run(&self, ir: &mut IR) {
+        for _ in 0..Self::MAX_ROUNDS {
+            let mut changed = false;
+            for pass in &self.passes {
+                changed |= pass.run(&mut ir.actions, &ir.symbols);
+            }
+            if !changed {
+                break;
+            }
+        }
+        ir.declare_symbols();
+    }
+}
+
+/// How many functions of each name the actions declare, in any block
+fn function_counts(actions: &mut Vec<Action>) -> HashMap<String, usize> {
+    struct Counter(HashMap<String, usize>);
+    impl Visitor for Counter {
+        fn visit_block(&mut self, actions: &mut Vec<Action>) {
+            for action in actions.iter() {
+                if let Action::Function(function) = action {
+                    *self.0.entry(function.name.clone()).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+    let mut counter = Counter(HashMap::new());
+    walk_block(&mut counter, actions);
+    counter.0
+}
+
+/// Walks every block and expression of the IR, including the bodies of functions and lambdas
+trait Visitor {
+    /// Called on every block, after the blocks and expressions in it
+    fn visit_block(&mut self, _actions: &mut Vec<Action>) {}
+    /// Called on every expression, after the expressions in it
+    fn visit_expression(&mut self, _expr: &mut Expression) {}
+}
+
+fn walk_block(visitor: &mut impl Visitor, actions: &mut Vec<Action>) {
+    for action in actions.iter_mut() {
+        walk_action(visitor, action);
+    }
+    visitor.visit_block(actions);
+}
+
+fn walk_function(visitor: &mut impl Visitor, function: &mut Function) {
+    for default in function.defaults.iter_mut().flatten() {
+        walk_expression(visitor, default);
+    }
+    walk_block(visitor, &mut function.body);
+}
+
+fn walk_action(visitor: &mut impl Visitor, action: &mut Action) {
+    match action {
+        Action::Function(function) => walk_function(visitor, function),
+        Action::Impl(implementation) => {
+            for method in &mut implementation.methods {
+                walk_function(visitor, method);
+            }
+        }
+        Action::Variable(variable) => walk_data(visitor, &mut variable.data),
+        Action::Binding(binding) => {
+            if let Some(value) = &mut binding.value {
+                walk_expression(visitor, value);
+            }
+        }
+        Action::Operation(operation) => {
+            walk_expression(visitor, &mut operation.left);
+            walk_expression(visitor, &mut operation.right);
+        }
+        Action::Expression(expr) => walk_expression(visitor, expr),
+        Action::Conditional(conditional) => {
+            walk_expression(visitor, &mut conditional.condition);
+            walk_block(visitor, &mut conditional.then_actions);
+            walk_block(visitor, &mut conditional.else_actions);
+        }
+        Action::Loop(loop_) => {
+            walk_block(visitor, &mut loop_.init);
+            walk_expression(visitor, &mut loop_.condition);
+            walk_block(visitor, &mut loop_.body);
+            walk_block(visitor, &mut loop_.update);
+        }
+        Action::ForIn(for_in) => {
+            walk_expression(visitor, &mut for_in.iterable);
+            walk_block(visitor, &mut for_in.body);
+        }
+        Action::Switch(switch) => {
+            walk_expression(visitor, &mut switch.value);
+            for case in &mut switch.cases {
+                if let Some(label) = &mut case.label {
+                    walk_expression(visitor, label);
+                }
+                walk_block(visitor, &mut case.body);
+            }
+        }
+        Action::Destructure(destructure) => walk_expression(visitor, &mut destructure.value),
+        Action::Try(try_) => {
+            walk_block(visitor, &mut try_.body);
+            if let Some(catch) = &mut try_.catch {
+                walk_block(visitor, &mut catch.body);
+            }
+            if let Some(finally) = &mut try_.finally {
+                walk_block(visitor, finally);
+            }
+        }
+        Action::Struct(_) | Action::Enum(_) | Action::Interface(_) | Action::Import(_) => {}
+    }
+}
+
+fn walk_data(visitor: &mut impl Visitor, data: &mut VariableData) {
+    match data {
+        VariableData::Expression(expr) => walk_expression(visitor, expr),
+        VariableData::Array(elements) => elements.iter_mut().for_each(|element| walk_data(visitor, element)),
+        VariableData::StructInstance(_, fields) => fields.iter_mut().for_each(|(_, field)| walk_data(visitor, field)),
+        VariableData::Literal(_) | VariableData::Null => {}
+    }
+}
+
+fn walk_expression(visitor: &mut impl Visitor, expr: &mut Expression) {
+    match expr {
+        Expression::Operation(operation) => {
+            walk_expression(visitor, &mut operation.left);
+            walk_expression(visitor, &mut operation.right);
+        }
+        Expression::FunctionCall(call) => call.args.iter_mut().for_each(|arg| walk_expression(visitor, arg)),
+        Expression::Call(call) => {
+            walk_expression(visitor, &mut call.callee);
+            call.args.iter_mut().for_each(|arg| walk_expression(visitor, arg));
+        }
+        Expression::Array(elements) | Expression::Tuple(elements) => {
+            elements.iter_mut().for_each(|element| walk_expression(visitor, element));
+        }
+        Expression::Variant(constructor) => constructor.args.iter_mut().for_each(|arg| walk_expression(visitor, arg)),
+        Expression::Match(match_expr) => {
+            walk_expression(visitor, &mut match_expr.scrutinee);
+            for arm in &mut match_expr.arms {
+                if let Some(guard) = &mut arm.guard {
+                    walk_expression(visitor, guard);
+                }
+                walk_expression(visitor, &mut arm.body);
+            }
+        }
+        Expression::Lambda(function) => walk_function(visitor, function),
+        Expression::Range(range) => {
+            walk_expression(visitor, &mut range.start);
+            walk_expression(visitor, &mut range.end);
+            if let Some(step) = &mut range.step {
+                walk_expression(visitor, step);
+            }
+        }
+        Expression::Allocation(allocation) => {
+            allocation.sizes.iter_mut().for_each(|size| walk_expression(visitor, size));
+            if let Some(initializer) = &mut allocation.initializer {
+                walk_expression(visitor, initializer);
+            }
+        }
+        Expression::SizeOf(SizeOf::Value(value)) | Expression::NamedArgument(_, value) => walk_expression(visitor, value),
+        Expression::SizeOf(SizeOf::Unsized(_)) | Expression::Literal(_) | Expression::Variable(..) => {}
+    }
+    visitor.visit_expression(expr);
+}
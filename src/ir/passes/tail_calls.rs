@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use crate::ir::resolver::Symbol;
+use super::{Pass, Visitor, walk_block, function_counts};
+use crate::ir::types::{Operator, Literal, Attribute};
+use crate::ir::expressions::{Expression, Operation, Pattern};
+use crate::ir::actions::{Action, Function, Loop, Destructure};
+
+/// Turns the calls a function makes to itself in tail position, like `return f(n - 1, acc);`, into a jump
+/// back to the start of its body with the parameters reassigned, so the recursion runs as a loop.
+///
+/// Only self-recursion is eliminated: a tail call to another function, as in mutually recursive functions,
+/// is still a call and still takes stack space.
+///
+/// Calls inside `try` are left alone, as the `catch` must still see the errors of the call.
+pub struct TailCalls;
+
+// the label of the loop wrapping the body, which no source label can clash with
+const LABEL: &str = "tail call";
+
+impl Pass for TailCalls {
+    fn run(&self, actions: &mut Vec<Action>, _symbols: &[Symbol]) -> bool {
+        let mut eliminator = Eliminator { counts: function_counts(actions), changed: false };
+        walk_block(&mut eliminator, actions);
+        eliminator.changed
+    }
+}
+
+struct Eliminator {
+    // overloads can't be told apart before the call runs
+    counts: HashMap<String, usize>,
+    changed: bool,
+}
+
+impl Visitor for Eliminator {
+    fn visit_block(&mut self, actions: &mut Vec<Action>) {
+        for action in actions {
+            if let Action::Function(function) = action {
+                if self.counts.get(&function.name) == Some(&1) {
+                    self.changed |= eliminate(function);
+                }
+            }
+        }
+    }
+}
+
+fn eliminate(function: &mut Function) -> bool {
+    if function.generator || function.variadic || Attribute::has(&function.attributes, "memoize") || has_stray_jump(&function.body, false) {
+        return false;
+    }
+    let params: Vec<String> = function.params.iter().map(|param| param.name.clone()).collect();
+    if !replace_tail_calls(&mut function.body, &function.name, &params) {
+        return false;
+    }
+    let mut body = std::mem::take(&mut function.body);
+    // running off the end of the body still returns
+    body.push(jump(Operator::Break));
+    function.body = vec![Action::Loop(Loop {
+        label: Some(LABEL.to_string()),
+        init: Vec::new(),
+        condition: Expression::Literal(Literal::Integer(1)),
+        update: Vec::new(),
+        body,
+        check_first: true,
+    })];
+    true
+}
+
+/// Whether a `break` or `continue` outside of any loop would end up in the loop wrapping the body
+fn has_stray_jump(actions: &[Action], in_switch: bool) -> bool {
+    actions.iter().any(|action| match action {
+        Action::Operation(operation) if matches!(operation.operator, Operator::Break | Operator::Continue) => {
+            let labeled = matches!(*operation.left, Expression::Variable(..));
+            let leaves_switch = in_switch && operation.operator == Operator::Break;
+            !(labeled || leaves_switch)
+        }
+        Action::Conditional(conditional) => has_stray_jump(&conditional.then_actions, in_switch) || has_stray_jump(&conditional.else_actions, in_switch),
+        Action::Switch(switch) => switch.cases.iter().any(|case| has_stray_jump(&case.body, true)),
+        Action::Try(try_) => has_stray_jump(&try_.body, in_switch)
+            || try_.catch.as_ref().is_some_and(|catch| has_stray_jump(&catch.body, in_switch))
+            || try_.finally.as_ref().is_some_and(|finally| has_stray_jump(finally, in_switch)),
+        _ => false,
+    })
+}
+
+/// Replaces every `return name(args);` of the block with the reassignment of the parameters and a jump
+fn replace_tail_calls(actions: &mut Vec<Action>, name: &str, params: &[String]) -> bool {
+    let mut changed = false;
+    for action in std::mem::take(actions) {
+        match action {
+            Action::Operation(operation) if operation.operator == Operator::Return => match *operation.left {
+                Expression::FunctionCall(call) if call.name == name && call.args.len() == params.len()
+                    && !call.args.iter().any(|arg| matches!(arg, Expression::NamedArgument(..))) => {
+                    actions.extend(reassign(params, call.args));
+                    actions.push(jump(Operator::Continue));
+                    changed = true;
+                }
+                left => actions.push(Action::Operation(Operation { left: Box::new(left), ..operation })),
+            },
+            mut action => {
+                match &mut action {
+                    Action::Conditional(conditional) => {
+                        changed |= replace_tail_calls(&mut conditional.then_actions, name, params);
+                        changed |= replace_tail_calls(&mut conditional.else_actions, name, params);
+                    }
+                    Action::Loop(loop_) => changed |= replace_tail_calls(&mut loop_.body, name, params),
+                    // the loop restores its variable once it ends, which would undo the reassignment of a parameter
+                    Action::ForIn(for_in) if !params.contains(&for_in.variable) => changed |= replace_tail_calls(&mut for_in.body, name, params),
+                    Action::Switch(switch) => {
+                        for case in &mut switch.cases {
+                            changed |= replace_tail_calls(&mut case.body, name, params);
+                        }
+                    }
+                    _ => {}
+                }
+                actions.push(action);
+            }
+        }
+    }
+    changed
+}
+
+/// Assigns the arguments to the parameters all at once, like `(n, acc) = (n - 1, acc + n);`, as the
+/// arguments can use the parameters being reassigned
+fn reassign(params: &[String], args: Vec<Expression>) -> Option<Action> {
+    let (mut names, mut values): (Vec<String>, Vec<Expression>) = params.iter().cloned().zip(args)
+        .filter(|(param, arg)| !matches!(arg, Expression::Variable(name, _) if name == param))
+        .unzip();
+    match names.len() {
+        0 => None,
+        1 => Some(Action::Operation(Operation {
+            operator: Operator::Expression,
+            left: Box::new(Expression::Operation(Operation {
+                operator: Operator::Assignment,
+                left: Box::new(Expression::Variable(names.remove(0), None)),
+                right: Box::new(values.remove(0)),
+            })),
+            right: Box::new(Expression::Literal(Literal::Integer(0))),
+        })),
+        _ => Some(Action::Destructure(Destructure {
+            pattern: Pattern::Tuple(names.into_iter().map(Pattern::Binding).collect()),
+            value: Expression::Tuple(values),
+        })),
+    }
+}
+
+fn jump(operator: Operator) -> Action {
+    Action::Operation(Operation {
+        operator,
+        left: Box::new(Expression::Variable(LABEL.to_string(), None)),
+        right: Box::new(Expression::Literal(Literal::Integer(0))),
+    })
+}
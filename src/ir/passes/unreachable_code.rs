@@ -0,0 +1,31 @@
+use crate::ir::resolver::Symbol;
+use super::{Pass, Visitor, walk_block};
+use crate::ir::types::Operator;
+use crate::ir::actions::Action;
+
+/// Removes the actions of a block that follow a `return`, `break`, `continue` or `throw`, as they never run
+pub struct UnreachableCode;
+
+impl Pass for UnreachableCode {
+    fn run(&self, actions: &mut Vec<Action>, _symbols: &[Symbol]) -> bool {
+        let mut remover = Remover { changed: false };
+        walk_block(&mut remover, actions);
+        remover.changed
+    }
+}
+
+struct Remover {
+    changed: bool,
+}
+
+impl Visitor for Remover {
+    fn visit_block(&mut self, actions: &mut Vec<Action>) {
+        let end = actions.iter().position(|action| matches!(action,
+            Action::Operation(operation) if matches!(operation.operator, Operator::Return | Operator::Break | Operator::Continue | Operator::Throw)
+        ));
+        if let Some(end) = end.filter(|end| end + 1 < actions.len()) {
+            actions.truncate(end + 1);
+            self.changed = true;
+        }
+    }
+}
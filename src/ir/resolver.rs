@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use super::types::{Operator, VariableData};
+use super::expressions::{Expression, Operation, SizeOf};
+use super::actions::{Action, Function};
+
+/// The index of a symbol in the IR's symbol table
+pub type SymbolId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymbolKind {
+    Builtin,
+    Function,
+    Method,
+    Struct,
+    Enum,
+    Interface,
+    Import,
+    Variable,
+    Parameter,
+}
+
+/// A declared name, with the function it is local to, None for top-level names
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub function: Option<String>,
+}
+
+/// The resolver binds every variable and called function to the symbol it refers to, before the program runs,
+/// by setting the symbol id on the node.
+///
+/// Declarations are hoisted: a name can be used anywhere in the scope that declares it, so functions can call
+/// functions declared after them. A variable assigned anywhere in a function is local to the whole function,
+/// and functions also see the names of the scopes enclosing them. A function nested in another one is a closure
+/// created where it is written, so it is only declared from there on, and the bindings of a match arm, a catch
+/// block and a for-in loop only exist inside them.
+///
+/// Methods of types from imported modules are only known at runtime, so once a module is imported,
+/// an unknown call with a receiver is left unresolved; an unknown call without arguments is still an error.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Resolver {
+    pub symbols: Vec<Symbol>,
+    // innermost last, the first one holds the top-level names
+    scopes: Vec<HashMap<String, SymbolId>>,
+    // the function being resolved, None at the top level
+    function: Option<String>,
+    imports: bool,
+}
+
+impl Resolver {
+    /// Creates a resolver that knows the given builtin functions and types
+    pub fn new(builtins: &[String]) -> Self {
+        let mut resolver = Resolver { scopes: vec![HashMap::new()], ..Resolver::default() };
+        for name in builtins.iter().map(String::as_str).chain(["Error", "Result"]) {
+            resolver.declare(name, SymbolKind::Builtin);
+        }
+        resolver
+    }
+
+    pub fn resolve(&mut self, actions: &mut [Action]) -> Result<(), String> {
+        self.declare_block(actions);
+        self.resolve_actions(actions)
+    }
+
+    /// Adds a name to the innermost scope, unless it's already declared there
+    fn declare(&mut self, name: &str, kind: SymbolKind) {
+        let scope = self.scopes.last_mut().expect("the top-level scope is never left");
+        if !scope.contains_key(name) {
+            scope.insert(name.to_string(), self.symbols.len());
+            self.symbols.push(Symbol { name: name.to_string(), kind, function: self.function.clone() });
+        }
+    }
+
+    /// Declares everything the actions declare or assign, including in nested blocks but not in nested functions
+    fn declare_block(&mut self, actions: &[Action]) {
+        for action in actions {
+            match action {
+                Action::Function(function) => self.declare(&function.name, SymbolKind::Function),
+                Action::Struct(structure) => self.declare(&structure.name, SymbolKind::Struct),
+                Action::Enum(enumeration) => self.declare(&enumeration.name, SymbolKind::Enum),
+                Action::Interface(interface) => {
+                    self.declare(&interface.name, SymbolKind::Interface);
+                    for method in &interface.methods {
+                        self.declare(&method.name, SymbolKind::Method);
+                    }
+                }
+                Action::Impl(implementation) => {
+                    for method in &implementation.methods {
+                        self.declare(&method.name, SymbolKind::Method);
+                    }
+                }
+                Action::Import(import) => {
+                    self.imports = true;
+                    for name in &import.names {
+                        self.declare(name, SymbolKind::Import);
+                    }
+                }
+                Action::Variable(variable) => self.declare(&variable.name, SymbolKind::Variable),
+                Action::Binding(binding) => self.declare(&binding.name, SymbolKind::Variable),
+                Action::Operation(operation) => self.declare_assignments(operation),
+                Action::Expression(Expression::Operation(operation)) => self.declare_assignments(operation),
+                Action::Expression(_) => {}
+                Action::Conditional(conditional) => {
+                    self.declare_block(&conditional.then_actions);
+                    self.declare_block(&conditional.else_actions);
+                }
+                Action::Loop(loop_) => {
+                    self.declare_block(&loop_.init);
+                    self.declare_block(&loop_.body);
+                    self.declare_block(&loop_.update);
+                }
+                Action::ForIn(for_in) => self.declare_block(&for_in.body),
+                Action::Switch(switch) => {
+                    for case in &switch.cases {
+                        self.declare_block(&case.body);
+                    }
+                }
+                Action::Destructure(destructure) => {
+                    for name in destructure.pattern.bindings() {
+                        self.declare(name, SymbolKind::Variable);
+                    }
+                }
+                Action::Try(try_) => {
+                    self.declare_block(&try_.body);
+                    if let Some(catch) = &try_.catch {
+                        self.declare_block(&catch.body);
+                    }
+                    if let Some(finally) = &try_.finally {
+                        self.declare_block(finally);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Declares the variables assigned by `x = ...`, which is how variables are introduced
+    fn declare_assignments(&mut self, operation: &Operation) {
+        if let (Operator::Assignment, Expression::Variable(name, _)) = (&operation.operator, &*operation.left) {
+            // a nested function is declared where it is written, by `resolve_actions`
+            if !matches!(&*operation.right, Expression::Lambda(function) if function.name == *name) {
+                self.declare(name, SymbolKind::Variable);
+            }
+        }
+        for operand in [&*operation.left, &*operation.right] {
+            if let Expression::Operation(operation) = operand {
+                self.declare_assignments(operation);
+            }
+        }
+    }
+
+    /// Finds the symbol a name refers to, innermost scope first
+    fn lookup(&self, name: &str) -> Option<SymbolId> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name).copied())
+    }
+
+    fn reference(&self, name: &str, kind: &str) -> Result<SymbolId, String> {
+        match self.lookup(name) {
+            Some(id) => Ok(id),
+            None => match &self.function {
+                Some(function) if !function.is_empty() => Err(format!("{} '{}' not found, in function '{}'", kind, name, function)),
+                Some(_) => Err(format!("{} '{}' not found, in anonymous function", kind, name)),
+                None => Err(format!("{} '{}' not found", kind, name)),
+            },
+        }
+    }
+
+    fn resolve_function(&mut self, function: &mut Function) -> Result<(), String> {
+        let saved_function = self.function.replace(function.name.clone());
+        self.scopes.push(HashMap::new());
+        for param in &function.params {
+            self.declare(&param.name, SymbolKind::Parameter);
+        }
+        let result = function.defaults.iter_mut().flatten()
+            .try_for_each(|default| self.resolve_expression(default))
+            .and_then(|_| self.resolve(&mut function.body));
+        self.scopes.pop();
+        self.function = saved_function;
+        result
+    }
+
+    /// Resolves the actions in order. The bodies of the nested functions they declare are resolved once the rest
+    /// of the block is, so nested functions can call each other
+    fn resolve_actions(&mut self, actions: &mut [Action]) -> Result<(), String> {
+        let mut nested = Vec::new();
+        for action in actions {
+            if nested_function(action).is_none() {
+                self.resolve_action(action)?;
+            } else if let Some((name, symbol, function)) = nested_function(action) {
+                self.declare(name, SymbolKind::Function);
+                *symbol = self.lookup(name);
+                nested.push(function);
+            }
+        }
+        nested.into_iter().try_for_each(|function| self.resolve_function(function))
+    }
+
+    /// Resolves a block with its own scope, holding the given bindings
+    fn resolve_scoped(&mut self, bindings: &[&String], resolve: impl FnOnce(&mut Self) -> Result<(), String>) -> Result<(), String> {
+        self.scopes.push(HashMap::new());
+        for name in bindings {
+            self.declare(name, SymbolKind::Variable);
+        }
+        let result = resolve(self);
+        self.scopes.pop();
+        result
+    }
+
+    fn resolve_action(&mut self, action: &mut Action) -> Result<(), String> {
+        match action {
+            Action::Function(function) => self.resolve_function(function),
+            Action::Impl(implementation) => implementation.methods.iter_mut().try_for_each(|method| self.resolve_function(method)),
+            Action::Struct(_) | Action::Enum(_) | Action::Interface(_) | Action::Import(_) => Ok(()),
+            Action::Variable(variable) => self.resolve_data(&mut variable.data),
+            Action::Binding(binding) => binding.value.iter_mut().try_for_each(|value| self.resolve_expression(value)),
+            // the label of `break` and `continue` is not a variable
+            Action::Operation(operation) if matches!(operation.operator, Operator::Break | Operator::Continue) => Ok(()),
+            Action::Operation(operation) => self.resolve_operation(operation),
+            Action::Expression(expr) => self.resolve_expression(expr),
+            Action::Conditional(conditional) => {
+                self.resolve_expression(&mut conditional.condition)?;
+                self.resolve_actions(&mut conditional.then_actions)?;
+                self.resolve_actions(&mut conditional.else_actions)
+            }
+            Action::Loop(loop_) => {
+                self.resolve_actions(&mut loop_.init)?;
+                self.resolve_expression(&mut loop_.condition)?;
+                self.resolve_actions(&mut loop_.body)?;
+                self.resolve_actions(&mut loop_.update)
+            }
+            Action::ForIn(for_in) => {
+                self.resolve_expression(&mut for_in.iterable)?;
+                self.resolve_scoped(&[&for_in.variable], |resolver| resolver.resolve_actions(&mut for_in.body))
+            }
+            Action::Switch(switch) => {
+                self.resolve_expression(&mut switch.value)?;
+                for case in &mut switch.cases {
+                    if let Some(label) = &mut case.label {
+                        self.resolve_expression(label)?;
+                    }
+                    self.resolve_actions(&mut case.body)?;
+                }
+                Ok(())
+            }
+            Action::Destructure(destructure) => self.resolve_expression(&mut destructure.value),
+            Action::Try(try_) => {
+                self.resolve_actions(&mut try_.body)?;
+                if let Some(catch) = &mut try_.catch {
+                    self.resolve_scoped(&[&catch.variable], |resolver| resolver.resolve_actions(&mut catch.body))?;
+                }
+                try_.finally.iter_mut().try_for_each(|finally| self.resolve_actions(finally))
+            }
+        }
+    }
+
+    fn resolve_data(&mut self, data: &mut VariableData) -> Result<(), String> {
+        match data {
+            VariableData::Expression(expr) => self.resolve_expression(expr),
+            VariableData::Array(elements) => elements.iter_mut().try_for_each(|element| self.resolve_data(element)),
+            VariableData::StructInstance(_, fields) => fields.iter_mut().try_for_each(|(_, field)| self.resolve_data(field)),
+            VariableData::Literal(_) | VariableData::Null => Ok(()),
+        }
+    }
+
+    fn resolve_expressions(&mut self, exprs: &mut [Expression]) -> Result<(), String> {
+        exprs.iter_mut().try_for_each(|expr| self.resolve_expression(expr))
+    }
+
+    fn resolve_expression(&mut self, expr: &mut Expression) -> Result<(), String> {
+        match expr {
+            Expression::Variable(name, symbol) => {
+                *symbol = Some(self.reference(name, "Variable")?);
+                Ok(())
+            }
+            Expression::FunctionCall(call) => {
+                self.resolve_expressions(&mut call.args)?;
+                // a method of an imported type gets its receiver as the first argument
+                if self.imports && !call.args.is_empty() && self.lookup(&call.name).is_none() {
+                    return Ok(());
+                }
+                call.symbol = Some(self.reference(&call.name, "Function")?);
+                Ok(())
+            }
+            Expression::Operation(operation) => self.resolve_operation(operation),
+            Expression::Call(call) => {
+                self.resolve_expression(&mut call.callee)?;
+                self.resolve_expressions(&mut call.args)
+            }
+            Expression::Array(elements) | Expression::Tuple(elements) => self.resolve_expressions(elements),
+            Expression::Variant(constructor) => self.resolve_expressions(&mut constructor.args),
+            Expression::Match(match_expr) => {
+                self.resolve_expression(&mut match_expr.scrutinee)?;
+                for arm in &mut match_expr.arms {
+                    let bindings = arm.pattern.bindings();
+                    self.resolve_scoped(&bindings, |resolver| {
+                        if let Some(guard) = &mut arm.guard {
+                            resolver.resolve_expression(guard)?;
+                        }
+                        resolver.resolve_expression(&mut arm.body)
+                    })?;
+                }
+                Ok(())
+            }
+            Expression::Lambda(function) => self.resolve_function(function),
+            Expression::Range(range) => {
+                self.resolve_expression(&mut range.start)?;
+                self.resolve_expression(&mut range.end)?;
+                range.step.iter_mut().try_for_each(|step| self.resolve_expression(step))
+            }
+            Expression::Allocation(allocation) => {
+                self.resolve_expressions(&mut allocation.sizes)?;
+                allocation.initializer.iter_mut().try_for_each(|initializer| self.resolve_expression(initializer))
+            }
+            Expression::SizeOf(SizeOf::Value(value)) => self.resolve_expression(value),
+            Expression::NamedArgument(_, value) => self.resolve_expression(value),
+            Expression::SizeOf(SizeOf::Unsized(_)) | Expression::Literal(_) => Ok(()),
+        }
+    }
+
+    fn resolve_operation(&mut self, operation: &mut Operation) -> Result<(), String> {
+        self.resolve_expression(&mut operation.left)?;
+        // a member is named by a string literal, it isn't resolved
+        match operation.operator {
+            Operator::MemberAccess | Operator::OptionalMemberAccess => Ok(()),
+            _ => self.resolve_expression(&mut operation.right),
+        }
+    }
+
+}
+
+/// The name, symbol and function of a nested function declaration: `fn f() {}` inside a function is stored
+/// as `f = fn() {};`, with the lambda named after the variable
+fn nested_function(action: &mut Action) -> Option<(&String, &mut Option<SymbolId>, &mut Function)> {
+    let Action::Operation(Operation { operator: Operator::Expression, left, .. }) = action else {
+        return None;
+    };
+    let Expression::Operation(Operation { operator: Operator::Assignment, left, right }) = &mut **left else {
+        return None;
+    };
+    match (&mut **left, &mut **right) {
+        (Expression::Variable(name, symbol), Expression::Lambda(function)) if *name == function.name => Some((name, symbol, function)),
+        _ => None,
+    }
+}
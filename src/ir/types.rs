@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Operator {
     Add,
@@ -13,12 +15,23 @@ pub enum Operator {
     GreaterEqual,
     And,
     Or,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    ShiftLeft,
+    ShiftRight,
+    BitwiseNot,
+    Coalesce,
+    OptionalMemberAccess,
+    Try,
     ArrayAccess,
     MemberAccess,
     Assignment,
     Conditional,
     Comma,
     Return,
+    Throw,
+    Yield,
     Break,
     Continue,
     Expression,
@@ -31,6 +44,75 @@ pub enum Literal {
     String(String),
     Character(char),
     Boolean(bool),
+    Null,
+}
+
+impl Literal {
+    /// Whether the literal counts as `true` in a condition, the same way as the value it evaluates to
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Literal::Integer(n) => *n != 0,
+            Literal::Float(x) => *x != 0.0,
+            Literal::String(s) => !s.is_empty(),
+            Literal::Character(_) => true,
+            Literal::Boolean(b) => *b,
+            Literal::Null => false,
+        }
+    }
+}
+
+impl Display for Literal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Literal::Integer(i) => write!(f, "{}", i),
+            Literal::Float(x) => write!(f, "{}", x),
+            Literal::String(s) => write!(f, "\"{}\"", s),
+            Literal::Character(c) => write!(f, "'{}'", c),
+            Literal::Boolean(b) => write!(f, "{}", b),
+            Literal::Null => write!(f, "null"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BindingKind {
+    Const,
+    Let,
+    Var,
+}
+
+impl BindingKind {
+    /// How errors refer to a binding of this kind
+    pub fn describe(&self) -> &'static str {
+        match self {
+            BindingKind::Const => "constant",
+            BindingKind::Let => "immutable binding",
+            BindingKind::Var => "variable",
+        }
+    }
+}
+
+/// An annotation attached to a function or struct, e.g. `@deprecated("use bar")`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub name: String,
+    pub arguments: Vec<String>,
+}
+
+impl Attribute {
+    /// The warning to report when `name`, declared with `attributes`, is used while marked `@deprecated`
+    pub fn deprecation(name: &str, attributes: &[Attribute]) -> Option<String> {
+        let attribute = attributes.iter().find(|a| a.name == "deprecated")?;
+        Some(match attribute.arguments.first() {
+            Some(note) => format!("'{}' is deprecated: {}", name, note),
+            None => format!("'{}' is deprecated", name),
+        })
+    }
+
+    /// Whether `attributes` contains an attribute called `name`
+    pub fn has(attributes: &[Attribute], name: &str) -> bool {
+        attributes.iter().any(|a| a.name == name)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -43,6 +125,69 @@ pub struct Item {
 pub struct Typing {
     pub name: String,
     pub array_dimensions: usize,
+    pub generics: Vec<Typing>,
+    pub parameter: bool,
+}
+
+impl Typing {
+    pub fn named(name: &str) -> Self {
+        Typing {
+            name: name.to_string(),
+            array_dimensions: 0,
+            generics: Vec::new(),
+            parameter: false,
+        }
+    }
+
+    /// An optional type like `int?`, written as an "optional" whose only generic is the type it wraps
+    pub fn optional(inner: Typing) -> Self {
+        let mut typing = Typing::named("optional");
+        typing.generics = vec![inner];
+        typing
+    }
+
+    pub fn is_optional(&self) -> bool {
+        self.name == "optional" && self.array_dimensions == 0 && !self.parameter
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.name == "null" && self.array_dimensions == 0
+    }
+
+    /// Whether a value of type `actual` can be stored where `self` is expected: the same type,
+    /// or null or the wrapped type where an optional is expected
+    pub fn accepts(&self, actual: &Typing) -> bool {
+        self == actual || (self.is_optional() && (actual.is_null() || self.generics[0] == *actual))
+    }
+
+    /// A tuple type like `(int, string)`, written as a "tuple" with its elements as generics
+    pub fn tuple(elements: Vec<Typing>) -> Self {
+        let mut typing = Typing::named("tuple");
+        typing.generics = elements;
+        typing
+    }
+}
+
+impl Display for Typing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let special = !self.parameter && matches!(self.name.as_str(), "tuple" | "optional");
+        if special && self.name == "tuple" {
+            let elements: Vec<String> = self.generics.iter().map(|g| g.to_string()).collect();
+            write!(f, "({})", elements.join(", "))?;
+        } else if special {
+            write!(f, "{}?", self.generics[0])?;
+        } else {
+            write!(f, "{}", self.name)?;
+        }
+        if !special && !self.generics.is_empty() {
+            let generics: Vec<String> = self.generics.iter().map(|g| g.to_string()).collect();
+            write!(f, "<{}>", generics.join(", "))?;
+        }
+        for _ in 0..self.array_dimensions {
+            write!(f, "[]")?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -5,6 +5,13 @@ pub mod parsing;
 mod ir;
 mod execution;
 mod builtin;
+pub mod modules;
+
+use std::sync::Arc;
+use modules::{FileResolver, ModuleResolver};
+
+pub use ir::{Attribute, OptimizationLevel};
+pub use execution::{Generator, Value};
 
 #[test]
 fn test_package() {
@@ -20,8 +27,8 @@ x = 2;
 x = run(x);
     ";
     let tokens = parsing::parse(input);
-    if tokens.is_err(){
-        println!("{}", tokens.unwrap_err());
+    if let Err(err) = tokens {
+        println!("{}", err);
         return;
     }
     let tokens = tokens.unwrap();
@@ -29,13 +36,94 @@ x = run(x);
     let mut program = execution::Program::new();
     program.include_std_library(builtin::get_std_lib(), builtin::get_std_functions());
     let result = program.run(&ir);
-    if result.is_err(){
-        println!("{}", result.unwrap_err());
+    if let Err(err) = result {
+        println!("{}", err);
         return;
     }
     println!("{}", program.get_variable(String::from("x")).unwrap().value.get_value());
 }
 
+#[cfg(test)]
+fn run_program(input: &str) -> Result<execution::Program, String> {
+    run_program_with(input, Arc::new(modules::MemoryResolver::new()))
+}
+
+#[cfg(test)]
+fn run_program_with(input: &str, resolver: Arc<dyn ModuleResolver>) -> Result<execution::Program, String> {
+    let (ir, _) = compile(input, OptimizationLevel::default())?;
+    let mut program = execution::Program::new();
+    program.include_std_library(builtin::get_std_lib(), builtin::get_std_functions());
+    program.set_resolver(resolver);
+    program.run(&ir)?;
+    Ok(program)
+}
+
+#[test]
+fn test_generics() {
+    let input = "
+struct Pair<A, B>{
+    A a;
+    B b;
+}
+fn first<T>(T[] xs) -> T{
+    return xs[0];
+}
+fn swap<A, B>(Pair<A, B> p) -> Pair<B, A>{
+    return Pair(p.b, p.a);
+}
+x = first([4, 5, 6]);
+s = first<string>([\"a\", \"b\"]);
+p = swap(Pair<int, string>(1, \"one\"));
+y = p.a;
+    ";
+    let program = run_program(input).unwrap();
+    assert_eq!(program.get_variable(String::from("x")).unwrap().value.get_value(), 4);
+    assert_eq!(program.get_variable(String::from("s")).unwrap().value.get_value(), "a");
+    assert_eq!(program.get_variable(String::from("y")).unwrap().value.get_value(), "one");
+
+    let mismatch = "
+struct Pair<A, B>{
+    A a;
+    B b;
+}
+p = Pair<int, string>(1, 2);
+    ";
+    assert!(run_program(mismatch).unwrap_err().contains("Conflicting types for type parameter 'B'"));
+
+    let conflicting = "
+fn same<T>(T a, T b) -> T{
+    return a;
+}
+x = same(1, \"one\");
+    ";
+    assert!(run_program(conflicting).unwrap_err().contains("Conflicting types for type parameter 'T'"));
+
+    let arity = "
+fn first<T>(T[] xs) -> T{
+    return xs[0];
+}
+x = first<int, int>([1]);
+    ";
+    assert!(run_program(arity).unwrap_err().contains("expects 1 type arguments, but 2 were provided"));
+
+    let returned = "
+fn wrong<T>(T x) -> T{
+    return 1;
+}
+    ";
+    assert!(run_program(returned).unwrap_err().contains("Expected a return value of type T, found int"));
+}
+
+/// Parses the given code and resolves its names, then type checks and optimizes it, returning the IR and the warnings found
+fn compile(code: &str, optimization: OptimizationLevel) -> Result<(ir::IR, Vec<String>), String> {
+    let tokens = parse(code).map_err(|err| err.to_string())?;
+    let mut ir = ir::IR::from_tokens(tokens);
+    ir.resolve(&builtin::get_std_names())?;
+    let warnings = ir.type_check()?;
+    ir::PassManager::for_level(optimization).run(&mut ir);
+    Ok((ir, warnings))
+}
+
 /// Checks if the given code is syntactically correct
 /// 
 /// # Arguments
@@ -50,27 +138,2099 @@ x = run(x);
 /// 
 /// ```
 /// use charlang::check;
-/// let valid_code = "int main() { return 0; }";
+/// let valid_code = "fn main() { return 0; }";
 /// assert_eq!(check(valid_code), None);
 /// 
-/// let invalid_code = "int main() { return 0";  // Missing closing brace
+/// let invalid_code = "fn main() { return 0";  // Missing closing brace
 /// assert!(check(invalid_code).is_some());
 /// ```
 /// 
 pub fn check(code: &str) -> Option<String> {
     let res = CharParser::parse(Rule::program, code);
     if res.is_ok(){return None;}
-    return Some(res.err().unwrap().to_string())
+    Some(res.err().unwrap().to_string())
+}
+
+/// How [`run_with_options`] loads modules and optimizes the code
+#[derive(Clone)]
+pub struct Options {
+    pub resolver: Arc<dyn ModuleResolver>,
+    pub optimization: OptimizationLevel,
 }
 
+impl Default for Options {
+    /// Loads modules from files relative to the current directory, with the default optimizations
+    fn default() -> Self {
+        Options {
+            resolver: Arc::new(FileResolver::new(".")),
+            optimization: OptimizationLevel::default(),
+        }
+    }
+}
+
+/// Runs the given code, loading imported modules from files relative to the current directory
 pub fn run(code: &str) -> Result<(), String> {
-    let res = parse(code);
-    if res.is_err(){return Err(res.unwrap_err().to_string());}
-    let tokens = res.unwrap();
-    let ir = ir::IR::from_tokens(tokens);
+    run_with_options(code, Options::default())
+}
+
+/// Runs the given code, loading imported modules through the given resolver
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use charlang::{run_with_resolver, modules::MemoryResolver};
+///
+/// let mut resolver = MemoryResolver::new();
+/// resolver.add_module("math", "fn square(int x) -> int { return x * x; }");
+/// assert!(run_with_resolver("import \"math\"; x = math::square(3);", Arc::new(resolver)).is_ok());
+/// ```
+pub fn run_with_resolver(code: &str, resolver: Arc<dyn ModuleResolver>) -> Result<(), String> {
+    run_with_options(code, Options { resolver, ..Options::default() })
+}
+
+/// Runs the given code with the given options
+///
+/// # Examples
+///
+/// ```
+/// use charlang::{run_with_options, Options, OptimizationLevel};
+///
+/// let options = Options { optimization: OptimizationLevel::None, ..Options::default() };
+/// assert!(run_with_options("x = 2 * 3;", options).is_ok());
+/// ```
+pub fn run_with_options(code: &str, options: Options) -> Result<(), String> {
+    let (ir, warnings) = compile(code, options.optimization)?;
+    for warning in warnings {
+        println!("warning: {}", warning);
+    }
     let mut program = execution::Program::new();
     program.include_std_library(builtin::get_std_lib(), builtin::get_std_functions());
+    program.set_resolver(options.resolver);
+    program.set_optimization(options.optimization);
     let result = program.run(&ir);
-    if result.is_err(){return Err(result.unwrap_err().to_string());}
-    return Ok(());
-}
\ No newline at end of file
+    for warning in program.warnings() {
+        println!("warning: {}", warning);
+    }
+    result?;
+    Ok(())
+}
+
+/// Runs the given code, then calls the generator function `name` with no arguments,
+/// returning the generator so the host can iterate over what it yields
+///
+/// # Examples
+///
+/// ```
+/// let squares = charlang::generate("fn* squares() { for (i in range(1, 4)) { yield i * i; } }", "squares").unwrap();
+/// let values: Vec<_> = squares.map(|value| value.unwrap().get_value()).collect();
+/// assert_eq!(values, vec![1, 4, 9]);
+/// ```
+pub fn generate(code: &str, name: &str) -> Result<Generator, String> {
+    let (ir, _) = compile(code, OptimizationLevel::default())?;
+    let mut program = execution::Program::new();
+    program.include_std_library(builtin::get_std_lib(), builtin::get_std_functions());
+    program.set_resolver(Arc::new(FileResolver::new(".")));
+    program.run(&ir)?;
+    match program.call_function(name, Vec::new())? {
+        Value::Generator(generator) => Ok(generator),
+        other => Err(format!("'{}' is not a generator function, it returned {}", name, other.get_name())),
+    }
+}
+
+/// Whether a [`Declaration`] is a function or a struct
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeclarationKind {
+    Function,
+    Struct,
+}
+
+/// A top-level function or struct, with the attributes written before it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration {
+    pub name: String,
+    pub kind: DeclarationKind,
+    pub attributes: Vec<Attribute>,
+}
+
+/// Lists the top-level functions and structs of the given code, so hosts can act on their attributes
+///
+/// # Examples
+///
+/// ```
+/// use charlang::declarations;
+///
+/// let code = "@export fn area(int w, int h) -> int { return w * h; }";
+/// let found = declarations(code).unwrap();
+/// assert_eq!(found[0].name, "area");
+/// assert_eq!(found[0].attributes[0].name, "export");
+/// ```
+pub fn declarations(code: &str) -> Result<Vec<Declaration>, String> {
+    let (ir, _) = compile(code, OptimizationLevel::None)?;
+    Ok(ir.actions.iter().filter_map(|action| match action {
+        ir::Action::Function(function) => Some(Declaration {
+            name: function.name.clone(),
+            kind: DeclarationKind::Function,
+            attributes: function.attributes.clone(),
+        }),
+        ir::Action::Struct(structure) => Some(Declaration {
+            name: structure.name.clone(),
+            kind: DeclarationKind::Struct,
+            attributes: structure.attributes.clone(),
+        }),
+        _ => None,
+    }).collect())
+}
+
+/// The outcome of a function marked `@test`, see [`run_tests`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestOutcome {
+    pub name: String,
+    pub result: Result<(), String>,
+}
+
+/// Runs the given code, then every function marked `@test`
+pub fn run_tests(code: &str, resolver: Arc<dyn ModuleResolver>) -> Result<Vec<TestOutcome>, String> {
+    let tests: Vec<String> = declarations(code)?.into_iter()
+        .filter(|declaration| declaration.kind == DeclarationKind::Function)
+        .filter(|declaration| declaration.attributes.iter().any(|attribute| attribute.name == "test"))
+        .map(|declaration| declaration.name)
+        .collect();
+    let (ir, _) = compile(code, OptimizationLevel::default())?;
+    let mut program = execution::Program::new();
+    program.include_std_library(builtin::get_std_lib(), builtin::get_std_functions());
+    program.set_resolver(resolver);
+    program.run(&ir)?;
+    Ok(tests.into_iter().map(|name| {
+        let result = program.clone().call_function(&name, Vec::new()).map(|_| ());
+        TestOutcome { name, result }
+    }).collect())
+}
+#[test]
+fn test_interfaces() {
+    let input = "
+struct Point{
+    int x;
+    int y;
+}
+struct Name{
+    string value;
+}
+interface Printable{
+    fn describe(self) -> string;
+}
+trait Comparable{
+    fn less(self, Self other) -> int;
+}
+impl Printable for Point{
+    fn describe(self) -> string{
+        return \"(\" + toString(self.x) + \", \" + toString(self.y) + \")\";
+    }
+}
+impl Printable for Name{
+    fn describe(self) -> string{
+        return self.value;
+    }
+}
+impl Comparable for Point{
+    fn less(self, Point other) -> int{
+        return self.x + self.y < other.x + other.y;
+    }
+}
+fn show(Printable p) -> string{
+    return \"<\" + describe(p) + \">\";
+}
+a = show(Point(1, 2));
+b = show(Name(\"bob\"));
+c = Point(1, 2) < Point(3, 4);
+    ";
+    let program = run_program(input).unwrap();
+    assert_eq!(program.get_variable(String::from("a")).unwrap().value.get_value(), "<(1, 2)>");
+    assert_eq!(program.get_variable(String::from("b")).unwrap().value.get_value(), "<bob>");
+    assert_eq!(program.get_variable(String::from("c")).unwrap().value.get_value(), 1);
+
+    let missing = "
+struct Point{
+    int x;
+}
+interface Printable{
+    fn describe(self) -> string;
+}
+impl Printable for Point{
+}
+    ";
+    assert!(run_program(missing).unwrap_err().contains("impl Printable for Point is missing method 'describe'"));
+
+    let not_implemented = "
+struct Point{
+    int x;
+}
+interface Printable{
+    fn describe(self) -> string;
+}
+fn show(Printable p) -> string{
+    return describe(p);
+}
+x = show(Point(1));
+    ";
+    assert!(run_program(not_implemented).unwrap_err().contains("Point does not implement Printable"));
+}
+
+#[test]
+fn test_enums() {
+    let input = "
+enum Shape {
+    Circle(float r),
+    Rect(float w, float h),
+    Empty,
+}
+a = Shape::Circle(1.5);
+b = Shape::Rect(2.0, 3.0);
+c = Shape::Empty;
+w = b.w;
+same = Shape::Circle(1.5) == a;
+different = a != Shape::Circle(2.5);
+empty = c == Shape::Empty;
+    ";
+    let program = run_program(input).unwrap();
+    assert_eq!(program.get_variable(String::from("a")).unwrap().value.get_name(), "Shape");
+    assert_eq!(program.get_variable(String::from("a")).unwrap().value.get_value(), serde_json::json!({"Circle": [1.5]}));
+    assert_eq!(program.get_variable(String::from("c")).unwrap().value.get_value(), "Empty");
+    assert_eq!(program.get_variable(String::from("w")).unwrap().value.get_value(), 2.0);
+    assert_eq!(program.get_variable(String::from("same")).unwrap().value.get_value(), true);
+    assert_eq!(program.get_variable(String::from("different")).unwrap().value.get_value(), true);
+    assert_eq!(program.get_variable(String::from("empty")).unwrap().value.get_value(), true);
+
+    let wrong_arity = "
+enum Shape {
+    Circle(float r),
+}
+a = Shape::Circle(1.0, 2.0);
+    ";
+    assert!(run_program(wrong_arity).unwrap_err().contains("constructor 'Shape::Circle' expects 1 arguments, but 2 were provided"));
+
+    let unknown_variant = "
+enum Shape {
+    Empty,
+}
+a = Shape::Square(1.0);
+    ";
+    assert!(run_program(unknown_variant).unwrap_err().contains("Enum 'Shape' has no variant 'Square'"));
+}
+
+#[test]
+fn test_match() {
+    let input = "
+enum Shape {
+    Circle(int r),
+    Rect(int w, int h),
+    Empty,
+}
+struct Point{
+    int x;
+    int y;
+}
+fn area(Shape s) -> int{
+    return match (s) {
+        Shape::Circle(r) => 3 * r * r,
+        Shape::Rect(w, h) if w == h => w * w,
+        Shape::Rect(w, h) => w * h,
+        Shape::Empty => 0,
+    };
+}
+fn describe(Point p) -> string{
+    return match (p) {
+        Point { x: 0, y: 0 } => \"origin\",
+        Point { x: 0, y } => \"on the y axis\",
+        Point { x, y: 0 } => \"on the x axis\",
+        _ => \"elsewhere\",
+    };
+}
+circle = area(Shape::Circle(2));
+square = area(Shape::Rect(3, 3));
+rect = area(Shape::Rect(2, 5));
+empty = area(Shape::Empty);
+origin = describe(Point(0, 0));
+axis = describe(Point(0, 4));
+other = describe(Point(1, 4));
+first = match ([7, 8, 9]) {
+    [] => 0,
+    [x, ..rest] => x + rest[1],
+};
+word = match (\"b\") {
+    \"a\" => 1,
+    \"b\" => 2,
+    _ => 3,
+};
+y = 5;
+shadowed = match (1) {
+    y => y,
+};
+    ";
+    let program = run_program(input).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("circle"), 12);
+    assert_eq!(get("square"), 9);
+    assert_eq!(get("rect"), 10);
+    assert_eq!(get("empty"), 0);
+    assert_eq!(get("origin"), "origin");
+    assert_eq!(get("axis"), "on the y axis");
+    assert_eq!(get("other"), "elsewhere");
+    assert_eq!(get("first"), 16);
+    assert_eq!(get("word"), 2);
+    assert_eq!(get("shadowed"), 1);
+    assert_eq!(get("y"), 5);
+
+    let missing = "
+enum Shape {
+    Circle(int r),
+    Rect(int w, int h),
+    Empty,
+}
+x = match (Shape::Empty) {
+    Shape::Circle(r) => r,
+    Shape::Rect(w, 1) => w,
+};
+    ";
+    let ir = ir::IR::from_tokens(parsing::parse(missing).unwrap());
+    assert_eq!(ir.type_check().unwrap(), vec!["Match on 'Shape' is not exhaustive, missing Shape::Rect, Shape::Empty".to_string()]);
+    assert!(run_program(missing).unwrap_err().contains("No match arm matches the value"));
+
+    let nested = "
+enum O {
+    Some(int n),
+    None,
+}
+enum W {
+    Wrap(O o),
+    Nothing,
+}
+fn unwrap(W w) -> int{
+    return match (w) {
+        W::Wrap(O::Some(n)) => n,
+        W::Wrap(O::None) => 0,
+        W::Nothing => -1,
+    };
+}
+fn partial(W w) -> int{
+    return match (w) {
+        W::Wrap(O::Some(n)) => n,
+        W::Nothing => -1,
+    };
+}
+    ";
+    let ir = ir::IR::from_tokens(parsing::parse(nested).unwrap());
+    assert_eq!(ir.type_check().unwrap(), vec!["Match on 'W' is not exhaustive, missing W::Wrap(O::None)".to_string()]);
+
+    let wrong_arity = "
+enum Shape {
+    Circle(int r),
+}
+x = match (Shape::Circle(1)) {
+    Shape::Circle(a, b) => a,
+};
+    ";
+    assert!(run_program(wrong_arity).unwrap_err().contains("Pattern 'Shape::Circle' expects 1 fields, but 2 were provided"));
+}
+
+#[test]
+fn test_methods() {
+    let input = "
+struct Point{
+    int x;
+    int y;
+}
+struct Line{
+    Point start;
+    Point end;
+}
+impl Point{
+    fn len(self) -> int{
+        return self.x * self.x + self.y * self.y;
+    }
+    fn shift(self, int dx, int dy){
+        self.x = self.x + dx;
+        self.y = self.y + dy;
+    }
+    fn scaled(self, int factor) -> Point{
+        return Point(self.x * factor, self.y * factor);
+    }
+}
+p = Point(3, 4);
+len = p.len();
+p.shift(1, 2);
+x = p.x;
+y = p.y;
+q = p.scaled(2);
+scaled = q.scaled(2).len();
+line = Line(Point(0, 0), Point(1, 1));
+line.end.shift(4, 4);
+end = line.end.x;
+    ";
+    let program = run_program(input).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("len"), 25);
+    assert_eq!(get("x"), 4);
+    assert_eq!(get("y"), 6);
+    assert_eq!(get("scaled"), 16 * 16 + 24 * 24);
+    assert_eq!(get("end"), 5);
+
+    let wrong_arity = "
+struct Point{
+    int x;
+}
+impl Point{
+    fn shift(self, int dx){
+        self.x = self.x + dx;
+    }
+}
+p = Point(1);
+p.shift();
+    ";
+    assert!(run_program(wrong_arity).unwrap_err().contains("call to method 'shift' expects 2 arguments, but 1 were provided"));
+}
+
+#[test]
+fn test_closures() {
+    let input = "
+fn make_adder(int n) -> fn{
+    fn add(int x) -> int{
+        return x + n;
+    }
+    return add;
+}
+fn apply(fn f, int x) -> int{
+    return f(x);
+}
+fn negate(int x) -> int{
+    return 0 - x;
+}
+fn countdown(int n) -> int{
+    fn step(int i) -> int{
+        if (i == 0) {
+            return 0;
+        }
+        return 1 + step(i - 1);
+    }
+    return step(n);
+}
+double = fn(int x) -> int { return x * 2; };
+a = double(3);
+add5 = make_adder(5);
+b = add5(10);
+c = apply(double, 7);
+d = apply(negate, 4);
+e = make_adder(1)(2);
+f = apply(fn(int x) -> int { return x * x; }, 9);
+g = countdown(4);
+sorted = sort([3, 1, 2], fn(int a, int b) -> int { return b - a; });
+    ";
+    let program = run_program(input).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("a"), 6);
+    assert_eq!(get("b"), 15);
+    assert_eq!(get("c"), 14);
+    assert_eq!(get("d"), -4);
+    assert_eq!(get("e"), 3);
+    assert_eq!(get("f"), 81);
+    assert_eq!(get("g"), 4);
+    assert_eq!(get("sorted"), serde_json::json!([3, 2, 1]));
+
+    let not_callable = "
+x = 1;
+y = [x][0](2);
+    ";
+    assert!(run_program(not_callable).unwrap_err().contains("int is not a function"));
+
+    let counter = "
+fn make() -> fn{
+    c = 0;
+    fn inc() -> int{
+        c = c + 1;
+        return c;
+    }
+    return inc;
+}
+inc = make();
+first = inc();
+second = inc();
+other = make()();
+    ";
+    let program = run_program(counter).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("first"), 1);
+    assert_eq!(get("second"), 2);
+    assert_eq!(get("other"), 1);
+
+    let anonymous_arity = "
+f = fn(int x) -> int { return x; };
+y = f(1, 2);
+    ";
+    assert!(run_program(anonymous_arity).unwrap_err().contains("anonymous function expects 1 arguments, but 2 were provided"));
+}
+
+#[test]
+fn test_modules() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug)]
+    struct CountingResolver {
+        inner: modules::MemoryResolver,
+        loads: AtomicUsize,
+    }
+    impl ModuleResolver for CountingResolver {
+        fn resolve(&self, path: &str, from: Option<&str>) -> Result<String, String> {
+            self.inner.resolve(path, from)
+        }
+        fn load(&self, key: &str) -> Result<String, String> {
+            self.loads.fetch_add(1, Ordering::SeqCst);
+            self.inner.load(key)
+        }
+    }
+
+    let mut inner = modules::MemoryResolver::new();
+    inner.add_module("math/vec.cl", "
+struct Vec2{
+    int x;
+    int y;
+}
+origin = Vec2(0, 0);
+fn square(int x) -> int{
+    return x * x;
+}
+fn dot(Vec2 a, Vec2 b) -> int{
+    return a.x * b.x + a.y * b.y;
+}
+fn length2(Vec2 a) -> int{
+    return dot(a, a);
+}
+    ");
+    inner.add_module("shapes", "
+import { Vec2, length2 } from \"math/vec.cl\";
+fn diagonal(int w, int h) -> int{
+    return length2(Vec2(w, h));
+}
+    ");
+    let resolver = Arc::new(CountingResolver { inner, loads: AtomicUsize::new(0) });
+    let input = "
+import \"math/vec.cl\";
+import { dot, Vec2 } from \"math/vec.cl\";
+import \"shapes\";
+a = vec::square(4);
+b = dot(Vec2(1, 2), Vec2(3, 4));
+c = vec::length2(vec::Vec2(3, 4));
+d = shapes::diagonal(1, 2);
+e = vec::origin.x;
+    ";
+    let program = run_program_with(input, resolver.clone()).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("a"), 16);
+    assert_eq!(get("b"), 11);
+    assert_eq!(get("c"), 25);
+    assert_eq!(get("d"), 5);
+    assert_eq!(get("e"), 0);
+    assert_eq!(resolver.loads.load(Ordering::SeqCst), 2);
+
+    let mut cyclic = modules::MemoryResolver::new();
+    cyclic.add_module("a", "import \"b\";");
+    cyclic.add_module("b", "import \"a\";");
+    let err = run_program_with("import \"a\";", Arc::new(cyclic)).unwrap_err();
+    assert!(err.contains("Import cycle: a -> b -> a"));
+
+    let mut warning = modules::MemoryResolver::new();
+    warning.add_module("partial", "enum E { A, B, } x = match (E::A) { E::A => 1, };");
+    let program = run_program_with("import \"partial\";", Arc::new(warning)).unwrap();
+    assert_eq!(program.warnings(), vec!["In module 'partial': Match on 'E' is not exhaustive, missing E::B".to_string()]);
+
+    let missing = run_program("import { nothing } from \"vec\";").unwrap_err();
+    assert!(missing.contains("Module 'vec' not found"));
+
+    let directory = std::env::temp_dir().join(format!("charlang_modules_{}", std::process::id()));
+    std::fs::create_dir_all(directory.join("lib")).unwrap();
+    std::fs::write(directory.join("lib/util.cl"), "import \"helpers\"; fn twice(int x) -> int { return helpers::add(x, x); }").unwrap();
+    std::fs::write(directory.join("lib/helpers.cl"), "fn add(int a, int b) -> int { return a + b; }").unwrap();
+    let program = run_program_with("import { twice } from \"lib/util\"; x = twice(21);", Arc::new(modules::FileResolver::new(&directory)));
+    std::fs::remove_dir_all(&directory).unwrap();
+    assert_eq!(program.unwrap().get_variable(String::from("x")).unwrap().value.get_value(), 42);
+}
+
+#[test]
+fn test_constants() {
+    let input = "
+const MAX = 10;
+const int LIMIT = MAX * 2 + 1;
+const GREETING = \"hi \" + \"there\";
+let scale = MAX * 3;
+var count = 0;
+count = count + MAX;
+struct Point{
+    int x;
+}
+impl Point{
+    fn shift(self){
+        self.x = self.x + 1;
+    }
+}
+fn bump(int MAX) -> int{
+    MAX = MAX + 1;
+    return MAX;
+}
+fn local() -> int{
+    let doubled = LIMIT * 2;
+    return doubled;
+}
+var p = Point(1);
+p.shift();
+bumped = bump(1);
+l = local();
+    ";
+    let program = run_program(input).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("LIMIT"), 21);
+    assert_eq!(get("GREETING"), "hi there");
+    assert_eq!(get("scale"), 30);
+    assert_eq!(get("count"), 10);
+    assert_eq!(get("p"), serde_json::json!([2]));
+    assert_eq!(get("bumped"), 2);
+    assert_eq!(get("l"), 42);
+
+    let reassigned = "
+const MAX = 10;
+MAX = 11;
+    ";
+    assert!(run_program(reassigned).unwrap_err().contains("Cannot assign to constant 'MAX'"));
+
+    let member = "
+struct Point{
+    int x;
+}
+let p = Point(1);
+p.x = 2;
+    ";
+    assert!(run_program(member).unwrap_err().contains("Cannot assign to immutable binding 'p'"));
+
+    let in_function = "
+let total = 0;
+fn add(int x){
+    total = total + x;
+}
+    ";
+    assert!(run_program(in_function).unwrap_err().contains("Cannot assign to immutable binding 'total'"));
+
+    let not_constant = "
+fn five() -> int{
+    return 5;
+}
+const FIVE = five();
+    ";
+    assert!(run_program(not_constant).unwrap_err().contains("The value of constant 'FIVE' must be a constant expression"));
+
+    // A constant declared in a block can be declared again once the block is left, as by a loop or a recursive call
+    let looped = "
+fn sum() -> int{
+    total = 0;
+    i = 0;
+    while (i < 3) {
+        const STEP = 2;
+        total = total + STEP;
+        i = i + 1;
+    }
+    for (x in [1, 2]) {
+        const BASE = 10;
+        total = total + BASE * x;
+    }
+    return total;
+}
+total = sum();
+fn f(int n) -> int{
+    const K = 5;
+    if (n == 0) {
+        return K;
+    }
+    return f(n - 1);
+}
+k = f(3);
+    ";
+    let program = run_program(looped).unwrap();
+    assert_eq!(program.get_variable(String::from("total")).unwrap().value.get_value(), 36);
+    assert_eq!(program.get_variable(String::from("k")).unwrap().value.get_value(), 5);
+
+    // constants and `let` bindings are scoped to the block declaring them
+    let scoped = "
+fn pick(int n) -> int{
+    if (n > 0) {
+        const K = 1;
+        let step = K;
+    } else {
+        const K = 2;
+    }
+    K = 3;
+    step = K + 1;
+    return step;
+}
+picked = pick(1);
+    ";
+    let program = run_program(scoped).unwrap();
+    assert_eq!(program.get_variable(String::from("picked")).unwrap().value.get_value(), 4);
+    assert!(run_program("fn f() { if (1 > 0) { const K = 1; K = 2; } }").unwrap_err().contains("Cannot assign to constant 'K'"));
+
+    let redefined = "
+const MAX = 10;
+const MAX = 11;
+    ";
+    assert!(run_program(redefined).unwrap_err().contains("Constant 'MAX' is already defined"));
+
+    let mismatch = "const int NAME = \"x\";";
+    assert!(run_program(mismatch).unwrap_err().contains("Type mismatch in constant 'NAME': expected int, found string"));
+
+    // The runtime guard catches what the checker can't see, like methods changing an immutable receiver
+    let method = "
+struct Point{
+    int x;
+}
+impl Point{
+    fn shift(self){
+        self.x = self.x + 1;
+    }
+}
+let p = Point(1);
+p.shift();
+    ";
+    assert!(run_program(method).unwrap_err().contains("Cannot assign to immutable binding 'p'"));
+}
+
+#[test]
+fn test_attributes() {
+    let input = "
+@deprecated(\"use area\")
+fn size(int w, int h) -> int{
+    return w * h;
+}
+@deprecated
+struct Old{
+    int x;
+}
+@memoize
+fn fib(int n) -> int{
+    if (n < 2) {
+        return n;
+    }
+    return fib(n - 1) + fib(n - 2);
+}
+a = size(2, 3);
+b = size(4, 5);
+o = Old(1);
+f = fib(25);
+    ";
+    let program = run_program(input).unwrap();
+    assert_eq!(program.get_variable(String::from("b")).unwrap().value.get_value(), 20);
+    assert_eq!(program.get_variable(String::from("f")).unwrap().value.get_value(), 75025);
+    assert_eq!(program.warnings(), vec![
+        "'size' is deprecated: use area".to_string(),
+        "'Old' is deprecated".to_string(),
+    ]);
+
+    let found = declarations("
+@test @inline
+fn check_sum(){
+    return 0;
+}
+@export
+struct Point{
+    int x;
+}
+x = 1;
+    ").unwrap();
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].kind, DeclarationKind::Function);
+    let names: Vec<&str> = found[0].attributes.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(names, vec!["test", "inline"]);
+    assert_eq!(found[1].name, "Point");
+
+    let tests = run_tests("
+@test
+fn passes(){
+    return 1;
+}
+@test
+fn fails(){
+    return 1 / helper();
+}
+fn helper(){
+    return 0;
+}
+    ", Arc::new(modules::MemoryResolver::new())).unwrap();
+    assert_eq!(tests.len(), 2);
+    assert!(tests[0].result.is_ok());
+    assert!(tests[1].result.as_ref().unwrap_err().contains("Division by zero"));
+    let undefined = run_tests("@test fn fails(){ return missing(1); }", Arc::new(modules::MemoryResolver::new()));
+    assert!(undefined.unwrap_err().contains("Function 'missing' not found"));
+
+    assert!(run_program("@fast fn f(){ return 1; }").unwrap_err().contains("Unknown attribute '@fast'"));
+    assert!(run_program("@memoize(1) fn f(){ return 1; }").unwrap_err().contains("'@memoize' takes no arguments"));
+    assert!(run_program("@test struct S{ int x; }").unwrap_err().contains("'@test' can only be used on functions"));
+}
+
+#[test]
+fn test_for_in() {
+    let input = "
+struct Bag{
+    int[] items;
+}
+impl Bag{
+    fn len(self) -> int{
+        return 2;
+    }
+    fn get(self, int i) -> int{
+        return self.items[i] * 10;
+    }
+}
+fn sum(int[] xs) -> int{
+    total = 0;
+    for (x in xs) {
+        total = total + x;
+    }
+    return total;
+}
+fn first_vowel(string word) -> char{
+    for (c in word) {
+        if (c == 'a') {
+            return c;
+        }
+    }
+    return 'z';
+}
+a = sum([1, 2, 3]);
+bs = 0;
+for (char c in \"abcb\") {
+    if (c == 'b') {
+        bs = bs + 1;
+    }
+}
+up = 0;
+for (i in 0..5) {
+    up = up + i;
+}
+through = 0;
+for (i in 1..=4) {
+    through = through * 10 + i;
+}
+down = \"\";
+for (i in 10..0 step -3) {
+    down = down + toString(i) + \" \";
+}
+bagged = 0;
+for (b in Bag([4, 5, 6])) {
+    bagged = bagged + b;
+}
+i = 99;
+for (i in 0..3) {
+}
+v = first_vowel(\"bank\");
+    ";
+    let program = run_program(input).unwrap();
+    assert_eq!(program.get_variable(String::from("a")).unwrap().value.get_value(), 6);
+    assert_eq!(program.get_variable(String::from("bs")).unwrap().value.get_value(), 2);
+    assert_eq!(program.get_variable(String::from("up")).unwrap().value.get_value(), 10);
+    assert_eq!(program.get_variable(String::from("through")).unwrap().value.get_value(), 1234);
+    assert_eq!(program.get_variable(String::from("down")).unwrap().value.get_value(), "10 7 4 1 ");
+    assert_eq!(program.get_variable(String::from("bagged")).unwrap().value.get_value(), 90);
+    assert_eq!(program.get_variable(String::from("i")).unwrap().value.get_value(), 99);
+    assert_eq!(program.get_variable(String::from("v")).unwrap().value.get_value(), "a");
+
+    assert!(run_program("for (x in 5) { }").unwrap_err().contains("Cannot iterate over a value of type int"));
+    assert!(run_program("for (string s in [1, 2]) { }").unwrap_err().contains("Type mismatch in for-in variable 's': expected string, found int"));
+    assert!(run_program("for (i in 0..10 step 0) { }").unwrap_err().contains("Range step cannot be zero"));
+    assert!(run_program("struct P{ int x; } for (p in P(1)) { }").unwrap_err().contains("it needs `len` and `get` methods"));
+}
+
+#[test]
+fn test_labeled_loops() {
+    let input = "
+fn count(int n) -> int{
+    i = 0;
+    total = 0;
+    while (i < n) {
+        i = i + 1;
+        if (i == 2) {
+            continue;
+        }
+        if (i == 5) {
+            break;
+        }
+        total = total + i;
+    }
+    return total;
+}
+fn first_pair(int target) -> int{
+    found = 0;
+    outer: for (a = 1; a < 10; a = a + 1) {
+        for (b = 1; b < 10; b = b + 1) {
+            if (a * b == target) {
+                found = a * 10 + b;
+                break outer;
+            }
+        }
+    }
+    return found;
+}
+fn skip_rows() -> int{
+    cells = 0;
+    rows: for (r in 0..4) {
+        c = 0;
+        do {
+            c = c + 1;
+            if (r == 1) {
+                continue rows;
+            }
+            cells = cells + 1;
+        } while (c < 3);
+    }
+    return cells;
+}
+fn find(int[] xs, int x) -> int{
+    for (i in 0..5) {
+        while (1) {
+            if (xs[i] == x) {
+                return i;
+            }
+            break;
+        }
+    }
+    return -1;
+}
+a = count(10);
+b = first_pair(12);
+c = skip_rows();
+d = find([3, 4, 5, 6, 7], 6);
+    ";
+    let program = run_program(input).unwrap();
+    assert_eq!(program.get_variable(String::from("a")).unwrap().value.get_value(), 8);
+    assert_eq!(program.get_variable(String::from("b")).unwrap().value.get_value(), 26);
+    assert_eq!(program.get_variable(String::from("c")).unwrap().value.get_value(), 9);
+    assert_eq!(program.get_variable(String::from("d")).unwrap().value.get_value(), 3);
+
+    assert!(run_program("fn f(){ break; }").unwrap_err().contains("'break' outside of a loop"));
+    assert!(run_program("fn f(){ while (1) { continue inner; } }").unwrap_err().contains("Unknown loop label 'inner' in 'continue inner'"));
+    assert!(run_program("fn f(){ l: while (1) { g = fn() { break l; }; } }").unwrap_err().contains("Unknown loop label 'l'"));
+    assert!(run_program("fn f(){ l: while (1) { l: while (1) { } } }").unwrap_err().contains("Loop label 'l' is already used"));
+}
+
+#[test]
+fn test_switch() {
+    let input = "
+const int TWO = 2;
+fn describe(int n) -> string{
+    s = \"\";
+    switch (n) {
+        default:
+            s = s + \"many \";
+        case 1:
+            s = s + \"one \";
+            break;
+        case TWO:
+        case TWO + 1:
+            s = s + \"few \";
+        case 10:
+            s = s + \"ten \";
+    }
+    return s;
+}
+fn evens(int n) -> int{
+    total = 0;
+    for (i in 0..n) {
+        switch (i % 2) {
+            case 1:
+                continue;
+            case 0:
+                total = total + i;
+                break;
+        }
+    }
+    return total;
+}
+a = describe(1);
+b = describe(3);
+c = describe(7);
+d = describe(10);
+fn once() -> int{
+    n = 0;
+    hits = 0;
+    switch (n = n + 1) {
+        case 3:
+            hits = hits + 100;
+        case 2:
+            hits = hits + 10;
+            break;
+        case 1:
+            hits = hits + 1;
+    }
+    return n * 1000 + hits;
+}
+e = evens(7);
+f = once();
+    ";
+    let program = run_program(input).unwrap();
+    assert_eq!(program.get_variable(String::from("a")).unwrap().value.get_value(), "one ");
+    assert_eq!(program.get_variable(String::from("b")).unwrap().value.get_value(), "few ten ");
+    assert_eq!(program.get_variable(String::from("c")).unwrap().value.get_value(), "many one ");
+    assert_eq!(program.get_variable(String::from("d")).unwrap().value.get_value(), "ten ");
+    assert_eq!(program.get_variable(String::from("e")).unwrap().value.get_value(), 12);
+    assert_eq!(program.get_variable(String::from("f")).unwrap().value.get_value(), 1001);
+
+    assert!(run_program("fn f(int x){ switch (x) { case 1: break; case 2 - 1: break; } }").unwrap_err().contains("Duplicate case label 1 in switch"));
+    assert!(run_program("fn f(int x, int y){ switch (x) { case y: break; } }").unwrap_err().contains("Case labels must be constant expressions"));
+    assert!(run_program("fn f(int x){ switch (x) { default: break; default: break; } }").unwrap_err().contains("only have one 'default'"));
+    assert!(run_program("fn f(int x){ switch (x) { case \"a\": break; } }").unwrap_err().contains("Case label \"a\" of type string doesn't match the switch value of type int"));
+    assert!(run_program("fn f(int x){ switch (x) { case 1: continue; } }").unwrap_err().contains("'continue' outside of a loop"));
+}
+
+#[test]
+fn test_bitwise() {
+    let input = "
+const READ = 1 << 0;
+const WRITE = 1 << 1;
+const EXEC = 1 << 2;
+flags = READ;
+flags |= WRITE | EXEC;
+flags &= ~WRITE;
+can_write = flags & WRITE;
+can_exec = flags & EXEC;
+toggled = flags ^ READ;
+a = 1 | 2 & 3;
+b = 1 << 2 + 1;
+c = 6 ^ 3 & 5;
+d = -16 >> 2;
+e = 1 | 2 == 2;
+shift = 3;
+g = 1 << shift;
+g <<= 1;
+g >>= 2;
+g ^= 7;
+    ";
+    let program = run_program(input).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("flags"), 5);
+    assert_eq!(get("can_write"), 0);
+    assert_eq!(get("can_exec"), 4);
+    assert_eq!(get("toggled"), 4);
+    assert_eq!(get("a"), 3);
+    assert_eq!(get("b"), 8);
+    assert_eq!(get("c"), 7);
+    assert_eq!(get("d"), -4);
+    assert_eq!(get("g"), 3);
+
+    assert!(run_program("fn f(int n) -> int{ return 1 << n; } x = f(64);").unwrap_err().contains("Shift amount 64 is out of range, it must be between 0 and 63"));
+    assert!(run_program("fn f(int n) -> int{ return 1 >> n; } x = f(-1);").unwrap_err().contains("Shift amount -1 is out of range"));
+    assert!(run_program("x = 1 << 70;").unwrap_err().contains("Shift amount 70 is out of range"));
+    assert!(run_program("x = 1.5 & 1;").unwrap_err().contains("Bitwise operators need int operands, found float"));
+}
+
+#[test]
+fn test_fixed_size_arrays() {
+    let input = "
+const MAX = 3;
+typedef int[4] Vec4;
+typedef Vec4 Quad;
+typedef float[MAX] Triple;
+struct Point{
+    int x;
+    int y;
+}
+int a[5];
+int grid[2][MAX];
+Quad q;
+Triple t;
+string names[2];
+int primes[5] = {2, 3, 5};
+int count;
+int b[sizeof(Vec4) + 1];
+fn squares() -> int[]{
+    int s[4];
+    for (i in 0..sizeof(s)) {
+        s[i] = i * i;
+    }
+    return s;
+}
+sq = squares();
+sizes = [sizeof(Vec4), sizeof(grid), sizeof(grid[0]), sizeof(Point), sizeof(int), sizeof(\"abc\"), sizeof(primes)];
+    ";
+    let program = run_program(input).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("a"), serde_json::json!([0, 0, 0, 0, 0]));
+    assert_eq!(get("grid"), serde_json::json!([[0, 0, 0], [0, 0, 0]]));
+    assert_eq!(get("q"), serde_json::json!([0, 0, 0, 0]));
+    assert_eq!(get("t"), serde_json::json!([0.0, 0.0, 0.0]));
+    assert_eq!(get("names"), serde_json::json!(["", ""]));
+    assert_eq!(get("primes"), serde_json::json!([2, 3, 5, 0, 0]));
+    assert_eq!(get("count"), 0);
+    assert_eq!(get("b"), serde_json::json!([0, 0, 0, 0, 0]));
+    assert_eq!(get("sq"), serde_json::json!([0, 1, 4, 9]));
+    assert_eq!(get("sizes"), serde_json::json!([4, 2, 3, 2, 1, 3, 5]));
+
+    // Without a type, an indexed name is an element being assigned, not an array being declared
+    let indexed = run_program("a = [1, 2, 3]; a[0] = 9; m = [[1, 2], [3, 4]]; m[1][0] = 5;").unwrap();
+    assert_eq!(indexed.get_variable(String::from("a")).unwrap().value.get_value(), serde_json::json!([9, 2, 3]));
+    assert_eq!(indexed.get_variable(String::from("m")).unwrap().value.get_value(), serde_json::json!([[1, 2], [5, 4]]));
+
+    assert!(run_program("int n = \"five\";").unwrap_err().contains("Type mismatch in variable 'n': expected int, found string"));
+    assert!(run_program("int a[2] = {1, 2, 3};").unwrap_err().contains("Too many initializers for an array of size 2: found 3"));
+    assert!(run_program("n = 3; int a[n];").unwrap_err().contains("Array size must be a constant expression"));
+    assert!(run_program("int a[1.5];").unwrap_err().contains("Array size must be an int, found float"));
+    assert!(run_program("x = sizeof(int[]);").unwrap_err().contains("sizeof needs a fixed-size array type, found int[]"));
+}
+
+#[test]
+fn test_tuples() {
+    let input = "
+struct Point{
+    int x;
+    int y;
+}
+fn divmod(int a, int b) -> (int, int){
+    return (a / b, a % b);
+}
+fn swap((int, string) pair) -> (string, int){
+    return (pair.1, pair.0);
+}
+(q, r) = divmod(7, 2);
+pair = swap((1, \"one\"));
+first = pair.0;
+p = Point(3, 4);
+Point{x, y} = p;
+Point{x: px, y: _} = Point(9, 8);
+[head, ..tail] = [1, 2, 3, 4];
+[.., last] = [5, 6, 7];
+((a, b), c) = ((1, 2), 3);
+(int, int) origin = (0, 0);
+kind = match (divmod(9, 3)) {
+    (n, 0) => n,
+    _ => -1,
+};
+    ";
+    let program = run_program(input).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("q"), 3);
+    assert_eq!(get("r"), 1);
+    assert_eq!(get("pair"), serde_json::json!(["one", 1]));
+    assert_eq!(get("first"), "one");
+    assert_eq!((get("x"), get("y"), get("px")), (serde_json::json!(3), serde_json::json!(4), serde_json::json!(9)));
+    assert_eq!(get("head"), 1);
+    assert_eq!(get("tail"), serde_json::json!([2, 3, 4]));
+    assert_eq!(get("last"), 7);
+    assert_eq!((get("a"), get("b"), get("c")), (serde_json::json!(1), serde_json::json!(2), serde_json::json!(3)));
+    assert_eq!(get("origin"), serde_json::json!([0, 0]));
+    assert_eq!(get("kind"), 3);
+
+    assert!(run_program("fn f() -> (int, int){ return (1, 2); } (a, b, c) = f();").unwrap_err().contains("Tuple pattern with 3 elements cannot match a value of type (int, int)"));
+    assert!(run_program("fn f() -> (int, int){ return (1, \"2\"); }").unwrap_err().contains("(int, int)"));
+    assert!(run_program("const a = 1; (a, b) = (2, 3);").unwrap_err().contains("Cannot assign to constant 'a'"));
+    assert!(run_program("t = (1, 2); x = t.2;").unwrap_err().contains("Tuple (int, int) has no element 2"));
+    assert!(run_program("[a, b] = [1, 2, 3];").unwrap_err().contains("Cannot destructure"));
+}
+
+#[test]
+fn test_optionals() {
+    let input = "
+struct Node{
+    int value;
+    Node? next;
+}
+fn find(int[] xs, int wanted) -> int?{
+    for (x in xs) {
+        if (x == wanted) {
+            return x;
+        }
+    }
+    return null;
+}
+fn second(Node n) -> int?{
+    return n.next?.value;
+}
+fn or_zero(int? x) -> int{
+    if (x != null) {
+        return x + 1;
+    }
+    return 0;
+}
+last = Node(2, null);
+head = Node(1, last);
+a = find([1, 2, 3], 2) ?? -1;
+b = find([1, 2, 3], 7) ?? -1;
+c = second(head) ?? 0;
+d = second(last) ?? 0;
+int? e = null;
+f = e ?? null ?? 5;
+g = or_zero(4);
+h = or_zero(null);
+k = match (find([4], 4)) {
+    null => 0,
+    n => n,
+};
+    ";
+    let program = run_program(input).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("a"), 2);
+    assert_eq!(get("b"), -1);
+    assert_eq!(get("c"), 2);
+    assert_eq!(get("d"), 0);
+    assert_eq!(get("e"), serde_json::Value::Null);
+    assert_eq!(get("f"), 5);
+    assert_eq!(get("g"), 5);
+    assert_eq!(get("h"), 0);
+    assert_eq!(get("k"), 4);
+
+    assert!(run_program("int x = null;").unwrap_err().contains("Null cannot be assigned to variable 'x' of non-optional type int"));
+    // Reassigning keeps the declared type, of variables, parameters and struct fields
+    assert!(run_program("int x = 1; x = null;").unwrap_err().contains("Null cannot be assigned to variable 'x' of non-optional type int"));
+    assert!(run_program("var int x = 1; x = null;").unwrap_err().contains("Null cannot be assigned to variable 'x' of non-optional type int"));
+    assert!(run_program("fn f(int n) { n = \"s\"; }").unwrap_err().contains("Type mismatch in variable 'n': expected int, found string"));
+    assert!(run_program("struct P{ int x; } p = P(1); p.x = null;").unwrap_err().contains("Null cannot be assigned to member 'p.x' of non-optional type int"));
+    let reassigned = run_program("int? x = 1; x = null; y = 1; y = \"one\";").unwrap();
+    assert_eq!(reassigned.get_variable(String::from("x")).unwrap().value.get_value(), serde_json::Value::Null);
+    assert_eq!(reassigned.get_variable(String::from("y")).unwrap().value.get_value(), "one");
+    assert!(run_program("fn f() -> int? { return null; } let int y = f();").unwrap_err().contains("Type mismatch in immutable binding 'y': expected int, found int?"));
+    assert!(run_program("fn f(int? x) -> int { return x + 1; }").unwrap_err().contains("'x' of type int? may be null"));
+    assert!(run_program("struct P{ int x; } fn f(P? p) -> int { return p.x; }").unwrap_err().contains("Cannot access 'x' because 'p' may be null, use '?.' instead"));
+    assert!(run_program("struct P{ int x; } fn f(P p) -> int { return p.x; } y = f(null);").unwrap_err().contains("expected P, found null"));
+    assert!(run_program("struct P{ P q; int x; } fn none() { return null; } fn f(P p) -> int { return p.q.x; } z = f(P(none(), 1));").unwrap_err().contains("Null dereference: 'p.q' is null"));
+    assert!(run_program("fn f(int x) -> int { return x * 2; } fn g() { return null; } y = f(g());").unwrap_err().contains("Null dereference: 'x' is null"));
+}
+
+#[test]
+fn test_results() {
+    let input = "
+enum Maybe<T>{
+    Some(T value),
+    Nothing,
+}
+fn half(int x) -> Result<int, string>{
+    if (x % 2 == 1) {
+        return Result::Err(\"odd\");
+    }
+    return Result::Ok(x / 2);
+}
+fn quarter(int x) -> Result<int, string>{
+    h = half(x)?;
+    return half(h);
+}
+fn sum(string a, string b) -> Result<int, string>{
+    return Result::Ok(parseInt(a)? + parseInt(b)?);
+}
+fn unwrap(Result<int, string> r) -> int{
+    return match (r) {
+        Result::Ok(v) => v,
+        Result::Err(e) => -1,
+    };
+}
+a = unwrap(quarter(12));
+b = unwrap(quarter(6));
+c = unwrap(quarter(5));
+d = unwrap(sum(\"4\", \" 38\"));
+e = unwrap(sum(\"4\", \"x\"));
+f = match (parseInt(\"x\")) {
+    Result::Ok(v) => \"\",
+    Result::Err(message) => message,
+};
+g = match (Maybe::Some(3)) {
+    Maybe::Some(v) => v,
+    Maybe::Nothing => 0,
+};
+    ";
+    let program = run_program(input).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("a"), 3);
+    assert_eq!(get("b"), -1);
+    assert_eq!(get("c"), -1);
+    assert_eq!(get("d"), 42);
+    assert_eq!(get("e"), -1);
+    assert_eq!(get("f"), "'x' is not an integer");
+    assert_eq!(get("g"), 3);
+
+    assert!(run_program("fn f(int x) -> Result<int, string> { return Result::Ok(x?); }").unwrap_err().contains("'?' needs a Result, found int"));
+    assert!(run_program("fn f(Result<int, string> r) -> int { return r?; }").unwrap_err().contains("'?' can only be used in a function that returns a Result, not int"));
+    assert!(run_program("fn f(Result<int, int> r) -> Result<int, string> { x = r?; return Result::Ok(x); }").unwrap_err().contains("'?' cannot return an error of type int from a function returning Result<int, string>"));
+    assert!(run_program("x = parseInt(\"1\")?;").unwrap_err().contains("'?' can only be used inside a function"));
+    assert!(run_program("fn f(Result<int, string> r) -> string { return match (r) { Result::Ok(v) => v, Result::Err(e) => 0 }; }").unwrap_err().contains("Expected a return value of type string, found int"));
+    let program = run_program("fn f() { x = parseInt(\"a\")?; return x; } y = f();").unwrap();
+    assert_eq!(program.get_variable(String::from("y")).unwrap().value.get_value(), serde_json::json!({ "Err": ["'a' is not an integer"] }));
+}
+
+#[test]
+fn test_exceptions() {
+    let input = "
+fn divide(int a, int b) -> int{
+    return a / b;
+}
+fn checked(int x) -> int{
+    if (x < 0) {
+        throw Error(\"negative\", \"RangeError\", \"checked\");
+    }
+    return x;
+}
+fn attempt(int x) -> string{
+    try {
+        checked(x);
+        return \"ok\";
+    } catch (e) {
+        return e.kind;
+    } finally {
+        log = log + 1;
+    }
+}
+log = 0;
+fn cleanup() -> int{
+    steps = 0;
+    try {
+        try {
+            throw \"inner\";
+        } finally {
+            steps = steps + 1;
+        }
+    } catch (e) {
+        steps = steps + 10;
+    }
+    return steps;
+}
+message = \"\";
+kind = \"\";
+location = \"\";
+try {
+    divide(1, 0);
+} catch (e) {
+    message = e.message;
+    kind = e.kind;
+    location = e.location;
+}
+thrown = 0;
+try {
+    throw 42;
+} catch (e) {
+    thrown = e;
+}
+a = attempt(1);
+b = attempt(-1);
+c = cleanup();
+    ";
+    let program = run_program(input).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("message"), "Division by zero");
+    assert_eq!(get("kind"), "ArithmeticError");
+    assert_eq!(get("location"), "function 'divide' called at line 39");
+    assert_eq!(get("thrown"), 42);
+    assert_eq!(get("a"), "ok");
+    assert_eq!(get("b"), "RangeError");
+    assert_eq!(get("c"), 11);
+
+    assert!(run_program("throw \"boom\";").unwrap_err().contains("Uncaught exception: boom"));
+    assert!(run_program("fn f() { try { throw 1; } catch (e) { throw Error(\"again\", \"Custom\", \"f\"); } } f();").unwrap_err().contains("Uncaught exception: Custom: again"));
+    assert!(run_program("x = 0; try { x = 1; } finally { x = 2; } try { y = [1][3]; } catch (e) { x = e.kind; }").is_ok());
+    // the kind is set where the error is raised, not guessed from its message
+    let kinds = "
+fn kind_of(int shift, int index) -> string{
+    try {
+        x = 1 << shift;
+        y = [1, 2][index];
+    } catch (e) {
+        return e.kind;
+    }
+    return \"none\";
+}
+shifted = kind_of(70, 0);
+negative = kind_of(1, 0 - 1);
+    ";
+    let program = run_program(kinds).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("shifted"), "ArithmeticError");
+    assert_eq!(get("negative"), "IndexError");
+
+    let program = run_program("fn f() -> Result<int, string> { try { x = parseInt(\"a\")?; } catch (e) { return Result::Ok(0); } return Result::Ok(1); } r = f();").unwrap();
+    assert_eq!(program.get_variable(String::from("r")).unwrap().value.get_value(), serde_json::json!({ "Err": ["'a' is not an integer"] }));
+}
+
+#[test]
+fn test_generators() {
+    let input = "
+fn* naturals() -> int{
+    n = 0;
+    while (n >= 0) {
+        yield n;
+        n = n + 1;
+    }
+}
+fn* take(int count) -> int{
+    taken = 0;
+    for (n in naturals()) {
+        if (taken == count) {
+            return;
+        }
+        yield n * n;
+        taken = taken + 1;
+    }
+}
+fn* failing(){
+    yield 1;
+    throw \"stopped\";
+}
+total = 0;
+for (square in take(4)) {
+    total = total + square;
+}
+evens = fn*(int limit) {
+    for (i in range(0, limit)) {
+        if (i % 2 == 0) {
+            yield i;
+        }
+    }
+};
+count = 0;
+for (even in evens(10)) {
+    count = count + 1;
+}
+seen = 0;
+caught = \"\";
+try {
+    for (x in failing()) {
+        seen = seen + x;
+    }
+} catch (e) {
+    caught = e;
+}
+    ";
+    let program = run_program(input).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("total"), 14);
+    assert_eq!(get("count"), 5);
+    assert_eq!(get("seen"), 1);
+    assert_eq!(get("caught"), "stopped");
+
+    let naturals = generate("fn* naturals() { n = 0; while (n >= 0) { yield n; n = n + 1; } }", "naturals").unwrap();
+    let first: Vec<serde_json::Value> = naturals.take(3).map(|value| value.unwrap().get_value()).collect();
+    assert_eq!(first, vec![0, 1, 2]);
+    let mut failing = generate("fn* failing() { yield 1; x = 1 / 0; }", "failing").unwrap();
+    assert_eq!(failing.next().unwrap().unwrap().get_value(), 1);
+    assert_eq!(failing.next().unwrap().unwrap_err(), "Division by zero");
+    assert!(failing.next().is_none());
+
+    // dropping a generator stops its body at the `yield` it waits at, even inside a `try`
+    let abandoned = "
+@memoize
+fn caught(int n) {
+    return n;
+}
+fn* ticks() {
+    n = 0;
+    while (n >= 0) {
+        try {
+            yield n;
+        } catch (e) {
+            caught(n);
+        } finally {
+            caught(n + 100);
+        }
+        n = n + 1;
+    }
+}
+    ";
+    let (ir, _) = compile(abandoned, OptimizationLevel::default()).unwrap();
+    let mut program = execution::Program::new();
+    program.include_std_library(builtin::get_std_lib(), builtin::get_std_functions());
+    program.run(&ir).unwrap();
+    let Value::Generator(mut ticks) = program.call_function("ticks", Vec::new()).unwrap() else { panic!("expected a generator") };
+    assert_eq!(ticks.next().unwrap().unwrap().get_value(), 0);
+    drop(ticks);
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    assert!(program.memoized("caught(int:0)").is_none() && program.memoized("caught(int:100)").is_none());
+
+    assert!(run_program("fn f() { yield 1; }").unwrap_err().contains("'yield' can only be used inside a generator function, declared with 'fn*'"));
+    assert!(run_program("fn* f() -> int { yield \"a\"; }").unwrap_err().contains("Expected a yielded value of type int, found string"));
+    assert!(run_program("fn* f() -> int { yield 1; } for (s in f()) { string t = s; }").unwrap_err().contains("expected string, found int"));
+    assert!(generate("fn f() { return 1; }", "f").unwrap_err().contains("'f' is not a generator function"));
+}
+
+#[test]
+fn test_default_and_named_arguments() {
+    let input = "
+fn area(int width, int height = 2, int depth = 1) -> int {
+    return width * height * depth;
+}
+fn scaled(int x, int factor = x) -> int {
+    return x * factor;
+}
+fn sum(string label, int ...numbers) -> int {
+    total = 0;
+    for (n in numbers) {
+        total = total + n;
+    }
+    return total;
+}
+fn count(...items) -> int {
+    return sizeof(items);
+}
+struct Point {
+    int x;
+    int y;
+}
+a = area(3);
+b = area(3, 4);
+c = area(3, depth: 5);
+d = area(height: 1, width: 7);
+e = scaled(4);
+f = sum(\"none\");
+g = sum(\"some\", 1, 2, 3);
+h = count(1, \"two\", 3.0);
+p = Point(y: 2, x: 1);
+px = p.x;
+py = p.y;
+    ";
+    let program = run_program(input).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("a"), 6);
+    assert_eq!(get("b"), 12);
+    assert_eq!(get("c"), 30);
+    assert_eq!(get("d"), 7);
+    assert_eq!(get("e"), 16);
+    assert_eq!(get("f"), 0);
+    assert_eq!(get("g"), 6);
+    assert_eq!(get("h"), 3);
+    assert_eq!(get("px"), 1);
+    assert_eq!(get("py"), 2);
+
+    let functions = "fn area(int width, int height = 2) -> int { return width * height; } fn sum(int ...numbers) -> int { return 0; }";
+    let check = |call: &str| run_program(&format!("{} x = {};", functions, call)).unwrap_err();
+    assert!(check("area()").contains("expects between 1 and 2 arguments, but 0 were provided"));
+    assert!(check("area(1, 2, 3)").contains("expects between 1 and 2 arguments, but 3 were provided"));
+    assert!(check("area(1, depth: 2)").contains("Unknown parameter 'depth'"));
+    assert!(check("area(1, width: 2)").contains("The parameter 'width' is given more than once"));
+    assert!(check("area(height: 3)").contains("Missing argument for parameter 'width'"));
+    assert!(check("area(width: 1, 2)").contains("Positional arguments must come before named ones"));
+    assert!(check("area(\"wide\")").contains("expected int, found string"));
+    assert!(check("sum(1, \"two\")").contains("expected int, found string"));
+    assert!(check("sum(numbers: 1)").contains("cannot be passed by name"));
+    assert!(run_program("fn f(int x = \"one\") {} f();").unwrap_err().contains("default value of parameter 'x'"));
+    assert!(run_program("fn f(int x = 1, int y) {}").is_err());
+    assert!(run_program("fn f(...rest, int y) {}").is_err());
+    assert!(run_program("struct P { int x; } p = P(y: 1);").unwrap_err().contains("Unknown field 'y'"));
+}
+
+#[test]
+fn test_overloading() {
+    let input = "
+fn describe(int x) -> string {
+    return \"int\";
+}
+fn describe(string s) -> string {
+    return \"string\";
+}
+fn describe(int x, int y) -> string {
+    return \"pair\";
+}
+fn describe<T>(T[] items) -> string {
+    return \"array\";
+}
+fn area(int side) -> int {
+    return side * side;
+}
+fn area(int width, int height = 1) -> int {
+    return width * height;
+}
+struct Point {
+    int x;
+    int y;
+}
+fn describe(Point p) -> string {
+    return \"point\";
+}
+a = describe(1);
+b = describe(\"one\");
+c = describe(1, 2);
+d = describe([1, 2]);
+e = describe(Point(1, 2));
+f = area(3);
+g = area(3, 2);
+h = area(width: 5);
+pick = fn(int i) {
+    if (i == 0) {
+        return 1;
+    }
+    return \"text\";
+};
+i = describe(pick(0));
+j = describe(pick(1));
+    ";
+    let program = run_program(input).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("a"), "int");
+    assert_eq!(get("b"), "string");
+    assert_eq!(get("c"), "pair");
+    assert_eq!(get("d"), "array");
+    assert_eq!(get("e"), "point");
+    assert_eq!(get("f"), 9);
+    assert_eq!(get("g"), 6);
+    assert_eq!(get("h"), 5);
+    assert_eq!(get("i"), "int");
+    assert_eq!(get("j"), "string");
+
+    let err = run_program("fn f(int x) {} fn f(string s) {} f(1.5);").unwrap_err();
+    assert!(err.contains("No overload of 'f' matches the arguments (float)"));
+    assert!(err.contains("f(int x)") && err.contains("f(string s)"));
+    let err = run_program("fn f<T>(int a, T b) {} fn f<T>(T a, int b) {} f(1, 2);").unwrap_err();
+    assert!(err.contains("Call to 'f' with arguments (int, int) is ambiguous"));
+    assert!(err.contains("f(int a, T b)") && err.contains("f(T a, int b)"));
+    let err = run_program("fn f<T>(int a, T b) {} fn f<T>(T a, int b) {} g = fn() { return 1; }; f(g(), g());").unwrap_err();
+    assert!(err.contains("is ambiguous"));
+    assert!(run_program("fn f(int x) {} fn f(int y) {}").unwrap_err().contains("Function 'f(int y)' is already defined"));
+    assert!(run_program("fn f(int x) {} fn f(string s) {} g = f;").unwrap_err().contains("'f' is overloaded"));
+}
+
+#[test]
+fn test_name_resolution() {
+    let input = "
+a = twice(3);
+p = Point(1, 2);
+fn twice(int x) -> int {
+    return add(x, x);
+}
+fn add(int x, int y) -> int {
+    return x + y;
+}
+fn apply(int x) -> int {
+    double = fn(int y) { return twice(y) + offset; };
+    offset = 1;
+    return double(x);
+}
+struct Point {
+    int x;
+    int y;
+}
+b = apply(4);
+px = p.x;
+    ";
+    let program = run_program(input).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("a"), 6);
+    assert_eq!(get("b"), 9);
+    assert_eq!(get("px"), 1);
+
+    let ir = ir::IR::from_tokens(parsing::parse("fn f(int x) {} fn f(string s) {} fn g() {}").unwrap());
+    assert_eq!(ir.lookup_function("f").unwrap().len(), 2);
+    assert!(ir.lookup_function("h").is_none());
+
+    assert!(run_program("x = 1; y = x + z;").unwrap_err().contains("Variable 'z' not found"));
+    assert!(run_program("fn f() { return g(); }").unwrap_err().contains("Function 'g' not found, in function 'f'"));
+    assert!(run_program("fn f() { y = 1; } fn g() { return y; }").unwrap_err().contains("Variable 'y' not found, in function 'g'"));
+    assert!(run_program("f = fn() { return w; };").unwrap_err().contains("Variable 'w' not found, in anonymous function"));
+    assert!(run_program("fn f(int x = y) {}").unwrap_err().contains("Variable 'y' not found"));
+    // a nested function exists from where it is written, and can call the nested functions after it
+    assert!(run_program("fn outer() { r = inner(2); fn inner(int x) { return x; } return r; }").unwrap_err().contains("Function 'inner' not found, in function 'outer'"));
+    let program = run_program("fn outer() { fn even(int n) { if (n == 0) { return 1; } return odd(n - 1); } fn odd(int n) { if (n == 0) { return 0; } return even(n - 1); } return even(4); } e = outer();").unwrap();
+    assert_eq!(program.get_variable(String::from("e")).unwrap().value.get_value(), 1);
+    // match arm, catch and for-in bindings only exist inside them
+    let arm = "enum Box { Full(int v), Empty, } fn f(Box b) { n = match (b) { Box::Full(v) => v, Box::Empty => v, }; return n; }";
+    assert!(run_program(arm).unwrap_err().contains("Variable 'v' not found, in function 'f'"));
+    assert!(run_program("fn f() { try { x = 1 / 0; } catch (e) { x = 0; } return e; }").unwrap_err().contains("Variable 'e' not found, in function 'f'"));
+    assert!(run_program("fn f() { for (i in 0..3) {} return i; }").unwrap_err().contains("Variable 'i' not found, in function 'f'"));
+    // an import only leaves calls with a receiver to the runtime
+    assert!(compile("import \"shapes\"; fn f(int n) { return n.area(); }", OptimizationLevel::None).is_ok());
+    assert!(compile("import \"shapes\"; fn f() { return g(); }", OptimizationLevel::None).unwrap_err().contains("Function 'g' not found, in function 'f'"));
+
+    let (ir, _) = compile("fn twice(int x) -> int { return x + x; } y = 2; z = twice(y);", OptimizationLevel::None).unwrap();
+    let ir::Action::Variable(z) = ir.actions.last().unwrap() else { panic!("expected a variable") };
+    let ir::VariableData::Expression(call) = &z.data else { panic!("expected an expression") };
+    let symbol = ir.symbol_of(call).unwrap();
+    assert_eq!((symbol.name.as_str(), symbol.kind), ("twice", ir::resolver::SymbolKind::Function));
+    let ir::Expression::FunctionCall(call) = &**call else { panic!("expected a call") };
+    let symbol = ir.symbol_of(&call.args[0]).unwrap();
+    assert_eq!((symbol.name.as_str(), symbol.kind, symbol.function.as_deref()), ("y", ir::resolver::SymbolKind::Variable, None));
+}
+
+#[test]
+fn test_optimization_passes() {
+    let input = "
+fn sign(int x) -> int {
+    if (x < 0) {
+        return 0 - 1;
+        x = 5;
+    }
+    return 1;
+}
+fn pick() -> string {
+    if (2 > 3) {
+        return \"never\";
+    } else {
+        label = \"a\" + \"b\";
+    }
+    return label;
+}
+a = 2 * 3 + 1;
+b = (1 << 4) | 1;
+c = 7.5 * 2.0;
+d = sign(0 - 4);
+e = pick();
+fn spin() -> int {
+    n = 0;
+    while (1 > 2) {
+        n = n + 1;
+    }
+    return n;
+}
+n = spin();
+f = 10 / (5 - 5);
+    ";
+    let tokens = parsing::parse(input).unwrap();
+    let mut ir = ir::IR::from_tokens(tokens.clone());
+    ir::PassManager::for_level(OptimizationLevel::Basic).run(&mut ir);
+    let assigned = |ir: &ir::IR, name: &str| ir.actions.iter().find_map(|action| match action {
+        ir::Action::Variable(variable) if variable.name == name => match &variable.data {
+            ir::VariableData::Expression(value) => Some((**value).clone()),
+            _ => None,
+        },
+        _ => None,
+    });
+    assert_eq!(assigned(&ir, "a"), Some(ir::Expression::Literal(ir::Literal::Integer(7))));
+    assert_eq!(assigned(&ir, "b"), Some(ir::Expression::Literal(ir::Literal::Integer(17))));
+    assert_eq!(assigned(&ir, "c"), Some(ir::Expression::Literal(ir::Literal::Float(15.0))));
+    // division by zero is left for the program to report
+    assert!(matches!(assigned(&ir, "f"), Some(ir::Expression::Operation(_))));
+    let spin = ir.lookup_function("spin").unwrap()[0].clone();
+    assert!(!spin.body.iter().any(|action| matches!(action, ir::Action::Loop(_))));
+    let sign = ir.lookup_function("sign").unwrap()[0].clone();
+    match &sign.body[0] {
+        ir::Action::Conditional(conditional) => assert_eq!(conditional.then_actions.len(), 1),
+        other => panic!("expected the if to stay, found {:?}", other),
+    }
+    let pick = ir.lookup_function("pick").unwrap()[0].clone();
+    assert!(!pick.body.iter().any(|action| matches!(action, ir::Action::Conditional(_))));
+
+    let unoptimized = ir::IR::from_tokens(tokens);
+    let mut copy = unoptimized.clone();
+    ir::PassManager::for_level(OptimizationLevel::None).run(&mut copy);
+    assert_eq!(copy.actions, unoptimized.actions);
+
+    let program = run_program(&input.replace("f = 10 / (5 - 5);", "")).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("a"), 7);
+    assert_eq!(get("b"), 17);
+    assert_eq!(get("c"), 15.0);
+    assert_eq!(get("d"), -1);
+    assert_eq!(get("e"), "ab");
+    assert_eq!(get("n"), 0);
+    assert!(run_program(input).unwrap_err().contains("Division by zero"));
+    let options = |optimization| Options { resolver: Arc::new(modules::MemoryResolver::new()), optimization };
+    assert!(run_with_options("x = 1 / 0;", options(OptimizationLevel::None)).unwrap_err().contains("Division by zero"));
+    assert!(run_with_options("x = 1 / 0;", options(OptimizationLevel::Basic)).unwrap_err().contains("Division by zero"));
+    // overflows aren't folded either, the program reports them as an error it can catch
+    let input = "
+fn big() -> int { return 9223372036854775807; }
+fn attempt(int a, int b) -> string {
+    try {
+        x = a / b;
+        return \"ok\";
+    } catch (e) {
+        return e.kind;
+    }
+}
+kind = \"\";
+try {
+    x = big() + 1;
+} catch (e) {
+    kind = e.kind;
+}
+min = 0 - big() - 1;
+quotient = attempt(min, 0 - 1);
+    ";
+    let program = run_program(input).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("kind"), "ArithmeticError");
+    assert_eq!(get("quotient"), "ArithmeticError");
+    assert!(run_program("x = 9223372036854775807 * 2;").unwrap_err().contains("Integer overflow in 9223372036854775807 * 2"));
+}
+
+#[test]
+fn test_control_flow_graph() {
+    use ir::cfg::{Cfg, Statement, Terminator};
+    let input = "
+fn sign(int x) {
+    if (x < 0) {
+        s = 0 - 1;
+    } else {
+        s = 1;
+    }
+    return s;
+}
+fn count(int n) {
+    total = 0;
+    i = 0;
+    while (i < n) {
+        if (i == 3) {
+            break;
+        }
+        total = total + i;
+        i = i + 1;
+    }
+    return total;
+}
+    ";
+    let ir = ir::IR::from_tokens(parsing::parse(input).unwrap());
+    let graph = |name: &str| {
+        let function = &ir.lookup_function(name).unwrap()[0];
+        let params: Vec<String> = function.params.iter().map(|param| param.name.clone()).collect();
+        Cfg::build(&params, &function.body)
+    };
+
+    let cfg = graph("sign");
+    let Terminator::Branch { then_block, else_block, .. } = cfg.blocks[Cfg::ENTRY].terminator else { panic!("expected a branch") };
+    assert_eq!(cfg.blocks[then_block].terminator, cfg.blocks[else_block].terminator);
+    let Terminator::Goto(join) = cfg.blocks[then_block].terminator else { panic!("expected a jump") };
+    let dominators = cfg.dominators();
+    assert_eq!(dominators[then_block], Some(Cfg::ENTRY));
+    assert_eq!(dominators[join], Some(Cfg::ENTRY));
+    assert_eq!(cfg.dominance_frontiers(&dominators)[then_block], [join].into());
+
+    let ssa = cfg.to_ssa();
+    assert_eq!(ssa.blocks[Cfg::ENTRY].statements, vec![Statement::Define("x.1".to_string())]);
+    assert_eq!(ssa.blocks[join].statements[0], Statement::Phi { variable: "s.3".to_string(), operands: vec!["s.1".to_string(), "s.2".to_string()] });
+    assert_eq!(ssa.blocks[join].terminator, Terminator::Return(ir::Expression::Variable("s.3".to_string(), None)));
+    assert_eq!(ssa.destruct_ssa(), cfg);
+
+    // the loop header merges the values from before the loop and from the end of the body
+    let cfg = graph("count");
+    let ssa = cfg.to_ssa();
+    let header = ssa.blocks.iter().position(|block| matches!(block.terminator, Terminator::Branch { .. })).unwrap();
+    let phis: Vec<&Statement> = ssa.blocks[header].statements.iter().filter(|s| matches!(s, Statement::Phi { .. })).collect();
+    assert_eq!(phis, vec![
+        &Statement::Phi { variable: "i.2".to_string(), operands: vec!["i.1".to_string(), "i.3".to_string()] },
+        &Statement::Phi { variable: "total.2".to_string(), operands: vec!["total.1".to_string(), "total.3".to_string()] },
+    ]);
+    let Terminator::Branch { then_block: body, else_block: exit, .. } = cfg.blocks[header].terminator else { unreachable!() };
+    let dominators = cfg.dominators();
+    assert_eq!((dominators[body], dominators[exit]), (Some(header), Some(header)));
+    assert_eq!(ssa.destruct_ssa(), cfg);
+
+    let uninitialized = "
+count = 0;
+fn maybe(int n) {
+    if (n > 0) {
+        y = 1;
+    }
+    return y;
+}
+fn never() {
+    z = w + 1;
+    w = 2;
+    return z;
+}
+fn global() {
+    count = count + 1;
+    return count;
+}
+fn looped(int n) {
+    for (i = 0; i < n; i = i + 1) {
+        last = i;
+    }
+    return last;
+}
+fn caught() {
+    try {
+        value = 1;
+    } catch (e) {
+        value = 2;
+    }
+    return value + e;
+}
+fn forever() {
+    while (1) {
+        r = 1;
+        break;
+    }
+    return r;
+}
+f = fn() {
+    z = w + 1;
+    w = 2;
+    return z;
+};
+    ";
+    let ir = ir::IR::from_tokens(parsing::parse(uninitialized).unwrap());
+    assert_eq!(ir.type_check().unwrap(), vec![
+        "Variable 'y' may be used before it is assigned, in function 'maybe'".to_string(),
+        "Variable 'w' is used before it is assigned, in function 'never'".to_string(),
+        "Variable 'last' may be used before it is assigned, in function 'looped'".to_string(),
+        "Variable 'e' may be used before it is assigned, in function 'caught'".to_string(),
+        "Variable 'w' is used before it is assigned, in anonymous function".to_string(),
+    ]);
+    assert_eq!(compile("x = 1;\ny = x;", OptimizationLevel::None).unwrap().1, Vec::<String>::new());
+    assert_eq!(compile("y = x + 1;\nx = 1;", OptimizationLevel::None).unwrap().1, vec!["Variable 'x' is used before it is assigned".to_string()]);
+}
+
+#[test]
+fn test_inlining_and_tail_calls() {
+    let input = "
+fn sum(int n, int acc) {
+    if (n == 0) {
+        return acc;
+    }
+    return sum(n - 1, acc + n);
+}
+fn is_even(int n, int even) {
+    while (n > 0) {
+        if (n % 2 == 0) {
+            return is_even(n - 2, even);
+        }
+        return is_even(n - 1, 1 - even);
+    }
+    return even;
+}
+fn collatz(int n, int steps) {
+    switch (n) {
+        case 1:
+            return steps;
+        default:
+            if (n % 2 == 0) {
+                return collatz(n / 2, steps + 1);
+            }
+            return collatz(3 * n + 1, steps + 1);
+    }
+}
+fn swap(int a, int b, int times) {
+    if (times == 0) {
+        return a * 10 + b;
+    }
+    return swap(b, a, times - 1);
+}
+fn g(int n, int i) {
+    for (i in 0..2) {
+        if (n > 0) {
+            return g(n - 1, i + 10);
+        }
+    }
+    return i;
+}
+fn f(int n) {
+    const K = 5;
+    if (n == 0) {
+        return K;
+    }
+    return f(n - 1);
+}
+total = sum(1000000, 0);
+even = is_even(1001, 1);
+steps = collatz(27, 0);
+swapped = swap(1, 2, 3);
+looped = g(1, 0);
+constant = f(3);
+    ";
+    let program = run_program(input).unwrap();
+    let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+    assert_eq!(get("total"), 500000500000i64);
+    assert_eq!(get("even"), 0);
+    assert_eq!(get("steps"), 111);
+    assert_eq!(get("swapped"), 21);
+    // the for-in would restore `i` once it ends, so that call isn't turned into a jump
+    assert_eq!(get("looped"), 10);
+    assert_eq!(get("constant"), 5);
+
+    // the recursive call is a jump back to the start of the body, rather than a call
+    let (ir, _) = compile(input, OptimizationLevel::Basic).unwrap();
+    let body = &ir.lookup_function("sum").unwrap()[0].body;
+    assert!(matches!(body.as_slice(), [ir::Action::Loop(_)]));
+    let (ir, _) = compile(input, OptimizationLevel::None).unwrap();
+    assert!(matches!(ir.lookup_function("sum").unwrap()[0].body.as_slice(), [ir::Action::Conditional(_), _]));
+    // only self-recursion is eliminated, a tail call to another function stays a call
+    let (ir, _) = compile("fn ping(int n) { if (n == 0) { return 0; } return pong(n - 1); } fn pong(int n) { return ping(n); }", OptimizationLevel::Basic).unwrap();
+    assert!(matches!(ir.lookup_function("ping").unwrap()[0].body.as_slice(), [ir::Action::Conditional(_), _]));
+
+    // a `catch` must see the errors of a call in a `try`, so it stays a call
+    let guarded = "
+fn depth(int n) {
+    try {
+        return depth(n - 1);
+    } catch (e) {
+        return 0;
+    }
+}
+    ";
+    let (ir, _) = compile(guarded, OptimizationLevel::Basic).unwrap();
+    assert!(matches!(ir.lookup_function("depth").unwrap()[0].body.as_slice(), [ir::Action::Try(_)]));
+
+    // modules are optimized like the main program, or the recursion would overflow the stack
+    let mut resolver = modules::MemoryResolver::new();
+    resolver.add_module("sums", "fn sum(int n, int acc) { if (n == 0) { return acc; } return sum(n - 1, acc + n); }");
+    let program = run_program_with("import { sum } from \"sums\"; total = sum(100000, 0);", Arc::new(resolver)).unwrap();
+    assert_eq!(program.get_variable(String::from("total")).unwrap().value.get_value(), 5000050000i64);
+
+    let inlined = "
+fn square(int x) {
+    return x * x;
+}
+fn area(int w, int h) {
+    return w * h;
+}
+fn noisy(int x) {
+    s = toString(x);
+    return x;
+}
+@inline
+fn long(int x) {
+    return x + x + x + x + x + x + x + x + x + x;
+}
+@deprecated
+fn old(int x) {
+    return x;
+}
+side = 4;
+a = square(side) + area(2, side);
+b = square(side + 1);
+c = noisy(3);
+d = long(1);
+e = old(2);
+    ";
+    // arguments that aren't literals or variables would have to be evaluated once, so `square(side + 1)` stays a call
+    let (ir, _) = compile(inlined, OptimizationLevel::Full).unwrap();
+    let value = |name: &str| ir.actions.iter().find_map(|action| match action {
+        ir::Action::Variable(variable) if variable.name == name => Some(format!("{:?}", variable.data)),
+        _ => None,
+    }).unwrap();
+    assert!(!value("a").contains("FunctionCall"));
+    assert!(value("b").contains("square") && value("c").contains("noisy"));
+    // `@inline` inlines past the size limit, a `@deprecated` function is still called
+    assert!(!value("d").contains("FunctionCall") && value("e").contains("old"));
+    for level in [OptimizationLevel::None, OptimizationLevel::Basic, OptimizationLevel::Full] {
+        let (ir, _) = compile(inlined, level).unwrap();
+        let mut program = execution::Program::new();
+        program.include_std_library(builtin::get_std_lib(), builtin::get_std_functions());
+        program.run(&ir).unwrap();
+        let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+        assert_eq!((get("a"), get("b"), get("c")), (serde_json::json!(24), serde_json::json!(25), serde_json::json!(3)));
+        assert_eq!((get("d"), get("e")), (serde_json::json!(10), serde_json::json!(2)));
+    }
+
+    // only calls bound to the top-level function are inlined, not those to a parameter or nested function of its name
+    let shadowed = "
+fn twice(int x) {
+    return x * 2;
+}
+fn apply(fn twice, int x) {
+    return twice(x);
+}
+fn square(int x) {
+    return x + 1;
+}
+fn scaled() {
+    fn square(int x) {
+        return x * 100;
+    }
+    return square(2);
+}
+applied = apply(fn(int x) -> int { return x + 3; }, 4);
+nested = scaled();
+    ";
+    for level in [OptimizationLevel::None, OptimizationLevel::Full] {
+        let (ir, _) = compile(shadowed, level).unwrap();
+        let mut program = execution::Program::new();
+        program.include_std_library(builtin::get_std_lib(), builtin::get_std_functions());
+        program.run(&ir).unwrap();
+        let get = |name: &str| program.get_variable(String::from(name)).unwrap().value.get_value();
+        assert_eq!((get("applied"), get("nested")), (serde_json::json!(7), serde_json::json!(200)));
+    }
+}
@@ -0,0 +1,41 @@
+use std::{collections::HashMap, sync::Arc};
+use crate::execution::Program;
+use super::resolver::ModuleResolver;
+
+/// Keeps track of the modules of a program while it runs.
+/// Each module is parsed, lowered and run once, and the resulting symbol table is shared by every import.
+#[derive(Debug)]
+pub struct ModuleLoader {
+    pub resolver: Arc<dyn ModuleResolver>,
+    pub modules: HashMap<String, Arc<Program>>,
+    // the modules currently being loaded, innermost last, to detect import cycles
+    pub loading: Vec<String>,
+}
+
+impl ModuleLoader {
+    pub fn new(resolver: Arc<dyn ModuleResolver>) -> Self {
+        ModuleLoader {
+            resolver,
+            modules: HashMap::new(),
+            loading: Vec::new(),
+        }
+    }
+
+    /// Marks a module as being loaded, failing if it is already being loaded further up the import chain
+    pub fn start(&mut self, key: &str) -> Result<(), String> {
+        if let Some(index) = self.loading.iter().position(|loading| loading == key) {
+            let mut cycle = self.loading[index..].to_vec();
+            cycle.push(key.to_string());
+            return Err(format!("Import cycle: {}", cycle.join(" -> ")));
+        }
+        self.loading.push(key.to_string());
+        Ok(())
+    }
+
+    pub fn finish(&mut self, key: &str, module: Option<Arc<Program>>) {
+        self.loading.retain(|loading| loading != key);
+        if let Some(module) = module {
+            self.modules.insert(key.to_string(), module);
+        }
+    }
+}
@@ -0,0 +1,12 @@
+mod resolver;
+mod loader;
+
+pub use resolver::{ModuleResolver, FileResolver, MemoryResolver};
+pub(crate) use loader::ModuleLoader;
+
+/// The namespace a module is imported under: the last segment of its path, without the extension
+/// (`import "math/vec.cl";` is used as `vec::dot(a, b)`)
+pub fn namespace(path: &str) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.strip_suffix(".cl").unwrap_or(name).to_string()
+}
@@ -0,0 +1,72 @@
+use std::{collections::HashMap, fmt::Debug, path::PathBuf};
+
+/// Finds the source of imported modules, so hosts can serve modules from their own storage
+pub trait ModuleResolver: Debug + Send + Sync {
+    /// Turns the path of an import into the key identifying the module.
+    /// `from` is the key of the importing module, or None for the main program.
+    /// Two imports resolving to the same key share one module.
+    fn resolve(&self, path: &str, from: Option<&str>) -> Result<String, String>;
+    /// Loads the source of a module from the key `resolve` returned
+    fn load(&self, key: &str) -> Result<String, String>;
+}
+
+/// Loads modules from `.cl` files, relative to the importing module or to the root directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileResolver {
+    pub root: PathBuf,
+}
+
+impl FileResolver {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FileResolver { root: root.into() }
+    }
+}
+
+impl ModuleResolver for FileResolver {
+    fn resolve(&self, path: &str, from: Option<&str>) -> Result<String, String> {
+        let base = match from.and_then(|from| PathBuf::from(from).parent().map(|p| p.to_path_buf())) {
+            Some(directory) => directory,
+            None => self.root.clone(),
+        };
+        let mut file = base.join(path);
+        if file.extension().is_none() {
+            file.set_extension("cl");
+        }
+        let file = file.canonicalize().map_err(|_| format!("Module '{}' not found", path))?;
+        Ok(file.to_string_lossy().to_string())
+    }
+
+    fn load(&self, key: &str) -> Result<String, String> {
+        std::fs::read_to_string(key).map_err(|err| format!("Cannot read module '{}': {}", key, err))
+    }
+}
+
+/// Serves modules from sources registered by the host, by path
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MemoryResolver {
+    pub modules: HashMap<String, String>,
+}
+
+impl MemoryResolver {
+    pub fn new() -> Self {
+        MemoryResolver::default()
+    }
+
+    pub fn add_module(&mut self, path: &str, source: &str) {
+        self.modules.insert(path.to_string(), source.to_string());
+    }
+}
+
+impl ModuleResolver for MemoryResolver {
+    fn resolve(&self, path: &str, _from: Option<&str>) -> Result<String, String> {
+        // `import "vec";` finds a module registered as either "vec" or "vec.cl"
+        let candidates = [path.to_string(), format!("{}.cl", path), path.strip_suffix(".cl").unwrap_or(path).to_string()];
+        candidates.into_iter()
+            .find(|candidate| self.modules.contains_key(candidate))
+            .ok_or(format!("Module '{}' not found", path))
+    }
+
+    fn load(&self, key: &str) -> Result<String, String> {
+        self.modules.get(key).cloned().ok_or(format!("Module '{}' not found", key))
+    }
+}
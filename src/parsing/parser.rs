@@ -1,4 +1,4 @@
-use pest::iterators::Pair;
+use pest::iterators::{Pair, Pairs};
 use pest::Parser;
 use std::error::Error;
 
@@ -12,11 +12,23 @@ impl CharParser {
                 let primitive = inner.next().expect("Type must have a primitive type");
                 let mut base_type = Self::parse_type(primitive);
                 
-                // Handle type suffixes (arrays)
+                // Handle type suffixes (generic arguments, arrays and optionals)
+                let mut sizes = Vec::new();
                 for suffix in inner {
                     match suffix.as_rule() {
+                        Rule::type_arguments => {
+                            let name = match base_type {
+                                Type::Struct(name) => name,
+                                _ => unreachable!("type arguments must follow the type name"),
+                            };
+                            base_type = Type::Generic(name, Self::parse_type_arguments(suffix));
+                        }
                         Rule::array_suffix => {
-                            base_type = Type::Array(Box::new(base_type.clone()));
+                            sizes.push(Self::parse_array_size(suffix));
+                        }
+                        Rule::optional_suffix => {
+                            // `int[2]?` is an optional array, `int?[2]` an array of optionals
+                            base_type = Type::Optional(Box::new(Self::wrap_array(base_type, std::mem::take(&mut sizes))));
                         }
                         _ => {
                             println!("Unexpected rule in type suffix: {:?}", suffix.as_rule()); // Debug print
@@ -24,10 +36,13 @@ impl CharParser {
                         }
                     }
                 }
-                base_type
+                Self::wrap_array(base_type, sizes)
             }
             Rule::identifier => {
-                return Type::Struct(pair.as_str().to_string());
+                Type::Struct(pair.as_str().to_string())
+            }
+            Rule::tuple_type => {
+                Type::Tuple(pair.into_inner().map(Self::parse_type).collect())
             }
             _ => {
                 panic!("Unexpected rule in parse_type: {:?}", pair.as_rule())
@@ -35,6 +50,48 @@ impl CharParser {
         }
     }
 
+    fn parse_array_size(suffix: Pair<Rule>) -> Option<Box<ExpressionDecl>> {
+        suffix.into_inner().next().map(|size| Box::new(Self::parse_expression(size)))
+    }
+
+    /// Wraps `base_type` in one array per suffix, the first suffix being the outermost like in C's `int a[2][3]`
+    fn wrap_array(base_type: Type, sizes: Vec<Option<Box<ExpressionDecl>>>) -> Type {
+        sizes.into_iter().rev().fold(base_type, |inner, size| Type::Array(Box::new(inner), size))
+    }
+
+    fn parse_type_arguments(pair: Pair<Rule>) -> Vec<Type> {
+        pair.into_inner().map(Self::parse_type).collect()
+    }
+
+    fn parse_type_parameters(pair: Pair<Rule>) -> Vec<String> {
+        pair.into_inner().map(|p| p.as_str().to_string()).collect()
+    }
+
+    /// Replaces every named type that refers to one of `parameters` with a `Type::Parameter`
+    fn bind_type_parameters(type_info: Type, parameters: &[String]) -> Type {
+        match type_info {
+            Type::Struct(name) if parameters.contains(&name) => Type::Parameter(name),
+            Type::Array(inner, size) => Type::Array(Box::new(Self::bind_type_parameters(*inner, parameters)), size),
+            Type::Tuple(elements) => Type::Tuple(elements.into_iter().map(|t| Self::bind_type_parameters(t, parameters)).collect()),
+            Type::Optional(inner) => Type::Optional(Box::new(Self::bind_type_parameters(*inner, parameters))),
+            Type::Generic(name, arguments) => Type::Generic(
+                name,
+                arguments.into_iter().map(|t| Self::bind_type_parameters(t, parameters)).collect(),
+            ),
+            other => other,
+        }
+    }
+
+    fn parse_fields(pair: Pair<Rule>, type_parameters: &[String]) -> Vec<FieldDecl> {
+        pair.into_inner().map(|t| FieldDecl {
+            type_info: Self::bind_type_parameters(
+                Self::parse_type(t.clone().into_inner().next().expect("Field type missing")),
+                type_parameters,
+            ),
+            name: t.into_inner().nth(1).expect("Field name missing").as_str().to_string(),
+        }).collect()
+    }
+
     fn parse_unary_operator(pair: Pair<Rule>) -> UnaryOperator {
         match pair.as_str() {
             "-" => UnaryOperator::Negate,
@@ -62,14 +119,18 @@ impl CharParser {
                     })
                 }
             }
+            Rule::constant_expression => {
+                Self::parse_expression(pair.into_inner().next().expect("Missing constant expression"))
+            }
             Rule::struct_declaration => {
                 let mut inner = pair.into_inner();
+                Self::parse_attributes(&mut inner);
                 let name = inner.next().expect("Missing struct name").as_str().to_string();
-                let fields = inner.next().expect("Struct fields missing").into_inner();
-                let fields = fields.map(|t| FieldDecl {
-                    type_info: Self::parse_type(t.clone().into_inner().next().expect("Field type missing")),
-                    name: t.into_inner().nth(1).expect("Field name missing").as_str().to_string(),
-                }).collect();
+                let mut type_parameters = Vec::new();
+                if inner.peek().map(|p| p.as_rule()) == Some(Rule::type_parameters) {
+                    type_parameters = Self::parse_type_parameters(inner.next().unwrap());
+                }
+                let fields = Self::parse_fields(inner.next().expect("Struct fields missing"), &type_parameters);
                 ExpressionDecl::Struct(name, fields)
             }
             Rule::assignment_expression => {
@@ -78,15 +139,22 @@ impl CharParser {
                 let operation = inner.next();
                 if let Some(right) = inner.next() {
                     let right = Self::parse_expression(right);
-                    if operation.is_some() {
-                        match operation.unwrap().as_str() {
-                            "=" => return ExpressionDecl::Assignment(Box::new(left), Box::new(right)),
-                            "-" => return ExpressionDecl::BinaryOp(BinaryOperator::Subtract, Box::new(left), Box::new(right)),
-                            "+" => return ExpressionDecl::BinaryOp(BinaryOperator::Add, Box::new(left), Box::new(right)),
-                            _ => {}
-                        }
-                    }
-                    return ExpressionDecl::Assignment(Box::new(left), Box::new(right));
+                    let operator = match operation.map(|o| o.as_str()) {
+                        Some("+=") => BinaryOperator::Add,
+                        Some("-=") => BinaryOperator::Subtract,
+                        Some("*=") => BinaryOperator::Multiply,
+                        Some("/=") => BinaryOperator::Divide,
+                        Some("%=") => BinaryOperator::Modulo,
+                        Some("&=") => BinaryOperator::BitwiseAnd,
+                        Some("|=") => BinaryOperator::BitwiseOr,
+                        Some("^=") => BinaryOperator::BitwiseXor,
+                        Some("<<=") => BinaryOperator::ShiftLeft,
+                        Some(">>=") => BinaryOperator::ShiftRight,
+                        _ => return ExpressionDecl::Assignment(Box::new(left), Box::new(right)),
+                    };
+                    // Compound assignments are expanded, so `x += 1` becomes `x = x + 1`
+                    let value = ExpressionDecl::BinaryOp(operator, Box::new(left.clone()), Box::new(right));
+                    ExpressionDecl::Assignment(Box::new(left), Box::new(value))
                 } else {
                     left
                 }
@@ -103,15 +171,28 @@ impl CharParser {
                             result = ExpressionDecl::ArrayAccess(Box::new(result), Box::new(index));
                         }
                         Rule::function_call => {
-                            let args = op.into_inner()
-                                .map(Self::parse_expression)
-                                .collect();
-                            result = ExpressionDecl::Call(Box::new(result), args);
+                            let line = op.as_span().start_pos().line_col().0;
+                            let args = Self::parse_arguments(op);
+                            result = ExpressionDecl::Call(Box::new(result), args, line);
+                        }
+                        Rule::generic_call => {
+                            let line = op.as_span().start_pos().line_col().0;
+                            let mut call = op.into_inner();
+                            let type_arguments = Self::parse_type_arguments(call.next().expect("Missing type arguments"));
+                            let args = Self::parse_arguments(call.next().expect("Missing call arguments"));
+                            result = ExpressionDecl::GenericCall(Box::new(result), type_arguments, args, line);
                         }
                         Rule::member_access => {
                             let member = op.into_inner().next().expect("Missing member name").as_str().to_string();
                             result = ExpressionDecl::MemberAccess(Box::new(result), member);
                         }
+                        Rule::optional_member_access => {
+                            let member = op.into_inner().next().expect("Missing member name").as_str().to_string();
+                            result = ExpressionDecl::OptionalMemberAccess(Box::new(result), member);
+                        }
+                        Rule::try_operator => {
+                            result = ExpressionDecl::Try(Box::new(result));
+                        }
                         _ => panic!("Unexpected postfix operator: {:?}", op.as_rule())
                     }
                 }
@@ -142,12 +223,20 @@ impl CharParser {
             }
             Rule::conditional_expression => {
                 let mut inner = pair.into_inner();
-                return Self::parse_expression(inner.next().expect("Missing condition"));
+                Self::parse_expression(inner.next().expect("Missing condition"))
+            }
+            Rule::coalesce_expression => {
+                let mut inner = pair.into_inner();
+                let mut result = Self::parse_expression(inner.next().expect("Missing first operand"));
+                for right in inner {
+                    result = ExpressionDecl::BinaryOp(BinaryOperator::Coalesce, Box::new(result), Box::new(Self::parse_expression(right)));
+                }
+                result
             }
             Rule::logical_or_expression => {
                 let mut inner = pair.into_inner();
                 let mut result = Self::parse_expression(inner.next().expect("Missing first operand"));
-                while let Some(right) = inner.next() {
+                for right in inner {
                     result = ExpressionDecl::BinaryOp(BinaryOperator::Or, Box::new(result), Box::new(Self::parse_expression(right)));
                 }
                 result
@@ -155,11 +244,31 @@ impl CharParser {
             Rule::logical_and_expression => {
                 let mut inner = pair.into_inner();
                 let mut result = Self::parse_expression(inner.next().expect("Missing first operand"));
-                while let Some(right) = inner.next() {
+                for right in inner {
                     result = ExpressionDecl::BinaryOp(BinaryOperator::And, Box::new(result), Box::new(Self::parse_expression(right)));
                 }
                 result
             }
+            Rule::bitwise_or_expression |
+            Rule::bitwise_xor_expression |
+            Rule::bitwise_and_expression |
+            Rule::shift_expression => {
+                let mut inner = pair.into_inner();
+                let mut result = Self::parse_expression(inner.next().expect("Missing first operand"));
+                while let Some(op) = inner.next() {
+                    let right = Self::parse_expression(inner.next().expect("Missing right operand"));
+                    let operator = match op.as_str() {
+                        "&" => BinaryOperator::BitwiseAnd,
+                        "|" => BinaryOperator::BitwiseOr,
+                        "^" => BinaryOperator::BitwiseXor,
+                        "<<" => BinaryOperator::ShiftLeft,
+                        ">>" => BinaryOperator::ShiftRight,
+                        _ => panic!("Unexpected bitwise operator: {}", op.as_str())
+                    };
+                    result = ExpressionDecl::BinaryOp(operator, Box::new(result), Box::new(right));
+                }
+                result
+            }
             Rule::equality_expression => {
                 let mut inner = pair.into_inner();
                 let mut result = Self::parse_expression(inner.next().expect("Missing first operand"));
@@ -190,18 +299,40 @@ impl CharParser {
                 }
                 result
             }
+            Rule::range_expression => {
+                let mut inner = pair.into_inner();
+                let start = Self::parse_expression(inner.next().expect("Missing range start"));
+                match inner.next() {
+                    None => start,
+                    Some(operator) => {
+                        let end = Self::parse_expression(inner.next().expect("Missing range end"));
+                        let step = inner.nth(1).map(|step| Box::new(Self::parse_expression(step)));
+                        ExpressionDecl::Range(RangeExpression {
+                            start: Box::new(start),
+                            end: Box::new(end),
+                            step,
+                            inclusive: operator.as_str() == "..=",
+                        })
+                    }
+                }
+            }
             Rule::additive_expression => {
                 let mut inner = pair.into_inner();
-                let result = Self::parse_expression(inner.next().expect("Missing first operand"));
+                let mut result = Self::parse_expression(inner.next().expect("Missing first operand"));
                 while let Some(op) = inner.next() {
-                    let expr = Self::parse_expression(op.clone());
-                    return ExpressionDecl::BinaryOp(BinaryOperator::Add, Box::new(result), Box::new(expr));
+                    let right = Self::parse_expression(inner.next().expect("Missing right operand"));
+                    let operator = match op.as_str() {
+                        "+" => BinaryOperator::Add,
+                        "-" => BinaryOperator::Subtract,
+                        _ => panic!("Unexpected additive operator: {}", op.as_str())
+                    };
+                    result = ExpressionDecl::BinaryOp(operator, Box::new(result), Box::new(right));
                 }
                 result
             }
             Rule::multiplicative_expression => {
                 let mut inner = pair.into_inner();
-                let left = Self::parse_expression(inner.next().expect("Missing first operand"));
+                let mut result = Self::parse_expression(inner.next().expect("Missing first operand"));
                 while let Some(op) = inner.next() {
                     let right = Self::parse_expression(inner.next().expect("Missing right operand"));
                     let operator = match op.as_str() {
@@ -210,15 +341,14 @@ impl CharParser {
                         "%" => BinaryOperator::Modulo,
                         _ => panic!("Unexpected multiplicative operator: {}", op.as_str())
                     };
-                    return ExpressionDecl::BinaryOp(operator, Box::new(left), Box::new(right));
+                    result = ExpressionDecl::BinaryOp(operator, Box::new(result), Box::new(right));
                 }
-                left
+                result
             }
             Rule::unary_expression => {
                 let mut inner = pair.into_inner();
-                if inner.peek().unwrap().as_rule() == Rule::postfix_expression{
-                    let expr = Self::parse_expression(inner.next().expect("Missing expression in unary operation"));
-                    return ExpressionDecl::UnaryOp(UnaryOperator::Negate, Box::new(expr));
+                if inner.peek().unwrap().as_rule() != Rule::unary_operator {
+                    return Self::parse_expression(inner.next().expect("Missing expression in unary operation"));
                 }
                 let op = inner.next().expect("Missing unary operator");
                 let expr = Self::parse_expression(inner.next().expect("Missing expression in unary operation"));
@@ -245,27 +375,58 @@ impl CharParser {
                     Rule::number => {
                         let num_str = inner.next().unwrap().as_str();
                         if num_str.contains('.') {
-                            return ExpressionDecl::Literal(Literal::Float(num_str.parse().unwrap()));
+                            ExpressionDecl::Literal(Literal::Float(num_str.parse().unwrap()))
                         } else {
-                            return ExpressionDecl::Literal(Literal::Integer(num_str.parse().unwrap()));
+                            ExpressionDecl::Literal(Literal::Integer(num_str.parse().unwrap()))
                         }
                     }
                     Rule::string => {
                         let content = inner.next().unwrap().as_str();
-                        return ExpressionDecl::Literal(Literal::String(content[1..content.len()-1].to_string()));
+                        ExpressionDecl::Literal(Literal::String(content[1..content.len()-1].to_string()))
                     }
                     Rule::char => {
                         let content = inner.next().unwrap().as_str();
-                        return ExpressionDecl::Literal(Literal::Char(content[1..content.len()-1].parse().unwrap()));
+                        ExpressionDecl::Literal(Literal::Char(content[1..content.len()-1].parse().unwrap()))
                     }
                     Rule::identifier => {
-                        return ExpressionDecl::Identifier(inner.next().unwrap().as_str().to_string());
+                        ExpressionDecl::Identifier(inner.next().unwrap().as_str().to_string())
+                    }
+                    Rule::lambda_expression => {
+                        let lambda = Self::parse_function(String::new(), Vec::new(), inner.next().unwrap().into_inner())
+                            .expect("Invalid anonymous function");
+                        ExpressionDecl::Lambda(Box::new(lambda))
+                    }
+                    Rule::match_expression => {
+                        Self::parse_match(inner.next().unwrap())
+                    }
+                    Rule::sizeof_expression => {
+                        let operand = inner.next().unwrap().into_inner().next().expect("Missing sizeof operand");
+                        match operand.as_rule() {
+                            Rule::sizeof_type => {
+                                let typing = operand.into_inner().next().expect("Missing sizeof type");
+                                ExpressionDecl::SizeOf(SizeOfOperand::Type(Self::parse_type(typing)))
+                            }
+                            _ => ExpressionDecl::SizeOf(SizeOfOperand::Expression(Box::new(Self::parse_expression(operand)))),
+                        }
+                    }
+                    Rule::variant_expression => {
+                        let mut variant = inner.next().unwrap().into_inner();
+                        let enum_name = variant.next().expect("Missing enum name").as_str().to_string();
+                        let variant_name = variant.next().expect("Missing variant name").as_str().to_string();
+                        ExpressionDecl::Variant(enum_name, variant_name)
                     }
                     Rule::array_literal => {
                         let elements: Vec<ExpressionDecl> = inner.next().unwrap().into_inner()
                             .map(Self::parse_expression)
                             .collect();
-                        return ExpressionDecl::ArrayLiteral(elements);
+                        ExpressionDecl::ArrayLiteral(elements)
+                    }
+                    Rule::null_literal => ExpressionDecl::Literal(Literal::Null),
+                    Rule::tuple_literal => {
+                        ExpressionDecl::Tuple(inner.next().unwrap().into_inner().map(Self::parse_expression).collect())
+                    }
+                    Rule::expression => {
+                        Self::parse_expression(inner.next().unwrap())
                     }
                     _ => {
                         println!("primary_expression: {:?}", inner.next().unwrap().as_rule());
@@ -273,13 +434,18 @@ impl CharParser {
                     }
                 }
             }
-            Rule::argument_expression_list => {
+            Rule::named_argument => {
                 let mut inner = pair.into_inner();
+                let name = inner.next().expect("Missing argument name").as_str().to_string();
+                ExpressionDecl::NamedArgument(name, Box::new(Self::parse_expression(inner.next().expect("Missing argument value"))))
+            }
+            Rule::argument_expression_list => {
+                let inner = pair.into_inner();
                 let mut result = Vec::new();
-                while let Some(expr) = inner.next() {
+                for expr in inner {
                     result.push(Self::parse_expression(expr));
                 }
-                return ExpressionDecl::ArrayLiteral(result);
+                ExpressionDecl::ArrayLiteral(result)
             }
             _ => {
                 println!("Unexpected rule in parse_expression: {:?}", pair.as_rule());
@@ -288,6 +454,86 @@ impl CharParser {
         }
     }
 
+    fn parse_match(pair: Pair<Rule>) -> ExpressionDecl {
+        let mut inner = pair.into_inner();
+        let scrutinee = Self::parse_expression(inner.next().expect("Missing match value"));
+        let arms = inner.map(|arm| {
+            let mut arm_inner = arm.into_inner();
+            let pattern = Self::parse_pattern(arm_inner.next().expect("Missing match pattern"));
+            let mut guard = None;
+            if arm_inner.peek().expect("Missing match arm body").as_rule() == Rule::match_guard {
+                let condition = arm_inner.next().unwrap().into_inner().next().expect("Missing guard condition");
+                guard = Some(Self::parse_expression(condition));
+            }
+            let body = Self::parse_expression(arm_inner.next().expect("Missing match arm body"));
+            MatchArm { pattern, guard, body }
+        }).collect();
+        ExpressionDecl::Match(MatchExpression { scrutinee: Box::new(scrutinee), arms })
+    }
+
+    fn parse_pattern(pair: Pair<Rule>) -> Pattern {
+        match pair.as_rule() {
+            Rule::pattern => Self::parse_pattern(pair.into_inner().next().expect("Empty pattern")),
+            Rule::wildcard_pattern => Pattern::Wildcard,
+            Rule::binding_pattern => Pattern::Binding(pair.as_str().to_string()),
+            Rule::rest_pattern => Pattern::Rest(pair.into_inner().next().map(|name| name.as_str().to_string())),
+            Rule::variant_pattern => {
+                let mut inner = pair.into_inner();
+                let enum_name = inner.next().expect("Missing enum name").as_str().to_string();
+                let variant_name = inner.next().expect("Missing variant name").as_str().to_string();
+                Pattern::Variant(enum_name, variant_name, inner.map(Self::parse_pattern).collect())
+            }
+            Rule::struct_pattern => {
+                let mut inner = pair.into_inner();
+                let name = inner.next().expect("Missing struct name").as_str().to_string();
+                let fields = inner.map(|field| {
+                    let mut field_inner = field.into_inner();
+                    let field_name = field_inner.next().expect("Missing field name").as_str().to_string();
+                    // `Point { x }` is shorthand for `Point { x: x }`
+                    let pattern = match field_inner.next() {
+                        Some(pattern) => Self::parse_pattern(pattern),
+                        None => Pattern::Binding(field_name.clone()),
+                    };
+                    (field_name, pattern)
+                }).collect();
+                Pattern::Struct(name, fields)
+            }
+            Rule::array_pattern => Pattern::Array(pair.into_inner().map(Self::parse_pattern).collect()),
+            Rule::tuple_pattern => Pattern::Tuple(pair.into_inner().map(Self::parse_pattern).collect()),
+            Rule::literal_pattern => {
+                let mut inner = pair.into_inner();
+                let negative = inner.peek().expect("Missing literal").as_rule() == Rule::negative;
+                if negative {
+                    inner.next();
+                }
+                let literal = inner.next().expect("Missing literal");
+                let content = literal.as_str();
+                match literal.as_rule() {
+                    Rule::number if content.contains('.') => {
+                        let value: f64 = content.parse().unwrap();
+                        Pattern::Literal(Literal::Float(if negative { -value } else { value }))
+                    }
+                    Rule::number => {
+                        let value: i64 = content.parse().unwrap();
+                        Pattern::Literal(Literal::Integer(if negative { -value } else { value }))
+                    }
+                    Rule::string => Pattern::Literal(Literal::String(content[1..content.len()-1].to_string())),
+                    Rule::char => Pattern::Literal(Literal::Char(content[1..content.len()-1].parse().unwrap())),
+                    Rule::null_literal => Pattern::Literal(Literal::Null),
+                    _ => panic!("Unexpected rule in literal pattern: {:?}", literal.as_rule())
+                }
+            }
+            _ => panic!("Unexpected rule in pattern: {:?}", pair.as_rule())
+        }
+    }
+
+    fn parse_arguments(pair: Pair<Rule>) -> Vec<ExpressionDecl> {
+        match pair.into_inner().next() {
+            Some(list) => list.into_inner().map(Self::parse_expression).collect(),
+            None => Vec::new(),
+        }
+    }
+
     fn parse_initializer(pair: Pair<Rule>) -> ExpressionDecl {
         match pair.as_rule() {
             Rule::initializer => {
@@ -309,6 +555,9 @@ impl CharParser {
 
     fn parse_statement(pair: Pair<Rule>) -> Statement {
         match pair.as_rule() {
+            Rule::statement => {
+                Self::parse_statement(pair.into_inner().next().expect("Empty statement"))
+            }
             Rule::compound_statement => {
                 let statements = pair.into_inner()
                     .map(Self::parse_statement)
@@ -330,34 +579,60 @@ impl CharParser {
             }
             Rule::while_statement => {
                 let mut inner = pair.into_inner();
+                let label = Self::parse_label(&mut inner);
                 let condition = Self::parse_expression(inner.next().expect("While condition missing"));
                 let body = Box::new(Self::parse_statement(inner.next().expect("While body missing")));
                 
                 Statement::While(WhileStatement {
+                    label,
                     condition,
                     body,
                 })
             }
             Rule::for_statement => {
                 let mut inner = pair.into_inner();
+                let label = Self::parse_label(&mut inner);
                 let initializer = Box::new(Self::parse_statement(inner.next().expect("For initializer missing")));
                 let condition = inner.next().map(Self::parse_expression);
                 let increment = inner.next().map(Self::parse_expression);
                 let body = Box::new(Self::parse_statement(inner.next().expect("For body missing")));
                 
                 Statement::For(ForStatement {
+                    label,
                     initializer,
                     condition,
                     increment,
                     body,
                 })
             }
+            Rule::for_in_statement => {
+                let mut inner = pair.into_inner();
+                let label = Self::parse_label(&mut inner);
+                let mut type_info = None;
+                if inner.peek().map(|p| p.as_rule()) == Some(Rule::typing) {
+                    type_info = Some(Self::parse_type(inner.next().unwrap()));
+                }
+                let variable = inner.next().expect("For-in variable missing").as_str().to_string();
+                inner.next(); // in
+                let iterable = Self::parse_expression(inner.next().expect("For-in iterable missing"));
+                let body = Box::new(Self::parse_statement(inner.next().expect("For-in body missing")));
+
+                Statement::ForIn(ForInStatement {
+                    label,
+                    type_info,
+                    variable,
+                    iterable,
+                    body,
+                })
+            }
             Rule::do_while_statement => {
                 let mut inner = pair.into_inner();
+                let label = Self::parse_label(&mut inner);
                 let body = Box::new(Self::parse_statement(inner.next().expect("Do-while body missing")));
                 let condition = Self::parse_expression(inner.next().expect("Do-while condition missing"));
                 
                 Statement::DoWhile(DoWhileStatement {
+                    label,
                     body,
                     condition,
                 })
@@ -366,7 +641,6 @@ impl CharParser {
                 let mut inner = pair.into_inner();
                 let expression = Self::parse_expression(inner.next().expect("Switch expression missing"));
                 let mut cases = Vec::new();
-                let mut default = None;
                 
                 for case in inner {
                     match case.as_rule() {
@@ -376,12 +650,13 @@ impl CharParser {
                             let statements = case_inner
                                 .map(Self::parse_statement)
                                 .collect();
-                            cases.push(CaseStatement { value, statements });
+                            cases.push(CaseStatement { value: Some(value), statements });
                         }
                         Rule::default_statement => {
-                            default = Some(case.into_inner()
+                            let statements = case.into_inner()
                                 .map(Self::parse_statement)
-                                .collect());
+                                .collect();
+                            cases.push(CaseStatement { value: None, statements });
                         }
                         _ => unreachable!("Unexpected rule in switch statement")
                     }
@@ -390,49 +665,54 @@ impl CharParser {
                 Statement::Switch(SwitchStatement {
                     expression,
                     cases,
-                    default,
                 })
             }
             Rule::return_statement => {
                 let expr = pair.into_inner().next().map(Self::parse_expression);
                 Statement::Return(expr)
             }
-            Rule::break_statement => Statement::Break,
-            Rule::continue_statement => Statement::Continue,
+            Rule::function_declaration => {
+                let function = Self::parse_function_declaration(pair).expect("Invalid function declaration");
+                Statement::Function(Box::new(function))
+            }
+            Rule::binding_declaration => {
+                Statement::Binding(Self::parse_binding_declaration(pair))
+            }
+            Rule::destructuring_assignment => {
+                let mut inner = pair.into_inner();
+                let pattern = Self::parse_pattern(inner.next().expect("Missing destructuring pattern"));
+                let value = Self::parse_expression(inner.next().expect("Missing destructured value"));
+                Statement::Destructure(pattern, value)
+            }
+            Rule::try_statement => {
+                let mut inner = pair.into_inner();
+                let body = Box::new(Self::parse_statement(inner.next().expect("Try block missing")));
+                let mut catch = None;
+                let mut finally = None;
+                for clause in inner {
+                    match clause.as_rule() {
+                        Rule::catch_clause => {
+                            let mut clause = clause.into_inner();
+                            let variable = clause.next().expect("Catch variable missing").as_str().to_string();
+                            catch = Some((variable, Box::new(Self::parse_statement(clause.next().expect("Catch block missing")))));
+                        }
+                        _ => finally = Some(Box::new(Self::parse_statement(clause.into_inner().next().expect("Finally block missing")))),
+                    }
+                }
+                Statement::Try(TryStatement { body, catch, finally })
+            }
+            Rule::throw_statement => Statement::Throw(Self::parse_expression(pair.into_inner().next().expect("Thrown value missing"))),
+            Rule::yield_statement => Statement::Yield(Self::parse_expression(pair.into_inner().next().expect("Yielded value missing"))),
+            Rule::break_statement => Statement::Break(pair.into_inner().next().map(|label| label.as_str().to_string())),
+            Rule::continue_statement => Statement::Continue(pair.into_inner().next().map(|label| label.as_str().to_string())),
             Rule::expression_statement => {
                 let expr = pair.into_inner().next()
                     .map(Self::parse_expression)
                     .unwrap_or(ExpressionDecl::Literal(Literal::Integer(0)));
                 Statement::Expression(expr)
             }
-            Rule::declaration_statement => {
-                let mut inner = pair.into_inner();
-                let declarations = inner.next().expect("Declaration list missing");
-                
-                // Handle multiple declarations in one statement
-                let mut vars = Vec::new();
-                for decl in declarations.into_inner() {
-                    let mut decl_inner = decl.into_inner();
-                    let name = decl_inner.next().expect("Variable name missing").as_str().to_string();
-                    
-                    let mut initializer = None;
-                    
-                    for item in decl_inner {
-                        match item.as_rule() {
-                            Rule::initializer => {
-                                initializer = Some(Self::parse_initializer(item));
-                            }
-                            Rule::array_suffix => {
-                            }
-                            _ => panic!("Unexpected rule in declaration: {:?}", item.as_rule())
-                        }
-                    }
-                    
-                    vars.push(VariableDecl {
-                        name,
-                        initializer,
-                    });
-                }
+            Rule::declaration_statement | Rule::variable_declaration => {
+                let mut vars = Self::parse_declarations(pair);
                 
                 // If there's only one declaration, return it directly
                 if vars.len() == 1 {
@@ -452,42 +732,289 @@ impl CharParser {
         }
     }
 
+    /// Parses `x = 1, y;` or a typed `int a[10], b;`, where each declarator's array suffixes wrap the declared type
+    fn parse_declarations(pair: Pair<Rule>) -> Vec<VariableDecl> {
+        let mut inner = pair.into_inner();
+        let mut type_info = None;
+        if inner.peek().map(|p| p.as_rule()) == Some(Rule::typing) {
+            type_info = Some(Self::parse_type(inner.next().unwrap()));
+        }
+        let declarations = inner.next().expect("Declaration list missing");
+        
+        // Handle multiple declarations in one statement
+        let mut vars = Vec::new();
+        for decl in declarations.into_inner() {
+            let mut decl_inner = decl.into_inner();
+            let name = decl_inner.next().expect("Variable name missing").as_str().to_string();
+            
+            let mut initializer = None;
+            let mut sizes = Vec::new();
+            
+            for item in decl_inner {
+                match item.as_rule() {
+                    Rule::initializer => {
+                        initializer = Some(Self::parse_initializer(item));
+                    }
+                    Rule::array_suffix => {
+                        sizes.push(Self::parse_array_size(item));
+                    }
+                    _ => panic!("Unexpected rule in declaration: {:?}", item.as_rule())
+                }
+            }
+            
+            vars.push(VariableDecl {
+                name,
+                type_info: type_info.clone().map(|base| Self::wrap_array(base, sizes)),
+                initializer,
+            });
+        }
+        vars
+    }
+
+    fn parse_typedef_declaration(pair: Pair<Rule>) -> TypedefDecl {
+        let mut inner = pair.into_inner();
+        let type_info = Self::parse_type(inner.next().expect("Typedef type missing"));
+        let name = inner.next().expect("Typedef name missing").as_str().to_string();
+        TypedefDecl { name, type_info }
+    }
+
     fn parse_struct_declaration(pair: Pair<Rule>) -> Result<StructDecl, Box<dyn Error>> {
         let mut inner = pair.into_inner();
+        let attributes = Self::parse_attributes(&mut inner);
         let name = inner.next().expect("Struct name missing").as_str().to_string();
-        let fields = inner.next().expect("Struct fields missing").into_inner();
-        let fields = fields.map(|t| FieldDecl {
-            type_info: Self::parse_type(t.clone().into_inner().next().expect("Field type missing")),
-            name: t.into_inner().nth(1).expect("Field name missing").as_str().to_string(),
-        }).collect();
-        Ok(StructDecl { name, fields })
+        let mut type_parameters = Vec::new();
+        if inner.peek().map(|p| p.as_rule()) == Some(Rule::type_parameters) {
+            type_parameters = Self::parse_type_parameters(inner.next().unwrap());
+        }
+        let fields = Self::parse_fields(inner.next().expect("Struct fields missing"), &type_parameters);
+        Ok(StructDecl { attributes, name, type_parameters, fields })
+    }
+
+    /// Consumes the `name:` label written before a loop, if there is one
+    fn parse_label(inner: &mut Pairs<Rule>) -> Option<String> {
+        if inner.peek().map(|p| p.as_rule()) != Some(Rule::loop_label) {
+            return None;
+        }
+        let label = inner.next().unwrap().into_inner().next().expect("Missing loop label");
+        Some(label.as_str().to_string())
+    }
+
+    /// Consumes the attributes written before a declaration
+    fn parse_attributes(inner: &mut Pairs<Rule>) -> Vec<Attribute> {
+        let mut attributes = Vec::new();
+        while inner.peek().map(|p| p.as_rule()) == Some(Rule::attribute) {
+            let mut attribute = inner.next().unwrap().into_inner();
+            let name = attribute.next().expect("Missing attribute name").as_str().to_string();
+            let arguments = attribute
+                .map(|argument| {
+                    let argument = argument.into_inner().next().expect("Missing attribute argument");
+                    match argument.as_rule() {
+                        Rule::string => {
+                            let text = argument.as_str();
+                            text[1..text.len() - 1].to_string()
+                        }
+                        _ => argument.as_str().to_string(),
+                    }
+                })
+                .collect();
+            attributes.push(Attribute { name, arguments });
+        }
+        attributes
+    }
+
+    fn parse_parameters(pair: Pair<Rule>, type_parameters: &[String]) -> Result<Vec<Parameter>, Box<dyn Error>> {
+        let mut parameters = Vec::new();
+        for param in pair.into_inner() {
+            if param.as_rule() == Rule::self_parameter {
+                // The receiver's type is resolved once the enclosing impl is known
+                parameters.push(Parameter { type_info: Type::Struct("Self".to_string()), name: Some("self".to_string()), default: None, variadic: false });
+                continue;
+            }
+            let variadic = param.as_rule() == Rule::variadic_parameter;
+            let mut param_inner = param.into_inner().peekable();
+            // An untyped variadic parameter takes arguments of any type
+            let type_info = match param_inner.peek().map(|p| p.as_rule()) {
+                Some(Rule::typing) => Self::parse_type(param_inner.next().unwrap()),
+                _ => Type::Struct("any".to_string()),
+            };
+            let type_info = Self::bind_type_parameters(type_info, type_parameters);
+            let name = param_inner.next_if(|p| p.as_rule() == Rule::identifier).map(|p| p.as_str().to_string());
+            let default = param_inner.next().map(Self::parse_expression);
+            parameters.push(Parameter { type_info, name, default, variadic });
+        }
+        let name = |param: &Parameter| param.name.clone().unwrap_or_default();
+        if let Some(param) = parameters.iter().rev().skip(1).find(|param| param.variadic) {
+            return Err(format!("Variadic parameter '{}' must be the last parameter", name(param)).into());
+        }
+        let mut defaults = parameters.iter().filter(|param| !param.variadic).skip_while(|param| param.default.is_none());
+        if let Some(param) = defaults.find(|param| param.default.is_none()) {
+            return Err(format!("Parameter '{}' needs a default value, as it follows one that has one", name(param)).into());
+        }
+        Ok(parameters)
+    }
+
+    /// Replaces every use of `Self` with the type an impl block is written for
+    fn bind_self_type(type_info: Type, target: &str) -> Type {
+        match type_info {
+            Type::Struct(name) if name == "Self" => Type::Struct(target.to_string()),
+            Type::Array(inner, size) => Type::Array(Box::new(Self::bind_self_type(*inner, target)), size),
+            Type::Tuple(elements) => Type::Tuple(elements.into_iter().map(|t| Self::bind_self_type(t, target)).collect()),
+            Type::Optional(inner) => Type::Optional(Box::new(Self::bind_self_type(*inner, target))),
+            Type::Generic(name, arguments) => Type::Generic(
+                name,
+                arguments.into_iter().map(|t| Self::bind_self_type(t, target)).collect(),
+            ),
+            other => other,
+        }
+    }
+
+    fn parse_enum_declaration(pair: Pair<Rule>) -> Result<EnumDecl, Box<dyn Error>> {
+        let mut inner = pair.into_inner();
+        let name = inner.next().ok_or("Missing enum name")?.as_str().to_string();
+        let mut type_parameters = Vec::new();
+        if inner.peek().map(|p| p.as_rule()) == Some(Rule::type_parameters) {
+            type_parameters = Self::parse_type_parameters(inner.next().unwrap());
+        }
+        let mut variants = Vec::new();
+        for variant in inner {
+            let mut variant_inner = variant.into_inner();
+            let variant_name = variant_inner.next().ok_or("Missing variant name")?.as_str().to_string();
+            let fields = variant_inner.map(|field| FieldDecl {
+                type_info: Self::bind_type_parameters(
+                    Self::parse_type(field.clone().into_inner().next().expect("Field type missing")),
+                    &type_parameters,
+                ),
+                name: field.into_inner().nth(1).expect("Field name missing").as_str().to_string(),
+            }).collect();
+            variants.push(VariantDecl { name: variant_name, fields });
+        }
+        Ok(EnumDecl { name, type_parameters, variants })
+    }
+
+    fn parse_interface_declaration(pair: Pair<Rule>) -> Result<InterfaceDecl, Box<dyn Error>> {
+        let mut inner = pair.into_inner();
+        let name = inner.next().ok_or("Missing interface name")?.as_str().to_string();
+        let mut methods = Vec::new();
+        for signature in inner {
+            let mut signature_inner = signature.into_inner();
+            let method_name = signature_inner.next().ok_or("Missing method name")?.as_str().to_string();
+            let mut parameters = Vec::new();
+            let mut return_type = None;
+            for item in signature_inner {
+                match item.as_rule() {
+                    Rule::parameter_list => parameters = Self::parse_parameters(item, &[])?,
+                    Rule::return_type => {
+                        let typing = item.into_inner().next().ok_or("Missing return type")?;
+                        return_type = Some(Self::parse_type(typing));
+                    }
+                    _ => unreachable!("Unexpected rule in method signature: {:?}", item.as_rule())
+                }
+            }
+            methods.push(MethodSignature { name: method_name, parameters, return_type });
+        }
+        Ok(InterfaceDecl { name, methods })
+    }
+
+    fn parse_impl_declaration(pair: Pair<Rule>) -> Result<ImplDecl, Box<dyn Error>> {
+        let mut inner = pair.into_inner();
+        let first = inner.next().ok_or("Missing impl target")?.as_str().to_string();
+        // `impl Interface for Target` names the interface first, `impl Target` only the target
+        let (interface, target) = match inner.peek().map(|p| p.as_rule()) {
+            Some(Rule::identifier) => (Some(first), inner.next().unwrap().as_str().to_string()),
+            _ => (None, first),
+        };
+        let mut methods = Vec::new();
+        for method in inner {
+            let mut method = Self::parse_function_declaration(method)?;
+            for parameter in method.parameters.iter_mut() {
+                parameter.type_info = Self::bind_self_type(parameter.type_info.clone(), &target);
+            }
+            method.return_type = method.return_type.map(|t| Self::bind_self_type(t, &target));
+            methods.push(method);
+        }
+        Ok(ImplDecl { interface, target, methods })
+    }
+
+    fn parse_binding_declaration(pair: Pair<Rule>) -> BindingDecl {
+        let mut inner = pair.into_inner();
+        let kind = match inner.next().expect("Missing binding kind").as_str() {
+            "const" => BindingKind::Const,
+            "let" => BindingKind::Let,
+            _ => BindingKind::Var,
+        };
+        let mut type_info = None;
+        if inner.peek().map(|p| p.as_rule()) == Some(Rule::typing) {
+            type_info = Some(Self::parse_type(inner.next().unwrap()));
+        }
+        let name = inner.next().expect("Missing binding name").as_str().to_string();
+        let initializer = inner.next().map(Self::parse_initializer);
+        BindingDecl { kind, type_info, name, initializer }
+    }
+
+    fn parse_import_declaration(pair: Pair<Rule>) -> Result<ImportDecl, Box<dyn Error>> {
+        let mut names = Vec::new();
+        let mut path = None;
+        for item in pair.into_inner() {
+            match item.as_rule() {
+                Rule::import_list => names = item.into_inner().map(|name| name.as_str().to_string()).collect(),
+                Rule::string => {
+                    let content = item.as_str();
+                    path = Some(content[1..content.len()-1].to_string());
+                }
+                _ => return Err(format!("Unexpected rule in import: {:?}", item.as_rule()).into()),
+            }
+        }
+        Ok(ImportDecl { path: path.ok_or("Missing module path")?, names })
     }
 
     fn parse_function_declaration(pair: Pair<Rule>) -> Result<FunctionDecl, Box<dyn Error>> {
         let mut inner = pair.into_inner();
-        
+        let attributes = Self::parse_attributes(&mut inner);
+        let generator = Self::parse_generator_marker(&mut inner);
+
         // Parse function name
         let name = inner.next()
             .ok_or("Missing function name")?
             .as_str()
             .to_string();
 
-        let params_exist = inner.len() > 1;
-        
-        let mut parameters = Vec::new();
-        
+        // Parse type parameters if they exist
+        let mut type_parameters = Vec::new();
+        if inner.peek().map(|p| p.as_rule()) == Some(Rule::type_parameters) {
+            type_parameters = Self::parse_type_parameters(inner.next().unwrap());
+        }
+
+        let mut function = Self::parse_function(name, type_parameters, inner)?;
+        function.attributes = attributes;
+        function.generator = generator;
+        Ok(function)
+    }
+
+    fn parse_generator_marker(inner: &mut Pairs<Rule>) -> bool {
+        let generator = inner.peek().map(|p| p.as_rule()) == Some(Rule::generator_marker);
+        if generator {
+            inner.next();
+        }
+        generator
+    }
+
+    /// Parses the parameters, return type and body shared by declared and anonymous functions
+    fn parse_function(name: String, type_parameters: Vec<String>, mut inner: Pairs<Rule>) -> Result<FunctionDecl, Box<dyn Error>> {
+        // Only anonymous functions still have their marker here, declarations take it before the name
+        let generator = Self::parse_generator_marker(&mut inner);
+
         // Parse parameters if they exist
-        if params_exist {
-            if let Some(param_list) = inner.next() {
-            if param_list.as_rule() == Rule::parameter_list {
-                for param in param_list.into_inner() {
-                    let mut param_inner = param.into_inner();
-                    let type_info = Self::parse_type(param_inner.next().ok_or("Missing parameter type")?);
-                    let name = param_inner.next().map(|p| p.as_str().to_string());
-                    parameters.push(Parameter { type_info, name });
-                }
-            }
-        }}
+        let mut parameters = Vec::new();
+        if inner.peek().map(|p| p.as_rule()) == Some(Rule::parameter_list) {
+            parameters = Self::parse_parameters(inner.next().unwrap(), &type_parameters)?;
+        }
+
+        // Parse return type if it exists
+        let mut return_type = None;
+        if inner.peek().map(|p| p.as_rule()) == Some(Rule::return_type) {
+            let typing = inner.next().unwrap().into_inner().next().ok_or("Missing return type")?;
+            return_type = Some(Self::bind_type_parameters(Self::parse_type(typing), &type_parameters));
+        }
         // Parse function body
         let body = if let Some(body_pair) = inner.next() {
             match body_pair.as_rule() {
@@ -497,12 +1024,19 @@ impl CharParser {
                         let stmt = stmt.into_inner().next().unwrap();
                         match stmt.as_rule() {
                             Rule::declaration_statement |
+                            Rule::variable_declaration |
+                            Rule::destructuring_assignment |
+                            Rule::binding_declaration |
                             Rule::expression_statement |
                             Rule::if_statement |
                             Rule::while_statement |
                             Rule::for_statement |
+                            Rule::for_in_statement |
                             Rule::do_while_statement |
                             Rule::switch_statement |
+                            Rule::try_statement |
+                            Rule::throw_statement |
+                            Rule::yield_statement |
                             Rule::return_statement |
                             Rule::break_statement |
                             Rule::continue_statement |
@@ -510,7 +1044,8 @@ impl CharParser {
                                 statements.push(Token::Statement(Self::parse_statement(stmt)));
                             }
                             Rule::function_declaration => {
-                                statements.push(Token::Function(Self::parse_function_declaration(stmt)?));
+                                let function = Self::parse_function_declaration(stmt)?;
+                                statements.push(Token::Statement(Statement::Function(Box::new(function))));
                             }
                             Rule::struct_declaration => {
                                 statements.push(Token::Struct(Self::parse_struct_declaration(stmt)?));
@@ -530,9 +1065,13 @@ impl CharParser {
         };
         
         Ok(FunctionDecl {
+            attributes: Vec::new(),
             name,
+            type_parameters,
             parameters,
+            return_type,
             body,
+            generator,
         })
     }
 }
@@ -552,7 +1091,7 @@ impl CharParser {
 /// ```
 /// use charlang::parsing::parse;
 /// 
-/// let input = "int main() { return 0; }";
+/// let input = "fn main() { return 0; }";
 /// let tokens = parse(input).unwrap();
 /// ```
 /// 
@@ -574,16 +1113,34 @@ pub fn parse(input: &str) -> Result<Vec<Token>, Box<dyn Error>> {
             Rule::function_declaration => {
                 tokens.push(Token::Function(CharParser::parse_function_declaration(pair)?));
             }
-            Rule::declaration_statement => {
-                let statement = CharParser::parse_statement(pair);
-                if let Statement::Declaration(var_decl) = statement {
-                    tokens.push(Token::Variable(var_decl));
-                }
+            Rule::declaration_statement | Rule::variable_declaration => {
+                tokens.extend(CharParser::parse_declarations(pair).into_iter().map(Token::Variable));
+            }
+            Rule::typedef_declaration => {
+                tokens.push(Token::Typedef(CharParser::parse_typedef_declaration(pair)));
             }
             Rule::expression => {
                 let expression = CharParser::parse_expression(pair);
                 tokens.push(Token::Expression(expression));
             }
+            Rule::enum_declaration => {
+                tokens.push(Token::Enum(CharParser::parse_enum_declaration(pair)?));
+            }
+            Rule::interface_declaration => {
+                tokens.push(Token::Interface(CharParser::parse_interface_declaration(pair)?));
+            }
+            Rule::impl_declaration => {
+                tokens.push(Token::Impl(CharParser::parse_impl_declaration(pair)?));
+            }
+            Rule::import_declaration => {
+                tokens.push(Token::Import(CharParser::parse_import_declaration(pair)?));
+            }
+            Rule::binding_declaration => {
+                tokens.push(Token::Statement(Statement::Binding(CharParser::parse_binding_declaration(pair))));
+            }
+            Rule::for_statement | Rule::for_in_statement | Rule::destructuring_assignment | Rule::try_statement | Rule::throw_statement => {
+                tokens.push(Token::Statement(CharParser::parse_statement(pair)));
+            }
             Rule::struct_declaration => {
                 let struct_decl = CharParser::parse_struct_declaration(pair);
                 if struct_decl.is_err() {return Err(struct_decl.err().unwrap())}
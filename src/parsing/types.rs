@@ -13,14 +13,67 @@ pub enum Token {
     Expression(ExpressionDecl),
     Type(Type),
     Struct(StructDecl),
+    Enum(EnumDecl),
+    Interface(InterfaceDecl),
+    Impl(ImplDecl),
+    Import(ImportDecl),
+    Typedef(TypedefDecl),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct StructDecl {
+    pub attributes: Vec<Attribute>,
     pub name: String,
+    pub type_parameters: Vec<String>,
     pub fields: Vec<FieldDecl>,
 }
 
+/// An annotation such as `@deprecated("use bar")` placed before a declaration
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub name: String,
+    pub arguments: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumDecl {
+    pub name: String,
+    pub type_parameters: Vec<String>,
+    pub variants: Vec<VariantDecl>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariantDecl {
+    pub name: String,
+    pub fields: Vec<FieldDecl>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceDecl {
+    pub name: String,
+    pub methods: Vec<MethodSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodSignature {
+    pub name: String,
+    pub parameters: Vec<Parameter>,
+    pub return_type: Option<Type>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImplDecl {
+    pub interface: Option<String>, // None for methods implemented directly on the type
+    pub target: String,
+    pub methods: Vec<FunctionDecl>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportDecl {
+    pub path: String,
+    pub names: Vec<String>, // empty when the whole module is imported under its namespace
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct FieldDecl {
     pub type_info: Type,
@@ -29,27 +82,45 @@ pub struct FieldDecl {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct FunctionDecl {
+    pub attributes: Vec<Attribute>,
     pub name: String,
+    pub type_parameters: Vec<String>,
     pub parameters: Vec<Parameter>,
+    pub return_type: Option<Type>,
     pub body: Vec<Token>,
+    pub generator: bool, // declared with `fn*`
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Parameter {
     pub type_info: Type,
     pub name: Option<String>,
+    pub default: Option<ExpressionDecl>, // e.g. `int x = 1`
+    pub variadic: bool, // e.g. `int ...xs`, whose type is the type of each argument
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct VariableDecl {
     pub name: String,
+    pub type_info: Option<Type>, // None for untyped declarations like `x = 1;`
     pub initializer: Option<ExpressionDecl>,
 }
 
+/// `typedef int[4] Vec4;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedefDecl {
+    pub name: String,
+    pub type_info: Type,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
-    Array(Box<Type>),
+    Array(Box<Type>, Option<Box<ExpressionDecl>>), // the size, for fixed-size arrays like int[4]
     Struct(String),
+    Generic(String, Vec<Type>), // e.g. Pair<int, string>
+    Parameter(String), // a type parameter of the enclosing function or struct
+    Tuple(Vec<Type>), // e.g. (int, string)
+    Optional(Box<Type>), // e.g. int?, which can also hold null
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -58,13 +129,36 @@ pub enum Statement {
     If(IfStatement),
     While(WhileStatement),
     For(ForStatement),
+    ForIn(ForInStatement),
     DoWhile(DoWhileStatement),
     Switch(SwitchStatement),
     Return(Option<ExpressionDecl>),
-    Break,
-    Continue,
+    Break(Option<String>), // the label of the loop to leave, the innermost loop if None
+    Continue(Option<String>),
     Expression(ExpressionDecl),
     Declaration(VariableDecl),
+    Function(Box<FunctionDecl>), // a function declared inside another function
+    Binding(BindingDecl),
+    Destructure(Pattern, ExpressionDecl), // e.g. (q, r) = divmod(7, 2);
+    Try(TryStatement),
+    Throw(ExpressionDecl),
+    Yield(ExpressionDecl),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BindingKind {
+    Const,
+    Let,
+    Var,
+}
+
+/// A `const`, `let` or `var` declaration, e.g. `const int MAX = 10;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BindingDecl {
+    pub kind: BindingKind,
+    pub type_info: Option<Type>,
+    pub name: String,
+    pub initializer: Option<ExpressionDecl>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -76,20 +170,41 @@ pub struct IfStatement {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct WhileStatement {
+    pub label: Option<String>,
     pub condition: ExpressionDecl,
     pub body: Box<Statement>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ForStatement {
+    pub label: Option<String>,
     pub initializer: Box<Statement>,
     pub condition: Option<ExpressionDecl>,
     pub increment: Option<ExpressionDecl>,
     pub body: Box<Statement>,
 }
 
+/// `for (x in items) body`, over an array, a string, a range or anything else that can be iterated
+/// `try { } catch (e) { } finally { }`, with at least one of the catch and finally blocks
+#[derive(Debug, Clone, PartialEq)]
+pub struct TryStatement {
+    pub body: Box<Statement>,
+    pub catch: Option<(String, Box<Statement>)>, // the variable the error is bound to, and the block handling it
+    pub finally: Option<Box<Statement>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForInStatement {
+    pub label: Option<String>,
+    pub type_info: Option<Type>,
+    pub variable: String,
+    pub iterable: ExpressionDecl,
+    pub body: Box<Statement>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct DoWhileStatement {
+    pub label: Option<String>,
     pub body: Box<Statement>,
     pub condition: ExpressionDecl,
 }
@@ -97,13 +212,12 @@ pub struct DoWhileStatement {
 #[derive(Debug, Clone, PartialEq)]
 pub struct SwitchStatement {
     pub expression: ExpressionDecl,
-    pub cases: Vec<CaseStatement>,
-    pub default: Option<Vec<Statement>>,
+    pub cases: Vec<CaseStatement>, // in source order, `default` included
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct CaseStatement {
-    pub value: ExpressionDecl,
+    pub value: Option<ExpressionDecl>, // None for `default`
     pub statements: Vec<Statement>,
 }
 
@@ -114,14 +228,65 @@ pub enum ExpressionDecl {
     Identifier(String),
     BinaryOp(BinaryOperator, Box<ExpressionDecl>, Box<ExpressionDecl>),
     UnaryOp(UnaryOperator, Box<ExpressionDecl>),
-    Call(Box<ExpressionDecl>, Vec<ExpressionDecl>),
+    Call(Box<ExpressionDecl>, Vec<ExpressionDecl>, usize), // with the line of the call
+    GenericCall(Box<ExpressionDecl>, Vec<Type>, Vec<ExpressionDecl>, usize), // e.g. first<int>(xs)
     Cast(Type, Box<ExpressionDecl>),
     ArrayAccess(Box<ExpressionDecl>, Box<ExpressionDecl>),
     MemberAccess(Box<ExpressionDecl>, String),
+    OptionalMemberAccess(Box<ExpressionDecl>, String), // p?.x, null when p is null
+    Try(Box<ExpressionDecl>), // r?, unwraps an Ok or returns an Err from the function
+    NamedArgument(String, Box<ExpressionDecl>), // f(x: 2)
     Assignment(Box<ExpressionDecl>, Box<ExpressionDecl>),
     Conditional(Box<ExpressionDecl>, Box<ExpressionDecl>, Box<ExpressionDecl>), // ternary operator
     ArrayLiteral(Vec<ExpressionDecl>),
     Struct(String, Vec<FieldDecl>),
+    Variant(String, String), // e.g. Shape::Circle
+    Match(MatchExpression),
+    Lambda(Box<FunctionDecl>), // e.g. fn(int x) { return x * 2; }
+    Range(RangeExpression),
+    SizeOf(SizeOfOperand),
+    Tuple(Vec<ExpressionDecl>),
+}
+
+/// What `sizeof` measures; `sizeof(a)` parses as a type and is told apart from a variable later
+#[derive(Debug, Clone, PartialEq)]
+pub enum SizeOfOperand {
+    Type(Type),
+    Expression(Box<ExpressionDecl>),
+}
+
+/// `start..end`, or `start..=end` to include the end, optionally followed by `step n`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeExpression {
+    pub start: Box<ExpressionDecl>,
+    pub end: Box<ExpressionDecl>,
+    pub step: Option<Box<ExpressionDecl>>,
+    pub inclusive: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchExpression {
+    pub scrutinee: Box<ExpressionDecl>,
+    pub arms: Vec<MatchArm>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<ExpressionDecl>,
+    pub body: ExpressionDecl,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Wildcard,
+    Binding(String),
+    Literal(Literal),
+    Variant(String, String, Vec<Pattern>), // e.g. Shape::Rect(w, _)
+    Struct(String, Vec<(String, Pattern)>), // e.g. Point { x: 0, y }
+    Array(Vec<Pattern>),
+    Rest(Option<String>), // `..` or `..rest` inside an array pattern
+    Tuple(Vec<Pattern>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -130,6 +295,7 @@ pub enum Literal {
     Float(f64),
     String(String),
     Char(char),
+    Null,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -152,6 +318,16 @@ pub enum BinaryOperator {
     // Logical
     And,
     Or,
+
+    // Null-coalescing, a ?? b
+    Coalesce,
+
+    // Bitwise
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    ShiftLeft,
+    ShiftRight,
 }
 
 #[derive(Debug, Clone, PartialEq)]